@@ -0,0 +1,82 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+
+use rsvg::svg::{BoundingBox, CoordinateSystem, Grid, Point, Rect, SVG};
+
+
+/// A grid of small rects laid out in rows, like the tiles of a generated map.
+fn tiled_grid(count: usize) -> Grid {
+    let mut grid = Grid::new(CoordinateSystem::TopLeftDownRight);
+    let columns = (count as f64).sqrt().ceil() as usize;
+
+    for i in 0..count {
+        let (x, y) = ((i % columns) as f64 * 10.0, (i / columns) as f64 * 10.0);
+        grid.add_shape(Rect::new(x, y, 8.0, 8.0).box_ptr());
+    }
+
+    grid
+}
+
+/// Hit testing by checking every shape, which is what `shapes_at` did before the spatial index.
+fn linear_shapes_at(grid: &Grid, count: usize, point: Point, tolerance: f64) -> Vec<&dyn SVG> {
+    (0..count).rev()
+        .filter_map(|i| grid.get_shape(i))
+        .filter(|shape| shape.distance_to(point) <= tolerance)
+        .collect()
+}
+
+fn shapes_at(c: &mut Criterion) {
+    let mut group = c.benchmark_group("shapes_at");
+
+    for count in [1_000, 10_000, 50_000] {
+        let grid = tiled_grid(count);
+        let point = Point { x: 104.0, y: 104.0 };
+
+        group.bench_with_input(BenchmarkId::new("indexed", count), &count, |b, _| {
+            b.iter(|| grid.shapes_at(black_box(point), 1.0).len())
+        });
+        group.bench_with_input(BenchmarkId::new("linear", count), &count, |b, &count| {
+            b.iter(|| linear_shapes_at(&grid, count, black_box(point), 1.0).len())
+        });
+    }
+
+    group.finish();
+}
+
+fn query_rect(c: &mut Criterion) {
+    let mut group = c.benchmark_group("query_rect");
+
+    for count in [1_000, 10_000, 50_000] {
+        let grid = tiled_grid(count);
+        let bounds = BoundingBox::new(100.0, 100.0, 50.0, 50.0);
+
+        group.bench_with_input(BenchmarkId::new("indexed", count), &count, |b, _| {
+            b.iter(|| grid.query_rect(black_box(bounds)).len())
+        });
+        group.bench_with_input(BenchmarkId::new("linear", count), &count, |b, &count| {
+            b.iter(|| {
+                (0..count)
+                    .filter_map(|i| grid.get_shape(i))
+                    .filter(|shape| shape.painted_bounding_box().is_some_and(|painted| painted.intersects(&bounds)))
+                    .count()
+            })
+        });
+    }
+
+    group.finish();
+}
+
+fn maintenance(c: &mut Criterion) {
+    let mut grid = tiled_grid(50_000);
+    let mut i = 0;
+
+    // Moving a shape updates its entry in the index, so edits stay cheap in large drawings
+    c.bench_function("move_shape/50000", |b| {
+        b.iter(|| {
+            i = (i + 7919) % 50_000;
+            grid.move_shape(i, (i % 300) as f64, (i % 200) as f64).unwrap();
+        })
+    });
+}
+
+criterion_group!(benches, shapes_at, query_rect, maintenance);
+criterion_main!(benches);
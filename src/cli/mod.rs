@@ -1,18 +1,20 @@
+use std::any::Any;
 use std::io::prelude::*;
 use std::io;
 
-use crate::svg::{Grid, Point, SVG, Rect, Circle, Path, Dimensions};
+use crate::svg::{Grid, Point, SVG, Rect, Circle, Ellipse, Line, Polyline, Polygon, Path, Text, Dimensions, Transform, Operation, SvgOptions};
 use crate::utils;
 
 
 /// Read a value from user input
 pub fn read_input() -> Result<String, &'static str> {
     print!("Enter Command: ");
-    io::stdout().flush().ok().expect("Could not flush stdout");
+    io::stdout().flush().expect("Could not flush stdout");
     let mut input = String::new();
     let line = io::stdin().read_line(&mut input);
 
     match line {
+        Ok(0) => Err("Reached the end of user input"),
         Ok(_) => Ok(input),
         Err(_) => Err("Failed to read user input"),
     }
@@ -21,17 +23,39 @@ pub fn read_input() -> Result<String, &'static str> {
 
 /// Parse and execute commands
 pub fn execute_command(grid: &mut Grid, cmd: String) -> Result<(), String> {
-    let args_iter : Vec<&str> = cmd.split_whitespace().collect();
-    let root_arg = args_iter[0];
-    let rest = &args_iter[1..];
+    let args = split_args(&cmd)?;
+    let args_iter : Vec<&str> = args.iter().map(String::as_str).collect();
+
+    let (root_arg, rest) = match args_iter.split_first() {
+        Some((root_arg, rest)) => (*root_arg, rest),
+        None => return Err(String::from("No command entered.")),
+    };
 
     match root_arg {
         "html" => println!("{}", grid.to_html()),
         "draw" => cmd_draw(grid, rest)?,
         "move" => cmd_move(grid, rest)?,
         "resize" => cmd_resize(grid, rest)?,
+        "style" => cmd_style(grid, rest)?,
+        "rotate" => cmd_rotate(grid, rest)?,
+        "scale" => cmd_scale(grid, rest)?,
+        "skew" => cmd_skew(grid, rest)?,
+        "bbox" => cmd_bbox(grid, rest)?,
+        "at" => cmd_at(grid, rest)?,
+        "import" => cmd_import(grid, rest)?,
+        "save" => cmd_save(grid, rest)?,
+        "load" => cmd_load(grid, rest)?,
+        "export" => cmd_export(grid, rest)?,
+        "delete" => cmd_delete(grid, rest)?,
+        "duplicate" => cmd_duplicate(grid, rest)?,
+        "raise" | "lower" | "to-front" | "to-back" => cmd_restack(grid, root_arg, rest)?,
+        "undo" => println!("Undid {}", grid.undo()?),
+        "redo" => println!("Redid {}", grid.redo()?),
+        "history" => cmd_history(grid, rest)?,
+        "close" => cmd_close(grid, rest, true)?,
+        "open" => cmd_close(grid, rest, false)?,
         _ => {
-            return Err(String::from(format!("Unable to parse command {}.", args_iter[0])));
+            return Err(format!("Unable to parse command {}.", root_arg));
         }
     }
 
@@ -39,28 +63,109 @@ pub fn execute_command(grid: &mut Grid, cmd: String) -> Result<(), String> {
 }
 
 
+/// Split a command into whitespace separated arguments.
+/// Double quotes group an argument containing whitespace, and a backslash escapes a following quote, backslash or
+/// whitespace. Any other backslash is kept as it is, so Windows paths can be typed without escaping.
+fn split_args(cmd: &str) -> Result<Vec<String>, String> {
+    let mut args = vec![];
+    let mut current: Option<String> = None;
+    let mut in_quotes = false;
+    let mut chars = cmd.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => {
+                in_quotes = !in_quotes;
+                current.get_or_insert_with(String::new);
+            },
+            '\\' => match chars.peek() {
+                Some(&escaped) if matches!(escaped, '"' | '\'' | '\\') || escaped.is_whitespace() => {
+                    current.get_or_insert_with(String::new).push(escaped);
+                    chars.next();
+                },
+                Some(_) => current.get_or_insert_with(String::new).push('\\'),
+                None => return Err(String::from("Unexpected end of command after \\")),
+            },
+            c if c.is_whitespace() && !in_quotes => {
+                if let Some(arg) = current.take() {
+                    args.push(arg);
+                }
+            },
+            c => current.get_or_insert_with(String::new).push(c),
+        }
+    }
+
+    if in_quotes {
+        return Err(String::from("Unterminated quote in command."));
+    }
+
+    if let Some(arg) = current {
+        args.push(arg);
+    }
+
+    Ok(args)
+}
+
+
 /// Handle drawing shapes
 fn cmd_draw(grid: &mut Grid, args: &[&str]) -> Result<(), String> {
     match args[..] {
-        [shape, x, y] => {
+        ["text", x, y, content] => {
             let x = utils::str_to_float(x)?;
             let y = utils::str_to_float(y)?;
 
-            let shape_idx : usize;
+            let shape_idx = grid.add_shape(Text::new(x, y, content).box_ptr());
+
+            println!("text created at index {}", shape_idx);
+        },
+        ["text", ..] => return Err(String::from("X, Y, and quoted content are required to draw text.")),
+        ["path", data] => {
+            let path = Path::from_path_data(data).map_err(|e| e.to_string())?;
+            let shape_idx = grid.add_shape(path.box_ptr());
+
+            println!("path created at index {}", shape_idx);
+        },
+        [shape @ ("line" | "polyline" | "polygon"), ref coords @ ..] if coords.len() >= 2 => {
+            let points = parse_points(coords)?;
+
+            let shape_idx = match (shape, &points[..]) {
+                ("line", [start]) => {
+                    grid.add_shape(Line::new(start.x, start.y, start.x, start.y).box_ptr())
+                },
+                ("line", [start, end]) => {
+                    grid.add_shape(Line::new(start.x, start.y, end.x, end.y).box_ptr())
+                },
+                ("line", _) => return Err(String::from("Too many arguments. Only a start and end point are required to draw line")),
+                ("polyline", _) => {
+                    grid.add_shape(Polyline::from_points(points)?.box_ptr())
+                },
+                _ => {
+                    grid.add_shape(Polygon::from_points(points)?.box_ptr())
+                },
+            };
+
+            println!("{} created at index {}", shape, shape_idx);
+        },
+        [shape, x, y] => {
+            let x = utils::str_to_float(x)?;
+            let y = utils::str_to_float(y)?;
 
-            match shape {
+            let shape_idx = match shape {
                 "rect" => {
-                    shape_idx = grid.add_shape(Rect::new(x, y, 0.0, 0.0).box_ptr());
+                    grid.add_shape(Rect::new(x, y, 0.0, 0.0).box_ptr())
                 },
                 "circle" => {
-                    shape_idx = grid.add_shape(Circle::new(x, y, 0.0).box_ptr());
+                    grid.add_shape(Circle::new(x, y, 0.0).box_ptr())
+                },
+                "ellipse" => {
+                    grid.add_shape(Ellipse::new(x, y, 0.0, 0.0).box_ptr())
                 },
                 "path" => {
                     let origin = Point{x, y};
-                    shape_idx = grid.add_shape(Path::from_points(vec![origin])?.box_ptr());
+                    grid.add_shape(Path::from_points(vec![origin])?.box_ptr())
                 },
                 _ => return Err(format!("Attampted to draw an unknown shape {}", shape)),
-            }
+            };
 
             println!("{} created at index {}", shape, shape_idx);
         },
@@ -73,21 +178,52 @@ fn cmd_draw(grid: &mut Grid, args: &[&str]) -> Result<(), String> {
 }
 
 
+/// Parse a flat list of arguments as X and Y pairs
+fn parse_points(args: &[&str]) -> Result<Vec<Point>, String> {
+    if !args.len().is_multiple_of(2) {
+        return Err(String::from("Coordinates must be given as X and Y pairs."));
+    }
+
+    args.chunks(2)
+        .map(|pair| Ok(Point {
+            x: utils::str_to_float(pair[0])?,
+            y: utils::str_to_float(pair[1])?,
+        }))
+        .collect()
+}
+
+
+/// Find the uuid of the shape an argument refers to, either `#` and the start of its uuid, or its index in the shapes group
+fn shape_uuid(grid: &Grid, arg: &str) -> Result<String, String> {
+    match arg.strip_prefix('#') {
+        Some(prefix) => grid.find_uuid(prefix),
+        None => {
+            let i = utils::str_to_usize(arg)?;
+            match grid.get_shape(i) {
+                Some(shape) => Ok(shape.get_uuid()),
+                None => Err(format!("No shape found at index {}", i)),
+            }
+        },
+    }
+}
+
+
+/// Find the shape an argument refers to
+fn shape_ref<'a>(grid: &'a Grid, arg: &str) -> Result<&'a dyn SVG, String> {
+    let uuid = shape_uuid(grid, arg)?;
+    grid.get_by_uuid(&uuid).ok_or(format!("No shape found with uuid #{}", uuid))
+}
+
+
 /// Handle Moving shapes
 fn cmd_move(grid: &mut Grid, args: &[&str]) -> Result<(), String> {
     match args[..] {
         [i, x, y] => {
-            let i = utils::str_to_usize(i)?;
             let x = utils::str_to_float(x)?;
             let y = utils::str_to_float(y)?;
 
-            let shape = grid.get_shape_mut(i);
-
-            if let Some(shape) = shape {
-                shape.as_mut().move_to(x, y);
-            } else {
-                return Err(format!("No shape found at index {}", i));
-            }
+            let uuid = shape_uuid(grid, i)?;
+            grid.move_shape_by_uuid(&uuid, x, y)?;
         },
         _ => return Err(String::from("The following values are required to move a shape: [shape_index, new_x, new_y]"))
     }
@@ -100,50 +236,482 @@ fn cmd_move(grid: &mut Grid, args: &[&str]) -> Result<(), String> {
 fn cmd_resize(grid: &mut Grid, args: &[&str]) -> Result<(), String> {
     match args[..] {
         ["rect", i, w, h] => {
-            let i = utils::str_to_usize(i)?;
             let w = utils::str_to_float(w)?;
             let h = utils::str_to_float(h)?;
 
-            let shape = grid.get_shape_mut(i);
+            let uuid = shape_uuid(grid, i)?;
+            grid.resize_shape_by_uuid(&uuid, Dimensions::Double(w, h))?;
+        },
+        ["ellipse", i, rx, ry] => {
+            let rx = utils::str_to_float(rx)?;
+            let ry = utils::str_to_float(ry)?;
 
-            if let Some(shape) = shape {
-                shape.as_mut().resize(Dimensions::Double(w, h)).unwrap();
-            } else {
-                return Err(format!("No shape found at index {}", i));
-            }
+            let uuid = shape_uuid(grid, i)?;
+            grid.resize_shape_by_uuid(&uuid, Dimensions::Double(rx, ry))?;
         },
         ["circle", i, r] => {
-            let i = utils::str_to_usize(i)?;
             let r = utils::str_to_float(r)?;
 
-            let shape = grid.get_shape_mut(i);
+            let uuid = shape_uuid(grid, i)?;
+            grid.resize_shape_by_uuid(&uuid, Dimensions::Single(r))?;
+        },
+        ["text", i, size] => {
+            let size = utils::str_to_float(size)?;
 
-            if let Some(shape) = shape {
-                shape.as_mut().resize(Dimensions::Single(r)).unwrap();
-            } else {
-                return Err(format!("No shape found at index {}", i));
-            }
+            let uuid = shape_uuid(grid, i)?;
+            grid.resize_shape_by_uuid(&uuid, Dimensions::Single(size))?;
         },
-        ["path", i, j, x, y] => {
-            let i = utils::str_to_usize(i)?;
+        ["path" | "line" | "polyline" | "polygon", i, j, x, y] => {
             let j = utils::str_to_usize(j)?;
             let x = utils::str_to_float(x)?;
             let y = utils::str_to_float(y)?;
 
-            let shape = grid.get_shape_mut(i);
-
-            if let Some(shape) = shape {
-                shape.as_mut().resize(Dimensions::IndexPosition(j, x, y)).unwrap();
-            } else {
-                return Err(format!("No shape found at index {}", i));
-            }
+            let uuid = shape_uuid(grid, i)?;
+            grid.resize_shape_by_uuid(&uuid, Dimensions::IndexPosition(j, x, y))?;
         },
         ["rect", ..] => return Err(String::from("Index, width, and height are required to resize a rect.")),
+        ["ellipse", ..] => return Err(String::from("Index, x radius, and y radius are required to resize an ellipse.")),
         ["circle", ..] => return Err(String::from("Index and radius are required to resize a circle.")),
-        ["path", ..] => return Err(String::from("Shape index, point index, new_x, and new_y are required to resize a path.")),
+        ["text", ..] => return Err(String::from("Index and font size are required to resize text.")),
+        ["path", i, j, k, x, y] => {
+            let j = utils::str_to_usize(j)?;
+            let k = utils::str_to_usize(k)?;
+            let x = utils::str_to_float(x)?;
+            let y = utils::str_to_float(y)?;
+
+            let uuid = shape_uuid(grid, i)?;
+            grid.resize_shape_by_uuid(&uuid, Dimensions::ControlPosition(j, k, x, y))?;
+        },
+        [shape @ ("path" | "line" | "polyline" | "polygon"), ..] => return Err(format!("Shape index, point index, new_x, and new_y are required to resize a {}.", shape)),
         [shape, ..] => return Err(format!("Unable to resize unknown shape {}", shape)),
         _ => return Err(String::from("A shape type, index, and new dimensions are required for resizing.")),
     }
 
     Ok(())
-}
\ No newline at end of file
+}
+
+
+/// Handle setting presentation attributes on shapes
+fn cmd_style(grid: &mut Grid, args: &[&str]) -> Result<(), String> {
+    match args[..] {
+        [i, ref attrs @ ..] if !attrs.is_empty() => {
+            let shape = shape_ref(grid, i)?;
+            let uuid = shape.get_uuid();
+
+            // Apply every attribute to a copy first, so an invalid attribute leaves the shape untouched
+            let mut style = shape.get_style().clone();
+
+            for attr in attrs {
+                match attr.split_once('=') {
+                    Some((key, value)) => style.set(key, value)?,
+                    None => return Err(format!("Expected a style attribute in the form key=value, got {}", attr)),
+                }
+            }
+
+            grid.set_style_by_uuid(&uuid, style)?;
+        },
+        _ => return Err(String::from("A shape index and at least one key=value attribute are required to style a shape.")),
+    }
+
+    Ok(())
+}
+
+
+/// Handle rotating shapes, around the origin or a given center
+fn cmd_rotate(grid: &mut Grid, args: &[&str]) -> Result<(), String> {
+    let (i, degrees, cx, cy) = match args[..] {
+        [i, degrees] => (i, degrees, "0", "0"),
+        [i, degrees, cx, cy] => (i, degrees, cx, cy),
+        _ => return Err(String::from("The following values are required to rotate a shape: [shape_index, degrees, (center_x, center_y)]")),
+    };
+
+    let degrees = utils::str_to_float(degrees)?;
+    let cx = utils::str_to_float(cx)?;
+    let cy = utils::str_to_float(cy)?;
+
+    let uuid = shape_uuid(grid, i)?;
+    grid.apply_transform_by_uuid(&uuid, Transform::rotate_about(degrees, cx, cy))?;
+
+    Ok(())
+}
+
+
+/// Print the bounding box of a shape, or of every shape if no index is given
+fn cmd_bbox(grid: &Grid, args: &[&str]) -> Result<(), String> {
+    let bounds = match args[..] {
+        [] => grid.bounding_box(),
+        [i] => shape_ref(grid, i)?.bounding_box(),
+        _ => return Err(String::from("The following values are required to get a bounding box: [(shape_index)]")),
+    };
+
+    match bounds {
+        Some(bounds) => println!("x={} y={} width={} height={}", bounds.min.x, bounds.min.y, bounds.width(), bounds.height()),
+        None => println!("Nothing to bound"),
+    }
+
+    Ok(())
+}
+
+
+/// Print the shapes under a point, topmost first
+fn cmd_at(grid: &Grid, args: &[&str]) -> Result<(), String> {
+    let (x, y, tolerance) = match args[..] {
+        [x, y] => (x, y, "0"),
+        [x, y, tolerance] => (x, y, tolerance),
+        _ => return Err(String::from("The following values are required to find shapes at a point: [x, y, (tolerance)]")),
+    };
+
+    let point = Point { x: utils::str_to_float(x)?, y: utils::str_to_float(y)? };
+    let tolerance = utils::str_to_float(tolerance)?;
+
+    for shape in grid.shapes_at(point, tolerance) {
+        println!("{}", shape.get_uuid());
+    }
+
+    Ok(())
+}
+
+
+/// Handle scaling shapes, uniformly or along each axis
+fn cmd_scale(grid: &mut Grid, args: &[&str]) -> Result<(), String> {
+    let (i, sx, sy) = match args[..] {
+        [i, s] => (i, s, s),
+        [i, sx, sy] => (i, sx, sy),
+        _ => return Err(String::from("The following values are required to scale a shape: [shape_index, scale_x, (scale_y)]")),
+    };
+
+    let sx = utils::str_to_float(sx)?;
+    let sy = utils::str_to_float(sy)?;
+
+    let uuid = shape_uuid(grid, i)?;
+    grid.apply_transform_by_uuid(&uuid, Transform::scale(sx, sy))?;
+
+    Ok(())
+}
+
+
+/// Handle skewing shapes along each axis
+fn cmd_skew(grid: &mut Grid, args: &[&str]) -> Result<(), String> {
+    let (i, x_degrees, y_degrees) = match args[..] {
+        [i, x_degrees] => (i, x_degrees, "0"),
+        [i, x_degrees, y_degrees] => (i, x_degrees, y_degrees),
+        _ => return Err(String::from("The following values are required to skew a shape: [shape_index, x_degrees, (y_degrees)]")),
+    };
+
+    let x_degrees = utils::str_to_float(x_degrees)?;
+    let y_degrees = utils::str_to_float(y_degrees)?;
+
+    let uuid = shape_uuid(grid, i)?;
+    grid.apply_transform_by_uuid(&uuid, Transform::skew(x_degrees, y_degrees))?;
+
+    Ok(())
+}
+
+
+/// Handle replacing the grid with the contents of an svg file
+fn cmd_import(grid: &mut Grid, args: &[&str]) -> Result<(), String> {
+    match args[..] {
+        [file] => {
+            let (mut imported, warnings) = Grid::from_svg_file(file)?;
+            imported.set_coordinate_system(grid.get_coordinate_system());
+
+            for warning in warnings {
+                eprintln!("Warning: {}", warning);
+            }
+
+            *grid = imported;
+            println!("Imported {}", file);
+        },
+        _ => return Err(String::from("A file path is required to import an svg document.")),
+    }
+
+    Ok(())
+}
+
+
+/// Handle deleting shapes
+fn cmd_delete(grid: &mut Grid, args: &[&str]) -> Result<(), String> {
+    match args[..] {
+        [i] => {
+            let uuid = shape_uuid(grid, i)?;
+            grid.remove_by_uuid(&uuid);
+            println!("Deleted {}", uuid);
+        },
+        _ => return Err(String::from("A shape index is required to delete a shape.")),
+    }
+
+    Ok(())
+}
+
+
+/// Handle copying shapes, optionally offsetting the copy
+fn cmd_duplicate(grid: &mut Grid, args: &[&str]) -> Result<(), String> {
+    let (i, dx, dy) = match args[..] {
+        [i] => (i, "0", "0"),
+        [i, dx, dy] => (i, dx, dy),
+        _ => return Err(String::from("The following values are required to duplicate a shape: [shape_index, (offset_x, offset_y)]")),
+    };
+
+    let uuid = shape_uuid(grid, i)?;
+    let dx = utils::str_to_float(dx)?;
+    let dy = utils::str_to_float(dy)?;
+
+    let copy = grid.duplicate_by_uuid(&uuid, dx, dy)?;
+    println!("Duplicate created with uuid {}", copy);
+
+    Ok(())
+}
+
+
+/// Handle changing the stacking order of shapes within their group
+fn cmd_restack(grid: &mut Grid, cmd: &str, args: &[&str]) -> Result<(), String> {
+    match args[..] {
+        [i] => {
+            let uuid = shape_uuid(grid, i)?;
+
+            match cmd {
+                "raise" => grid.raise(&uuid)?,
+                "lower" => grid.lower(&uuid)?,
+                "to-front" => grid.to_front(&uuid)?,
+                _ => grid.to_back(&uuid)?,
+            }
+        },
+        _ => return Err(format!("A shape index is required to {} a shape.", cmd)),
+    }
+
+    Ok(())
+}
+
+
+/// Print the operations that can be undone and redone, or change how many are kept
+fn cmd_history(grid: &mut Grid, args: &[&str]) -> Result<(), String> {
+    match args[..] {
+        [] => {
+            let history = grid.get_history();
+
+            for (i, operation) in history.done().enumerate() {
+                println!("{} {}", i + 1, operation);
+            }
+            for operation in history.undone() {
+                println!("(undone) {}", operation);
+            }
+        },
+        ["depth", depth] => grid.set_history_depth(utils::str_to_usize(depth)?),
+        _ => return Err(String::from("History takes no arguments, or depth and the number of operations to keep.")),
+    }
+
+    Ok(())
+}
+
+
+/// Handle saving the drawing as json
+fn cmd_save(grid: &Grid, args: &[&str]) -> Result<(), String> {
+    match args[..] {
+        [file] => {
+            grid.save_json_file(file)?;
+            println!("Saved {}", file);
+        },
+        _ => return Err(String::from("A file path is required to save the drawing.")),
+    }
+
+    Ok(())
+}
+
+
+/// Handle replacing the grid with a drawing saved as json
+fn cmd_load(grid: &mut Grid, args: &[&str]) -> Result<(), String> {
+    match args[..] {
+        [file] => {
+            *grid = Grid::from_json_file(file)?;
+            println!("Loaded {}", file);
+        },
+        _ => return Err(String::from("A file path is required to load a drawing.")),
+    }
+
+    Ok(())
+}
+
+
+/// Handle exporting the drawing as a standalone svg document, with an optional title and description
+fn cmd_export(grid: &Grid, args: &[&str]) -> Result<(), String> {
+    let (file, options) = match args[..] {
+        [file] => (file, SvgOptions::new()),
+        [file, title] => (file, SvgOptions::new().with_title(title)),
+        [file, title, description] => (file, SvgOptions::new().with_title(title).with_description(description)),
+        _ => return Err(String::from("A file path is required to export the drawing, optionally followed by a title and description.")),
+    };
+
+    grid.save_svg_file(file, &options)?;
+    println!("Exported {}", file);
+
+    Ok(())
+}
+
+
+/// Handle closing and opening subpaths of a path
+fn cmd_close(grid: &mut Grid, args: &[&str], closed: bool) -> Result<(), String> {
+    match args[..] {
+        [i, j] => {
+            let j = utils::str_to_usize(j)?;
+
+            let before = shape_ref(grid, i)?.clone_box();
+            let mut after = before.clone_box();
+            let shape: &mut dyn Any = after.as_mut();
+
+            match shape.downcast_mut::<Path>() {
+                Some(path) => path.set_closed(j, closed)?,
+                None => return Err(format!("Shape {} is not a path", i)),
+            }
+
+            // Closing a subpath changes the shape's outline, much like moving one of its points
+            grid.perform(Operation::Resize { before, after })?;
+        },
+        _ => return Err(String::from("A path index and subpath index are required to close or open a subpath.")),
+    }
+
+    Ok(())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verify `split_args` splits on whitespace, and keeps quoted arguments together.
+    #[test]
+    fn split_args_quotes() {
+        assert_eq!(split_args("  draw  circle 1 2\n").unwrap(), vec!["draw", "circle", "1", "2"]);
+        assert_eq!(split_args("draw text 1 2 \"Hello, world\"").unwrap(), vec!["draw", "text", "1", "2", "Hello, world"]);
+        assert_eq!(split_args("draw text 1 2 \"\"").unwrap(), vec!["draw", "text", "1", "2", ""]);
+        assert_eq!(split_args("a\" b \"c").unwrap(), vec!["a b c"]);
+        assert_eq!(split_args("").unwrap(), Vec::<String>::new());
+    }
+
+    /// Verify `split_args` handles escaped characters, and rejects unterminated quotes and escapes.
+    #[test]
+    fn split_args_escapes() {
+        assert_eq!(split_args("\"say \\\"hi\\\"\" a\\ b").unwrap(), vec!["say \"hi\"", "a b"]);
+        assert_eq!(split_args("it\\'s a\\\\b").unwrap(), vec!["it's", "a\\b"]);
+
+        // Backslashes before anything else are kept, so Windows paths need no escaping
+        assert_eq!(split_args("import C:\\drawings\\a.svg").unwrap(), vec!["import", "C:\\drawings\\a.svg"]);
+        assert_eq!(split_args("save \"C:\\my drawings\\x.json\"").unwrap(), vec!["save", "C:\\my drawings\\x.json"]);
+
+        let err = split_args("draw text 1 2 \"oops").unwrap_err();
+        assert_eq!(err, "Unterminated quote in command.");
+        let err = split_args("draw \\").unwrap_err();
+        assert_eq!(err, "Unexpected end of command after \\");
+    }
+
+    /// Verify shapes can be referred to by index or by a unique uuid prefix.
+    #[test]
+    fn shape_references() {
+        let mut grid = Grid::new(crate::svg::CoordinateSystem::TopLeftDownRight);
+        grid.add_shape(Rect::new(0.0, 0.0, 1.0, 1.0).box_ptr());
+        grid.add_shape(Circle::new(0.0, 0.0, 1.0).box_ptr());
+        let uuid = grid.get_shape(1).unwrap().get_uuid();
+
+        assert_eq!(shape_ref(&grid, "1").unwrap().get_uuid(), uuid);
+        assert_eq!(shape_ref(&grid, &format!("#{}", &uuid[..8])).unwrap().get_uuid(), uuid);
+        assert_eq!(shape_uuid(&grid, "2").unwrap_err(), "No shape found at index 2");
+        assert_eq!(shape_uuid(&grid, "#").unwrap_err(), "Uuid # is ambiguous, it matches 2 shapes");
+
+        execute_command(&mut grid, format!("move #{} 5 6", uuid)).unwrap();
+        assert_eq!(grid.get_shape(1).unwrap().bounding_box().unwrap().min, Point{ x: 4.0, y: 5.0 });
+        let err = execute_command(&mut grid, format!("close #{} 0", uuid)).unwrap_err();
+        assert_eq!(err, format!("Shape #{} is not a path", uuid));
+    }
+
+    /// Verify the delete, duplicate and restacking commands.
+    #[test]
+    fn edit_commands() {
+        let mut grid = Grid::new(crate::svg::CoordinateSystem::TopLeftDownRight);
+        grid.add_shape(Rect::new(0.0, 0.0, 1.0, 1.0).box_ptr());
+        grid.add_shape(Circle::new(0.0, 0.0, 1.0).box_ptr());
+        let first = grid.get_shape(0).unwrap().get_uuid();
+
+        execute_command(&mut grid, String::from("to-front 0")).unwrap();
+        assert_eq!(grid.get_shape(1).unwrap().get_uuid(), first);
+        execute_command(&mut grid, format!("lower #{}", &first[..8])).unwrap();
+        assert_eq!(grid.get_shape(0).unwrap().get_uuid(), first);
+
+        execute_command(&mut grid, String::from("duplicate 0 5 5")).unwrap();
+        assert_eq!(grid.get_shape(1).unwrap().bounding_box().unwrap().min, Point{ x: 5.0, y: 5.0 });
+        assert_ne!(grid.get_shape(1).unwrap().get_uuid(), first);
+
+        execute_command(&mut grid, String::from("delete 0")).unwrap();
+        assert!(grid.get_by_uuid(&first).is_none());
+        assert_eq!(execute_command(&mut grid, String::from("delete 2")).unwrap_err(), "No shape found at index 2");
+        assert_eq!(execute_command(&mut grid, String::from("raise")).unwrap_err(), "A shape index is required to raise a shape.");
+    }
+
+    /// Verify edits made through commands can be undone and redone.
+    #[test]
+    fn undo_commands() {
+        let mut grid = Grid::new(crate::svg::CoordinateSystem::TopLeftDownRight);
+        let empty = grid.to_html();
+        execute_command(&mut grid, String::from("draw circle 5 5")).unwrap();
+        execute_command(&mut grid, String::from("resize circle 0 3")).unwrap();
+        let drawn = grid.to_html();
+
+        for cmd in ["style 0 fill=red", "rotate 0 45", "scale 0 2", "skew 0 10", "move 0 1 1", "duplicate 0", "delete 0"] {
+            execute_command(&mut grid, String::from(cmd)).unwrap();
+        }
+        for _ in 0..7 {
+            execute_command(&mut grid, String::from("undo")).unwrap();
+        }
+        assert_eq!(grid.to_html(), drawn);
+
+        execute_command(&mut grid, String::from("redo")).unwrap();
+        assert_eq!(grid.get_shape(0).unwrap().get_style().fill.as_deref(), Some("red"));
+
+        execute_command(&mut grid, String::from("history depth 1")).unwrap();
+        execute_command(&mut grid, String::from("history")).unwrap();
+        execute_command(&mut grid, String::from("undo")).unwrap();
+        assert_eq!(execute_command(&mut grid, String::from("undo")).unwrap_err(), "Nothing to undo");
+        assert_ne!(grid.to_html(), empty);
+    }
+
+    /// Verify a drawing saved to a file loads back the same, replacing the current grid.
+    #[test]
+    fn save_and_load() {
+        let path = std::env::temp_dir().join(format!("rsvg-{}.json", utils::gen_uuid()));
+        let path = path.to_str().unwrap();
+
+        let mut grid = Grid::new(crate::svg::CoordinateSystem::BottomLeftUpRight);
+        execute_command(&mut grid, String::from("draw path \"M 0 0 C 1 2 3 4 5 6 Z\"")).unwrap();
+        execute_command(&mut grid, String::from("draw text 1 2 \"Hi\"")).unwrap();
+        execute_command(&mut grid, format!("save {}", path)).unwrap();
+        let saved = grid.to_html();
+
+        let mut loaded = Grid::new(crate::svg::CoordinateSystem::TopLeftDownRight);
+        execute_command(&mut loaded, format!("load {}", path)).unwrap();
+        std::fs::remove_file(path).unwrap();
+        assert_eq!(loaded.to_html(), saved);
+        assert_eq!(loaded.get_coordinate_system(), crate::svg::CoordinateSystem::BottomLeftUpRight);
+
+        let err = execute_command(&mut loaded, format!("load {}", path)).unwrap_err();
+        assert!(err.starts_with(&format!("Unable to read {}", path)));
+    }
+
+    /// Verify exported documents can be imported again, and carry the title and description given.
+    #[test]
+    fn export() {
+        let path = std::env::temp_dir().join(format!("rsvg-{}.svg", utils::gen_uuid()));
+        let path = path.to_str().unwrap();
+
+        let mut grid = Grid::new(crate::svg::CoordinateSystem::TopLeftDownRight);
+        execute_command(&mut grid, String::from("draw rect 1 2")).unwrap();
+        execute_command(&mut grid, format!("export {} \"Floor plan\" \"Ground floor\"", path)).unwrap();
+        let source = std::fs::read_to_string(path).unwrap();
+        assert!(source.contains("<title>Floor plan</title>\n<desc>Ground floor</desc>"));
+
+        let mut imported = Grid::new(crate::svg::CoordinateSystem::TopLeftDownRight);
+        execute_command(&mut imported, format!("import {}", path)).unwrap();
+        std::fs::remove_file(path).unwrap();
+        assert_eq!(imported.content_bounding_box(), grid.content_bounding_box());
+
+        let err = execute_command(&mut grid, String::from("export")).unwrap_err();
+        assert_eq!(err, "A file path is required to export the drawing, optionally followed by a title and description.");
+    }
+}
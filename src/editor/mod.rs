@@ -1,142 +1,131 @@
-use crate::svg::{Point, SVG, Rect};
+use crate::svg::{Point, SVG};
 
+mod select;
+mod reshape;
+mod session;
+mod tools;
 
-trait UserInteraction {
-    type Arg;
-    type Result;
-
-    fn mouse_down(&mut self, point: Point, item: Option<&mut Self::Arg>) -> Option<Self::Result>;
-    fn mouse_move(&mut self, point: Point, item: Option<&mut Self::Arg>) -> Option<Self::Result>;
-    fn mouse_up(&mut self, point: Point, item: Option<&mut Self::Arg>) -> Option<Self::Result>;
-}
+pub use select::{SelectTool, SelectionChange, BandMode};
+pub use reshape::{MoveTool, ResizeTool, Handle};
+pub use session::{Editor, Tool, DrawKind};
+pub use tools::{DragTool, DragShape, RectTool, CircleTool, EllipseTool, LineTool, PenTool};
 
 
-enum DrawState {
-    Waiting,
-    Ready,
-    Drawing,
+/// Keys held down during a pointer event.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Modifiers {
+    pub shift: bool,
+    pub alt: bool,
+    pub ctrl: bool,
 }
 
-// Waiting
-// Click -> Ready
-// Move -> Drawing
-// Up -> Waiting
-
-// Click... On first mouse_mouse RectTool should create a shape, in the Grid
-// On subsequent mouse moves, the RectTool should resize the shape?
-// On mouseup, Finish resizes
-// 
-// I think we also  want a separate "ResizeTool" though, but maybe that does that same thing, except on mouse_down
-// it expects to be given a Shape, instead of preparing to create a shape like the RectTool
-// 
-// MoveTool will be similar - expect to be given a shape on mousedown
-
-
-trait DrawTool: UserInteraction {
-    type Shape;
-
-    fn create_shape(&self) -> Self::Shape;
+impl Modifiers {
+    ///
+    pub const NONE: Modifiers = Modifiers { shift: false, alt: false, ctrl: false };
+    ///
+    pub const SHIFT: Modifiers = Modifiers { shift: true, alt: false, ctrl: false };
 }
 
+/// The keys tools respond to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Key {
+    Escape,
+}
 
-pub struct RectTool {
-    origin: Option<Point>,
-    state: DrawState,
+/// Something the user did with the mouse, at a point in the grid's coordinates, or with the keyboard.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PointerEvent {
+    MouseDown(Point, Modifiers),
+    MouseMove(Point, Modifiers),
+    MouseUp(Point, Modifiers),
+    /// Sent after the mouse down and up events of the second click
+    DoubleClick(Point, Modifiers),
+    KeyDown(Key),
 }
 
-impl RectTool {
-    pub fn new() -> RectTool {
-        RectTool{
-            origin: None,
-            state: DrawState::Waiting,
+impl PointerEvent {
+    /// Where the event happened, unless it came from the keyboard.
+    pub fn point(&self) -> Option<Point> {
+        match self {
+            PointerEvent::MouseDown(point, _)
+            | PointerEvent::MouseMove(point, _)
+            | PointerEvent::MouseUp(point, _)
+            | PointerEvent::DoubleClick(point, _) => Some(*point),
+            PointerEvent::KeyDown(_) => None,
         }
     }
 }
 
-impl UserInteraction for RectTool {
-    type Arg = Rect;
-    type Result = Rect;
 
-    fn mouse_down(&mut self, point: Point, _: Option<&mut Self::Arg>) -> Option<Self::Result> {
-        self.origin = Some(point);
+/// Something that responds to the mouse and keyboard, such as a tool. Each event may be given the item being worked on,
+/// and may produce a result, such as a newly drawn shape.
+pub trait UserInteraction {
+    type Arg;
+    type Result;
 
-        if let DrawState::Waiting = self.state {
-            self.state = DrawState::Ready;
-        }
+    fn mouse_down(&mut self, point: Point, modifiers: Modifiers, item: Option<&mut Self::Arg>) -> Option<Self::Result>;
+    fn mouse_move(&mut self, point: Point, modifiers: Modifiers, item: Option<&mut Self::Arg>) -> Option<Self::Result>;
+    fn mouse_up(&mut self, point: Point, modifiers: Modifiers, item: Option<&mut Self::Arg>) -> Option<Self::Result>;
 
+    ///
+    fn double_click(&mut self, _point: Point, _modifiers: Modifiers, _item: Option<&mut Self::Arg>) -> Option<Self::Result> {
         None
     }
 
-    fn mouse_move(&mut self, point: Point, _: Option<&mut Self::Arg>) -> Option<Self::Result> {
-        match self.state {
-            DrawState::Ready => {
-                self.state = DrawState::Drawing;
-                Some(self.create_shape())
-            },
-            DrawState::Drawing => {
-                self.state = DrawState::Drawing;
-                Some(self.create_shape())
-            },
-            _ => None
-        }
-    }
-
-    fn mouse_up(&mut self, point: Point, _: Option<&mut Self::Arg>) -> Option<Self::Result> {
+    ///
+    fn key_down(&mut self, _key: Key, _item: Option<&mut Self::Arg>) -> Option<Self::Result> {
         None
     }
-}
-
-impl DrawTool for RectTool {
-    type Shape = Rect;
-
-    fn draw(&self) {
 
+    /// Pass `event` to the method handling it.
+    fn handle(&mut self, event: PointerEvent, item: Option<&mut Self::Arg>) -> Option<Self::Result> {
+        match event {
+            PointerEvent::MouseDown(point, modifiers) => self.mouse_down(point, modifiers, item),
+            PointerEvent::MouseMove(point, modifiers) => self.mouse_move(point, modifiers, item),
+            PointerEvent::MouseUp(point, modifiers) => self.mouse_up(point, modifiers, item),
+            PointerEvent::DoubleClick(point, modifiers) => self.double_click(point, modifiers, item),
+            PointerEvent::KeyDown(key) => self.key_down(key, item),
+        }
     }
 }
 
 
-fn calc<T>(a: RectTool) {
-    println!("hey!")
-}
-
-fn stuff() {
-    let x = RectTool::<Rect>{ shape: None };
-    calc(x);
+/// Where a drawing tool is in drawing a shape.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DrawState {
+    Waiting,
+    Ready,
+    Drawing,
 }
 
+// Waiting
+// Click -> Ready
+// Move -> Drawing
+// Up -> Waiting
 
+// Click... On first mouse_move RectTool should create a shape, in the Grid
+// On subsequent mouse moves, the RectTool should resize the shape?
+// On mouseup, Finish resizes
+// 
+// I think we also  want a separate "ResizeTool" though, but maybe that does that same thing, except on mouse_down
+// it expects to be given a Shape, instead of preparing to create a shape like the RectTool
+// 
+// MoveTool will be similar - expect to be given a shape on mousedown
 
-// struct Editor {
-//     tool: Optional<Tool>,
-// }
-
-// impl Editor {
-//     fn new() -> Editor {
-//         Editor { tool: None }
-//     }
-
-//     fn activate_tool(&self, t: Tool) {
-//         self.tool = t;
-//     }
-
-//     fn handle_interaction(&self, event: UserInteraction) {
-//         match self.tool {
-
-//         }
-//     }
-// }
 
+/// A tool that draws a new shape. The shape is created as soon as there is something to show, such as on the first move
+/// after the mouse goes down, and given back to the tool as the event's item on later events to be reshaped,
+/// until the tool stops drawing. Events without an item produce a new shape instead, for callers that don't keep
+/// the one being drawn.
+pub trait DrawTool: UserInteraction {
+    type Shape: SVG;
 
-// enum UserInteraction {
-//     MouseDown(point: Point),
-//     MouseMove(point: Point),
-//     MouseUp(point: Point),
-// }
+    /// A new shape from what has been drawn so far, if drawing has started.
+    fn create_shape(&self) -> Option<Self::Shape>;
 
+    /// Reshape a shape made by `create_shape` to match what has been drawn so far.
+    fn update_shape(&self, shape: &mut Self::Shape);
 
-// enum Tool {
-//     Select,
-//     Move,
-//     Draw,
-//     Resize,
-// }
\ No newline at end of file
+    ///
+    fn is_drawing(&self) -> bool;
+}
@@ -0,0 +1,594 @@
+use std::any::Any;
+
+use crate::svg::{Point, SVG, BoundingBox, Transform, Dimensions, Operation, Rect, Circle, Ellipse, Group, Path, Segment};
+use super::{UserInteraction, Modifiers, Key};
+
+
+/// A handle dragged to resize a shape. The eight box handles sit on the corners and edge midpoints of the shape's
+/// bounding box in its own coordinates, and are named as they appear when y points down.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Handle {
+    TopLeft,
+    Top,
+    TopRight,
+    Right,
+    BottomRight,
+    Bottom,
+    BottomLeft,
+    Left,
+    /// The point at this index of a path
+    Point(usize),
+}
+
+/// Where a box handle sits along one axis of the bounding box.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Side {
+    Min,
+    Mid,
+    Max,
+}
+
+impl Handle {
+    ///
+    const BOX: [Handle; 8] = [
+        Handle::TopLeft,
+        Handle::Top,
+        Handle::TopRight,
+        Handle::Right,
+        Handle::BottomRight,
+        Handle::Bottom,
+        Handle::BottomLeft,
+        Handle::Left,
+    ];
+
+    /// Where a box handle sits along the x and y axes.
+    fn sides(&self) -> Option<(Side, Side)> {
+        match self {
+            Handle::TopLeft => Some((Side::Min, Side::Min)),
+            Handle::Top => Some((Side::Mid, Side::Min)),
+            Handle::TopRight => Some((Side::Max, Side::Min)),
+            Handle::Right => Some((Side::Max, Side::Mid)),
+            Handle::BottomRight => Some((Side::Max, Side::Max)),
+            Handle::Bottom => Some((Side::Mid, Side::Max)),
+            Handle::BottomLeft => Some((Side::Min, Side::Max)),
+            Handle::Left => Some((Side::Min, Side::Mid)),
+            Handle::Point(_) => None,
+        }
+    }
+}
+
+
+/// Moves the shape it is given when the mouse goes down, following the mouse until it goes up, when the move is given
+/// back as an operation to record. The shape is placed with `SVG::move_to`, always from where it was when the mouse
+/// went down, so rounding doesn't build up. Escape puts it back.
+pub struct MoveTool {
+    parent: Transform,
+    // Where the mouse went down, and the shape as it was then
+    press: Option<(Point, Box<dyn SVG>)>,
+}
+
+impl MoveTool {
+    /// A move tool for a shape whose parent's coordinates become the grid's through `parent`,
+    /// as given by `Grid::parent_transform`.
+    pub fn new(parent: Transform) -> MoveTool {
+        MoveTool { parent, press: None }
+    }
+
+    ///
+    pub fn is_moving(&self) -> bool {
+        self.press.is_some()
+    }
+
+    /// The shape moved by the offset from `origin` to `point`, which are in the grid's coordinates.
+    fn moved(&self, before: &dyn SVG, origin: Point, point: Point) -> Option<Box<dyn SVG>> {
+        let to_shape = before.get_transform().then(&self.parent).inverse()?;
+        let (dx, dy) = to_shape.apply_vector(point.x - origin.x, point.y - origin.y);
+        let anchor = anchor(before)?;
+
+        let mut after = before.clone_box();
+        after.move_to(anchor.x + dx, anchor.y + dy);
+        Some(after)
+    }
+}
+
+impl Default for MoveTool {
+    fn default() -> Self {
+        MoveTool::new(Transform::identity())
+    }
+}
+
+impl UserInteraction for MoveTool {
+    type Arg = Box<dyn SVG>;
+    type Result = Operation;
+
+    ///
+    fn mouse_down(&mut self, point: Point, _: Modifiers, item: Option<&mut Self::Arg>) -> Option<Self::Result> {
+        self.press = item.map(|shape| (point, shape.clone_box()));
+        None
+    }
+
+    ///
+    fn mouse_move(&mut self, point: Point, _: Modifiers, item: Option<&mut Self::Arg>) -> Option<Self::Result> {
+        if let (Some((origin, before)), Some(item)) = (&self.press, item) {
+            if let Some(after) = self.moved(before.as_ref(), *origin, point) {
+                *item = after;
+            }
+        }
+        None
+    }
+
+    /// Finish the move, which is only worth recording if the mouse went up somewhere other than where it went down.
+    fn mouse_up(&mut self, point: Point, _: Modifiers, item: Option<&mut Self::Arg>) -> Option<Self::Result> {
+        let (origin, before) = self.press.take()?;
+        let item = item?;
+
+        if let Some(after) = self.moved(before.as_ref(), origin, point) {
+            *item = after;
+        }
+
+        if point == origin {
+            return None;
+        }
+        Some(Operation::Move { before, after: item.clone_box() })
+    }
+
+    ///
+    fn key_down(&mut self, key: Key, item: Option<&mut Self::Arg>) -> Option<Self::Result> {
+        if let (Key::Escape, Some((_, before)), Some(item)) = (key, self.press.take(), item) {
+            *item = before;
+        }
+        None
+    }
+}
+
+
+/// Resizes the shape it is given when the mouse goes down on one of its handles, following the mouse until it goes up,
+/// when the resize is given back as an operation to record. Box handles scale the shape away from the opposite side,
+/// or from its centre with alt held, and shift keeps its proportions. Circles always keep theirs. Point handles move a
+/// single point of a path.
+///
+/// Shapes are resized with `SVG::resize`, always from how they were when the mouse went down. Shapes made of points
+/// have each point moved, so the arcs of a path keep their radii. Escape puts the shape back.
+pub struct ResizeTool {
+    parent: Transform,
+    tolerance: f64,
+    grab: Option<Grab>,
+}
+
+/// A handle being dragged, where the mouse went down on it and the shape as it was then.
+struct Grab {
+    handle: Handle,
+    origin: Point,
+    before: Box<dyn SVG>,
+}
+
+impl ResizeTool {
+    /// A resize tool picking handles within `tolerance` of the mouse, for a shape whose parent's coordinates become the
+    /// grid's through `parent`, as given by `Grid::parent_transform`.
+    pub fn new(parent: Transform, tolerance: f64) -> ResizeTool {
+        ResizeTool { parent, tolerance, grab: None }
+    }
+
+    /// The handle being dragged.
+    pub fn get_handle(&self) -> Option<Handle> {
+        self.grab.as_ref().map(|grab| grab.handle)
+    }
+
+    /// Where the handles of `shape` are, in the grid's coordinates. A path's point handles come first, and are picked
+    /// over box handles in the same place. Shapes with no size, such as text, have no handles.
+    pub fn handles(&self, shape: &dyn SVG) -> Vec<(Handle, Point)> {
+        let bounds = match local_bounds(shape) {
+            Some(bounds) if bounds.width() > 0.0 || bounds.height() > 0.0 => bounds,
+            _ => return vec![],
+        };
+        let to_grid = shape.get_transform().then(&self.parent);
+
+        let shape_any: &dyn Any = shape;
+        let mut handles = if shape_any.is::<Path>() {
+            shape.get_points().into_iter().enumerate().map(|(i, at)| (Handle::Point(i), to_grid.apply(at))).collect()
+        } else {
+            vec![]
+        };
+
+        handles.extend(Handle::BOX.iter().filter_map(|handle| {
+            let (x_side, y_side) = handle.sides()?;
+            let at = Point { x: side_at(x_side, bounds.min.x, bounds.max.x), y: side_at(y_side, bounds.min.y, bounds.max.y) };
+            Some((*handle, to_grid.apply(at)))
+        }));
+
+        handles
+    }
+
+    /// The handle of `shape` nearest `point`, if it is within the tolerance. Of handles equally near, the first wins.
+    fn handle_at(&self, shape: &dyn SVG, point: Point) -> Option<Handle> {
+        self.handles(shape).into_iter()
+            .map(|(handle, at)| (handle, (at.x - point.x).hypot(at.y - point.y)))
+            .filter(|(_, distance)| *distance <= self.tolerance)
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(handle, _)| handle)
+    }
+
+    /// The grabbed shape with its handle dragged to `point`, in the grid's coordinates.
+    fn resized(&self, grab: &Grab, point: Point, modifiers: Modifiers) -> Result<Box<dyn SVG>, String> {
+        let before = grab.before.as_ref();
+        let to_shape = before.get_transform().then(&self.parent).inverse()
+            .ok_or_else(|| String::from("Cannot resize a shape with a transform that can't be inverted"))?;
+        let (dx, dy) = to_shape.apply_vector(point.x - grab.origin.x, point.y - grab.origin.y);
+        let mut after = before.clone_box();
+
+        match (grab.handle, grab.handle.sides()) {
+            (Handle::Point(i), _) => {
+                let at = before.get_points().get(i).copied()
+                    .ok_or_else(|| format!("Cannot resize shape because point does not exist at index {}", i))?;
+                after.resize(Dimensions::IndexPosition(i, at.x + dx, at.y + dy))?;
+            },
+            (_, Some(sides)) => {
+                let bounds = local_bounds(before).ok_or_else(|| String::from("Cannot resize a shape with no size"))?;
+                let before_any: &dyn Any = before;
+                let keep_ratio = modifiers.shift || before_any.is::<Circle>();
+                let scale = box_scale(&bounds, sides, (dx, dy), keep_ratio, modifiers.alt);
+
+                scale_shape(after.as_mut(), &bounds, &scale)?;
+            },
+            (_, None) => {},
+        }
+
+        Ok(after)
+    }
+}
+
+impl UserInteraction for ResizeTool {
+    type Arg = Box<dyn SVG>;
+    type Result = Operation;
+
+    /// Grab the handle under the mouse, if there is one.
+    fn mouse_down(&mut self, point: Point, _: Modifiers, item: Option<&mut Self::Arg>) -> Option<Self::Result> {
+        self.grab = item.and_then(|shape| {
+            let handle = self.handle_at(shape.as_ref(), point)?;
+            Some(Grab { handle, origin: point, before: shape.clone_box() })
+        });
+        None
+    }
+
+    /// Follow the mouse. Sizes the shape can't take, such as a group with no width, leave it as it last was.
+    fn mouse_move(&mut self, point: Point, modifiers: Modifiers, item: Option<&mut Self::Arg>) -> Option<Self::Result> {
+        if let (Some(grab), Some(item)) = (&self.grab, item) {
+            if let Ok(after) = self.resized(grab, point, modifiers) {
+                *item = after;
+            }
+        }
+        None
+    }
+
+    /// Finish the resize, which is only worth recording if the mouse went up somewhere other than where it went down.
+    fn mouse_up(&mut self, point: Point, modifiers: Modifiers, item: Option<&mut Self::Arg>) -> Option<Self::Result> {
+        let grab = self.grab.take()?;
+        let item = item?;
+
+        if let Ok(after) = self.resized(&grab, point, modifiers) {
+            *item = after;
+        }
+
+        if point == grab.origin {
+            return None;
+        }
+        Some(Operation::Resize { before: grab.before, after: item.clone_box() })
+    }
+
+    ///
+    fn key_down(&mut self, key: Key, item: Option<&mut Self::Arg>) -> Option<Self::Result> {
+        if let (Key::Escape, Some(grab), Some(item)) = (key, self.grab.take(), item) {
+            *item = grab.before;
+        }
+        None
+    }
+}
+
+
+/// The bounding box of `shape` in its own coordinates, before its transform.
+fn local_bounds(shape: &dyn SVG) -> Option<BoundingBox> {
+    shape.bounding_box_in(&shape.get_transform().inverse()?)
+}
+
+/// Where `move_to` has to put `shape` to leave it where it is, in the shape's own coordinates. Shapes are placed by
+/// different points, such as a rect's corner or a circle's centre, so it is found from where a copy moved to the origin
+/// ends up.
+fn anchor(shape: &dyn SVG) -> Option<Point> {
+    let mut probe = shape.clone_box();
+    probe.move_to(0.0, 0.0);
+
+    let (at, moved) = (local_bounds(shape)?, local_bounds(probe.as_ref())?);
+    Some(Point { x: at.min.x - moved.min.x, y: at.min.y - moved.min.y })
+}
+
+///
+fn side_at(side: Side, min: f64, max: f64) -> f64 {
+    match side {
+        Side::Min => min,
+        Side::Mid => (min + max) / 2.0,
+        Side::Max => max,
+    }
+}
+
+/// The scaling of `bounds` that drags the handle on `sides` by `delta`, all in the shape's own coordinates.
+/// Keeping the ratio scales both axes by whichever scales more, or by the only one a side handle scales.
+fn box_scale(bounds: &BoundingBox, sides: (Side, Side), delta: (f64, f64), keep_ratio: bool, from_centre: bool) -> Transform {
+    let (x_anchor, x_scale) = axis_scale(bounds.min.x, bounds.max.x, sides.0, delta.0, from_centre);
+    let (y_anchor, y_scale) = axis_scale(bounds.min.y, bounds.max.y, sides.1, delta.1, from_centre);
+
+    let (sx, sy) = match (x_scale, y_scale) {
+        (Some(sx), Some(sy)) if keep_ratio => {
+            let s = if sx.abs() >= sy.abs() { sx } else { sy };
+            (s, s)
+        },
+        (Some(s), None) | (None, Some(s)) if keep_ratio => (s, s),
+        (sx, sy) => (sx.unwrap_or(1.0), sy.unwrap_or(1.0)),
+    };
+
+    Transform::translate(-x_anchor, -y_anchor)
+        .then(&Transform::scale(sx, sy))
+        .then(&Transform::translate(x_anchor, y_anchor))
+}
+
+/// The position along one axis that stays put, and the scale along it, when the handle on `side` is dragged by `delta`.
+/// Handles in the middle of the axis, and boxes with no extent along it, don't scale it.
+fn axis_scale(min: f64, max: f64, side: Side, delta: f64, from_centre: bool) -> (f64, Option<f64>) {
+    let mid = (min + max) / 2.0;
+    let (edge, anchor) = match side {
+        Side::Min => (min, if from_centre { mid } else { max }),
+        Side::Max => (max, if from_centre { mid } else { min }),
+        Side::Mid => return (mid, None),
+    };
+
+    if edge == anchor {
+        return (anchor, None);
+    }
+    (anchor, Some((edge + delta - anchor) / (edge - anchor)))
+}
+
+/// Scale `shape` by `scale`, in its own coordinates, with `SVG::resize`. Shapes made of points have each point moved,
+/// and other shapes are given the dimensions of their scaled bounding box, then moved into it.
+fn scale_shape(shape: &mut dyn SVG, bounds: &BoundingBox, scale: &Transform) -> Result<(), String> {
+    let points = shape.get_points();
+
+    if !points.is_empty() {
+        for (i, point) in points.into_iter().enumerate() {
+            let point = scale.apply(point);
+            shape.resize(Dimensions::IndexPosition(i, point.x, point.y))?;
+        }
+        for (i, j, control) in control_points(shape) {
+            let control = scale.apply(control);
+            shape.resize(Dimensions::ControlPosition(i, j, control.x, control.y))?;
+        }
+
+        return Ok(());
+    }
+
+    let target = BoundingBox::from_points([scale.apply(bounds.min), scale.apply(bounds.max)])
+        .ok_or_else(|| String::from("Cannot resize a shape with no size"))?;
+    shape.resize(dimensions(shape, &target)?)?;
+
+    if let (Some(anchor), Some(landed)) = (anchor(shape), local_bounds(shape)) {
+        shape.move_to(anchor.x + target.min.x - landed.min.x, anchor.y + target.min.y - landed.min.y);
+    }
+
+    Ok(())
+}
+
+/// The dimensions giving `shape` the size of `target`.
+fn dimensions(shape: &dyn SVG, target: &BoundingBox) -> Result<Dimensions, String> {
+    let shape: &dyn Any = shape;
+
+    if shape.is::<Rect>() || shape.is::<Group>() {
+        Ok(Dimensions::Double(target.width(), target.height()))
+    } else if shape.is::<Ellipse>() {
+        Ok(Dimensions::Double(target.width() / 2.0, target.height() / 2.0))
+    } else if shape.is::<Circle>() {
+        Ok(Dimensions::Single(target.width().max(target.height()) / 2.0))
+    } else {
+        Err(String::from("Only rects, circles, ellipses, groups and shapes made of points can be resized by their handles"))
+    }
+}
+
+/// The control points of a path's curves, with the index of the point each curve ends at and the control point's
+/// index within its curve, as `Dimensions::ControlPosition` takes them.
+fn control_points(shape: &dyn SVG) -> Vec<(usize, usize, Point)> {
+    let shape: &dyn Any = shape;
+    let path = match shape.downcast_ref::<Path>() {
+        Some(path) => path,
+        None => return vec![],
+    };
+
+    (0..path.get_points().len())
+        .filter_map(|i| path.get_segment(i).map(|segment| (i, segment)))
+        .flat_map(|(i, segment)| {
+            let controls = match segment {
+                Segment::Cubic(c1, c2) => vec![*c1, *c2],
+                Segment::SmoothCubic(c) | Segment::Quadratic(c) => vec![*c],
+                Segment::Line | Segment::SmoothQuadratic | Segment::Arc { .. } => vec![],
+            };
+            controls.into_iter().enumerate().map(move |(j, control)| (i, j, control))
+        })
+        .collect()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::PointerEvent;
+
+    ///
+    fn point(x: f64, y: f64) -> Point {
+        Point { x, y }
+    }
+
+    /// Drag from `from` to `to`, passing `shape` to every event, and return whatever the tool finished.
+    fn drag<T>(tool: &mut T, shape: &mut Box<dyn SVG>, from: Point, to: Point, modifiers: Modifiers) -> Option<Operation>
+    where
+        T: UserInteraction<Arg = Box<dyn SVG>, Result = Operation>,
+    {
+        tool.handle(PointerEvent::MouseDown(from, modifiers), Some(shape));
+        tool.handle(PointerEvent::MouseMove(point((from.x + to.x) / 2.0, (from.y + to.y) / 2.0), modifiers), Some(shape));
+        tool.handle(PointerEvent::MouseUp(to, modifiers), Some(shape))
+    }
+
+    /// Verify the move tool follows the mouse and gives back the move, and a click, Escape or no shape changes nothing.
+    #[test]
+    fn move_tool() {
+        let mut shape: Box<dyn SVG> = Rect::new(0.0, 0.0, 10.0, 10.0).box_ptr();
+        let mut tool = MoveTool::default();
+
+        match drag(&mut tool, &mut shape, point(5.0, 5.0), point(8.0, 1.0), Modifiers::NONE) {
+            Some(Operation::Move { before, after }) => {
+                assert_eq!(before.bounding_box(), Some(BoundingBox::new(0.0, 0.0, 10.0, 10.0)));
+                assert_eq!(after.bounding_box(), Some(BoundingBox::new(3.0, -4.0, 10.0, 10.0)));
+            },
+            _ => panic!("Expected a move"),
+        }
+        assert!(!tool.is_moving());
+        assert_eq!(shape.bounding_box(), Some(BoundingBox::new(3.0, -4.0, 10.0, 10.0)));
+
+        assert!(drag(&mut tool, &mut shape, point(5.0, 5.0), point(5.0, 5.0), Modifiers::NONE).is_none());
+
+        tool.handle(PointerEvent::MouseDown(point(0.0, 0.0), Modifiers::NONE), Some(&mut shape));
+        tool.handle(PointerEvent::MouseMove(point(100.0, 0.0), Modifiers::NONE), Some(&mut shape));
+        assert_eq!(shape.bounding_box(), Some(BoundingBox::new(103.0, -4.0, 10.0, 10.0)));
+        tool.handle(PointerEvent::KeyDown(Key::Escape), Some(&mut shape));
+        assert_eq!(shape.bounding_box(), Some(BoundingBox::new(3.0, -4.0, 10.0, 10.0)));
+        assert!(tool.handle(PointerEvent::MouseUp(point(100.0, 0.0), Modifiers::NONE), Some(&mut shape)).is_none());
+
+        tool.handle(PointerEvent::MouseDown(point(0.0, 0.0), Modifiers::NONE), None);
+        assert!(!tool.is_moving());
+    }
+
+    /// Verify shapes placed by different points by `move_to` all follow the mouse, through their own and their
+    /// parent's transforms.
+    #[test]
+    fn move_anchors() {
+        let mut group = Group::new("group");
+        group.add_shape(Rect::new(10.0, 10.0, 5.0, 5.0).box_ptr());
+        let mut scaled = Circle::new(2.0, 3.0, 1.0);
+        scaled.scale(2.0, 1.0);
+        let shapes: Vec<Box<dyn SVG>> = vec![
+            Circle::new(2.0, 3.0, 1.0).box_ptr(),
+            scaled.box_ptr(),
+            Path::from_points(vec![point(5.0, 5.0), point(0.0, 0.0), point(10.0, 0.0)]).unwrap().box_ptr(),
+            group.box_ptr(),
+        ];
+
+        for mut shape in shapes {
+            let parent = Transform::scale(0.5, 0.5).then(&Transform::translate(7.0, 0.0));
+            let before = shape.bounding_box_in(&parent).unwrap();
+            let mut tool = MoveTool::new(parent);
+            drag(&mut tool, &mut shape, point(0.0, 0.0), point(4.0, -2.0), Modifiers::NONE).unwrap();
+
+            let after = shape.bounding_box_in(&parent).unwrap();
+            assert_eq!(after.min, point(before.min.x + 4.0, before.min.y - 2.0));
+            assert_eq!(after.max, point(before.max.x + 4.0, before.max.y - 2.0));
+        }
+    }
+
+    /// Verify the eight box handles sit on the transformed bounding box, paths also have a handle on each point,
+    /// and shapes with no size have none.
+    #[test]
+    fn handles() {
+        let tool = ResizeTool::new(Transform::translate(100.0, 0.0), 1.0);
+        let mut rect = Rect::new(0.0, 0.0, 10.0, 20.0);
+        rect.scale(2.0, 1.0);
+
+        assert_eq!(tool.handles(&rect), vec![
+            (Handle::TopLeft, point(100.0, 0.0)),
+            (Handle::Top, point(110.0, 0.0)),
+            (Handle::TopRight, point(120.0, 0.0)),
+            (Handle::Right, point(120.0, 10.0)),
+            (Handle::BottomRight, point(120.0, 20.0)),
+            (Handle::Bottom, point(110.0, 20.0)),
+            (Handle::BottomLeft, point(100.0, 20.0)),
+            (Handle::Left, point(100.0, 10.0)),
+        ]);
+
+        let path = Path::from_points(vec![point(0.0, 0.0), point(4.0, 2.0), point(8.0, 0.0)]).unwrap();
+        let handles = tool.handles(&path);
+        assert_eq!(handles.len(), 11);
+        assert_eq!(handles[1], (Handle::Point(1), point(104.0, 2.0)));
+        assert_eq!(handles[3], (Handle::TopLeft, point(100.0, 0.0)));
+
+        assert!(tool.handles(&crate::svg::Text::new(0.0, 0.0, "label")).is_empty());
+    }
+
+    /// Verify dragging a box handle scales from the opposite side, from the centre with alt, and keeps the
+    /// proportions with shift, and only a press on a handle grabs it.
+    #[test]
+    fn resize_box() {
+        let resize = |from: Point, to: Point, modifiers: Modifiers| {
+            let mut shape: Box<dyn SVG> = Rect::new(0.0, 0.0, 10.0, 20.0).box_ptr();
+            let mut tool = ResizeTool::new(Transform::identity(), 1.0);
+            let operation = drag(&mut tool, &mut shape, from, to, modifiers);
+            assert_eq!(operation.is_some(), from != to && shape.to_html() != Rect::new(0.0, 0.0, 10.0, 20.0).to_html());
+            shape.bounding_box().unwrap()
+        };
+        let alt = Modifiers { alt: true, ..Modifiers::NONE };
+
+        assert_eq!(resize(point(10.0, 20.0), point(15.0, 30.0), Modifiers::NONE), BoundingBox::new(0.0, 0.0, 15.0, 30.0));
+        assert_eq!(resize(point(0.0, 10.5), point(-5.0, 50.0), Modifiers::NONE), BoundingBox::new(-5.0, 0.0, 15.0, 20.0));
+        assert_eq!(resize(point(10.0, 0.0), point(20.0, 5.0), alt), BoundingBox::new(-10.0, 5.0, 30.0, 10.0));
+        assert_eq!(resize(point(10.0, 20.0), point(15.0, 60.0), Modifiers::SHIFT), BoundingBox::new(0.0, 0.0, 30.0, 60.0));
+        assert_eq!(resize(point(5.0, 20.0), point(0.0, 40.0), Modifiers::SHIFT), BoundingBox::new(-5.0, 0.0, 20.0, 40.0));
+
+        // Pressing away from the handles, or dragging past the opposite side, leaves a valid box
+        assert_eq!(resize(point(5.0, 10.0), point(50.0, 50.0), Modifiers::NONE), BoundingBox::new(0.0, 0.0, 10.0, 20.0));
+        assert_eq!(resize(point(10.0, 20.0), point(-10.0, 20.0), Modifiers::NONE), BoundingBox::new(-10.0, 0.0, 10.0, 20.0));
+    }
+
+    /// Verify each kind of shape is resized to fill the dragged box, circles keep their proportions, path curves are
+    /// scaled along with their points, and point handles move a single point.
+    #[test]
+    fn resize_shapes() {
+        let resize = |mut shape: Box<dyn SVG>, from: Point, to: Point| {
+            let mut tool = ResizeTool::new(Transform::identity(), 0.5);
+            drag(&mut tool, &mut shape, from, to, Modifiers::NONE).unwrap();
+            shape
+        };
+
+        let ellipse = resize(Ellipse::new(5.0, 5.0, 5.0, 5.0).box_ptr(), point(10.0, 10.0), point(20.0, 15.0));
+        assert_eq!(ellipse.to_html(), "<ellipse cx=\"10\" cy=\"7.5\" rx=\"10\" ry=\"7.5\"></ellipse>");
+
+        let circle = resize(Circle::new(5.0, 5.0, 5.0).box_ptr(), point(10.0, 10.0), point(20.0, 15.0));
+        assert_eq!(circle.to_html(), "<circle cx=\"10\" cy=\"10\" r=\"10\"></circle>");
+
+        let mut group = Group::new("group");
+        group.add_shape(Rect::new(0.0, 0.0, 2.0, 2.0).box_ptr());
+        group.add_shape(Rect::new(8.0, 8.0, 2.0, 2.0).box_ptr());
+        let group = resize(group.box_ptr(), point(0.0, 0.0), point(-10.0, 5.0));
+        assert_eq!(group.bounding_box(), Some(BoundingBox::new(-10.0, 5.0, 20.0, 5.0)));
+
+        let mut path = Path::from_points(vec![point(0.0, 0.0)]).unwrap();
+        path.add_segment(Segment::Cubic(point(0.0, 10.0), point(10.0, 10.0)), point(10.0, 0.0)).unwrap();
+        let path = resize(path.box_ptr(), point(10.0, 7.5), point(20.0, 7.5));
+        assert_eq!(path.to_html(), "<path d=\"M 0 0 C 0 10 20 10 20 0\"></path>");
+
+        // The point handle on the path's first point is picked over the box's corner handle
+        let path = resize(path, point(0.0, 0.0), point(-1.0, -1.0));
+        assert_eq!(path.to_html(), "<path d=\"M -1 -1 C 0 10 20 10 20 0\"></path>");
+
+        let line = resize(crate::svg::Line::new(0.0, 0.0, 10.0, 0.0).box_ptr(), point(10.0, 0.0), point(5.0, 0.0));
+        assert_eq!(line.to_html(), "<line x1=\"0\" y1=\"0\" x2=\"5\" y2=\"0\"></line>");
+    }
+
+    /// Verify Escape puts a shape being resized back as it was.
+    #[test]
+    fn cancel_resize() {
+        let mut shape: Box<dyn SVG> = Rect::new(0.0, 0.0, 10.0, 10.0).box_ptr();
+        let mut tool = ResizeTool::new(Transform::identity(), 1.0);
+
+        tool.handle(PointerEvent::MouseDown(point(10.0, 10.0), Modifiers::NONE), Some(&mut shape));
+        assert_eq!(tool.get_handle(), Some(Handle::BottomRight));
+        tool.handle(PointerEvent::MouseMove(point(30.0, 30.0), Modifiers::NONE), Some(&mut shape));
+        assert_eq!(shape.bounding_box(), Some(BoundingBox::new(0.0, 0.0, 30.0, 30.0)));
+
+        tool.handle(PointerEvent::KeyDown(Key::Escape), Some(&mut shape));
+        assert_eq!(shape.bounding_box(), Some(BoundingBox::new(0.0, 0.0, 10.0, 10.0)));
+        assert!(tool.get_handle().is_none());
+    }
+}
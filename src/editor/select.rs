@@ -0,0 +1,300 @@
+use std::collections::BTreeSet;
+
+use crate::svg::{Grid, Point, SVG, BoundingBox, Style, Transform};
+use super::{UserInteraction, Modifiers, Key};
+
+
+/// How closely curves are followed when checking whether they cross a rubber band, in the grid's coordinates.
+const FLATTEN_TOLERANCE: f64 = 0.1;
+
+
+/// Which shapes a rubber band selects.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BandMode {
+    /// Every shape the band touches
+    Intersect,
+    /// Only the shapes entirely inside the band
+    Inside,
+}
+
+/// A change to the selection, made by the select tool.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SelectionChange {
+    /// Select exactly these shapes
+    Replace(Vec<String>),
+    /// Add these shapes to the selection
+    Add(Vec<String>),
+    /// Select the shape if it isn't selected, otherwise deselect it
+    Toggle(String),
+}
+
+impl SelectionChange {
+    ///
+    pub fn apply(self, selection: &mut BTreeSet<String>) {
+        match self {
+            SelectionChange::Replace(uuids) => *selection = uuids.into_iter().collect(),
+            SelectionChange::Add(uuids) => selection.extend(uuids),
+            SelectionChange::Toggle(uuid) => {
+                if !selection.remove(&uuid) {
+                    selection.insert(uuid);
+                }
+            },
+        }
+    }
+}
+
+
+/// Selects the topmost shape under a click, or the shapes picked out by dragging a rubber band.
+/// Holding shift when the mouse goes down toggles the clicked shape, or adds the band's shapes, instead of replacing
+/// the selection. The tool is given the grid to select from as the event's item.
+pub struct SelectTool {
+    tolerance: f64,
+    mode: BandMode,
+    // Where the mouse went down, and whether shift was held
+    press: Option<(Point, bool)>,
+    band_to: Option<Point>,
+}
+
+impl SelectTool {
+    /// A select tool picking shapes within `tolerance` of a click, and banding them by `mode`.
+    pub fn new(tolerance: f64, mode: BandMode) -> SelectTool {
+        SelectTool { tolerance, mode, press: None, band_to: None }
+    }
+
+    /// The rubber band being dragged, once the mouse has moved further than the tolerance from where it went down.
+    pub fn get_band(&self) -> Option<BoundingBox> {
+        let (origin, _) = self.press?;
+        BoundingBox::from_points([origin, self.band_to?])
+    }
+
+    /// The shapes `band` picks out of `grid`, which are top-level shapes of the grid's groups.
+    fn shapes_in_band(&self, grid: &Grid, band: &BoundingBox) -> Vec<String> {
+        grid.query_rect(*band).into_iter()
+            .filter(|shape| {
+                let uuid = shape.get_uuid();
+                let (parent, style) = match (grid.parent_transform(&uuid), grid.parent_style(&uuid)) {
+                    (Some(parent), Some(style)) => (parent, style),
+                    _ => return false,
+                };
+
+                match self.mode {
+                    BandMode::Intersect => touches(*shape, &parent, &style, band),
+                    BandMode::Inside => shape.bounding_box_in(&parent).is_some_and(|bounds| contains(band, &bounds)),
+                }
+            })
+            .map(|shape| shape.get_uuid())
+            .collect()
+    }
+}
+
+impl UserInteraction for SelectTool {
+    type Arg = Grid;
+    type Result = SelectionChange;
+
+    ///
+    fn mouse_down(&mut self, point: Point, modifiers: Modifiers, _: Option<&mut Self::Arg>) -> Option<Self::Result> {
+        self.press = Some((point, modifiers.shift));
+        self.band_to = None;
+        None
+    }
+
+    /// Start or stretch the rubber band. Small movements during a click don't start one.
+    fn mouse_move(&mut self, point: Point, _: Modifiers, _: Option<&mut Self::Arg>) -> Option<Self::Result> {
+        if let Some((origin, _)) = self.press {
+            if self.band_to.is_some() || (point.x - origin.x).hypot(point.y - origin.y) > self.tolerance {
+                self.band_to = Some(point);
+            }
+        }
+
+        None
+    }
+
+    ///
+    fn mouse_up(&mut self, point: Point, _: Modifiers, item: Option<&mut Self::Arg>) -> Option<Self::Result> {
+        let (origin, toggle) = self.press.take()?;
+        let banding = self.band_to.take().is_some();
+        let grid = item?;
+
+        if banding {
+            let band = BoundingBox::from_points([origin, point])?;
+            let shapes = self.shapes_in_band(grid, &band);
+
+            return Some(if toggle { SelectionChange::Add(shapes) } else { SelectionChange::Replace(shapes) });
+        }
+
+        let hit = grid.shapes_at(point, self.tolerance).first().map(|shape| shape.get_uuid());
+        match (hit, toggle) {
+            (Some(uuid), true) => Some(SelectionChange::Toggle(uuid)),
+            (None, true) => None,
+            (hit, false) => Some(SelectionChange::Replace(hit.into_iter().collect())),
+        }
+    }
+
+    /// Escape drops the rubber band, leaving the selection as it was.
+    fn key_down(&mut self, key: Key, _: Option<&mut Self::Arg>) -> Option<Self::Result> {
+        match key {
+            Key::Escape => {
+                self.press = None;
+                self.band_to = None;
+                None
+            },
+        }
+    }
+}
+
+
+/// Whether the outline of `shape`, after its own transform followed by `parent`, touches `band`,
+/// or the shape's fill, on top of the `inherited` style, covers it.
+fn touches(shape: &dyn SVG, parent: &Transform, inherited: &Style, band: &BoundingBox) -> bool {
+    let crossed = shape.outlines_in(parent).iter().any(|outline| {
+        let points = outline.flatten(FLATTEN_TOLERANCE);
+        contains(band, &BoundingBox { min: points[0], max: points[0] })
+            || points.windows(2).any(|pair| segment_touches(pair[0], pair[1], band))
+    });
+
+    crossed || shape.distance_in(band.center(), parent, inherited) == 0.0
+}
+
+/// Whether the segment from `a` to `b` passes through `band`, by clipping it to each side of the band in turn.
+fn segment_touches(a: Point, b: Point, band: &BoundingBox) -> bool {
+    let (dx, dy) = (b.x - a.x, b.y - a.y);
+    let (mut enter, mut exit) = (0.0_f64, 1.0_f64);
+
+    for (p, q) in [(-dx, a.x - band.min.x), (dx, band.max.x - a.x), (-dy, a.y - band.min.y), (dy, band.max.y - a.y)] {
+        if p == 0.0 {
+            // Parallel to this side, so either always inside it or never
+            if q < 0.0 {
+                return false;
+            }
+        } else if p < 0.0 {
+            enter = enter.max(q / p);
+        } else {
+            exit = exit.min(q / p);
+        }
+    }
+
+    enter <= exit
+}
+
+///
+fn contains(outer: &BoundingBox, inner: &BoundingBox) -> bool {
+    inner.min.x >= outer.min.x && inner.min.y >= outer.min.y && inner.max.x <= outer.max.x && inner.max.y <= outer.max.y
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::svg::{CoordinateSystem, Rect, Circle, Line};
+
+    ///
+    fn point(x: f64, y: f64) -> Point {
+        Point { x, y }
+    }
+
+    /// Click or drag from `from` to `to`, returning the change to the selection.
+    fn gesture(tool: &mut SelectTool, grid: &mut Grid, from: Point, to: Point, modifiers: Modifiers) -> Option<SelectionChange> {
+        tool.mouse_down(from, modifiers, Some(grid));
+        tool.mouse_move(to, modifiers, Some(grid));
+        tool.mouse_up(to, modifiers, Some(grid))
+    }
+
+    ///
+    fn grid() -> (Grid, Vec<String>) {
+        let mut grid = Grid::new(CoordinateSystem::TopLeftDownRight);
+        grid.add_shape(Rect::new(0.0, 0.0, 10.0, 10.0).box_ptr());
+        grid.add_shape(Circle::new(30.0, 5.0, 5.0).box_ptr());
+        grid.add_shape(Line::new(50.0, 0.0, 60.0, 10.0).box_ptr());
+        let uuids = (0..3).map(|i| grid.get_shape(i).unwrap().get_uuid()).collect();
+
+        (grid, uuids)
+    }
+
+    /// Verify a click selects the topmost shape under it, or nothing, and shift-clicking toggles the shape.
+    #[test]
+    fn click() {
+        let (mut grid, uuids) = grid();
+        grid.add_shape(Rect::new(5.0, 5.0, 10.0, 10.0).box_ptr());
+        let top = grid.get_shape(3).unwrap().get_uuid();
+        let mut tool = SelectTool::new(1.0, BandMode::Intersect);
+
+        let change = gesture(&mut tool, &mut grid, point(7.0, 7.0), point(7.5, 7.0), Modifiers::NONE);
+        assert_eq!(change, Some(SelectionChange::Replace(vec![top.clone()])));
+        let change = gesture(&mut tool, &mut grid, point(2.0, 2.0), point(2.0, 2.0), Modifiers::SHIFT);
+        assert_eq!(change, Some(SelectionChange::Toggle(uuids[0].clone())));
+
+        assert_eq!(gesture(&mut tool, &mut grid, point(100.0, 100.0), point(100.0, 100.0), Modifiers::NONE), Some(SelectionChange::Replace(vec![])));
+        assert_eq!(gesture(&mut tool, &mut grid, point(100.0, 100.0), point(100.0, 100.0), Modifiers::SHIFT), None);
+
+        let mut selection = BTreeSet::from([top.clone()]);
+        SelectionChange::Toggle(uuids[0].clone()).apply(&mut selection);
+        SelectionChange::Toggle(top).apply(&mut selection);
+        assert_eq!(selection, BTreeSet::from([uuids[0].clone()]));
+    }
+
+    /// Verify the rubber band picks shapes whose outline it touches, but not shapes whose box alone it reaches.
+    #[test]
+    fn band_intersect() {
+        let (mut grid, uuids) = grid();
+        let mut tool = SelectTool::new(1.0, BandMode::Intersect);
+
+        tool.mouse_down(point(5.0, -5.0), Modifiers::NONE, Some(&mut grid));
+        tool.mouse_move(point(25.5, 1.0), Modifiers::NONE, Some(&mut grid));
+        assert_eq!(tool.get_band(), Some(BoundingBox::new(5.0, -5.0, 20.5, 6.0)));
+
+        // The band reaches the circle's box but not the circle itself
+        let change = tool.mouse_up(point(25.5, 1.0), Modifiers::NONE, Some(&mut grid));
+        assert_eq!(change, Some(SelectionChange::Replace(vec![uuids[0].clone()])));
+        assert!(tool.get_band().is_none());
+
+        // The band across the middle of the line's box misses the diagonal
+        let change = gesture(&mut tool, &mut grid, point(51.0, 6.0), point(53.0, 9.0), Modifiers::NONE);
+        assert_eq!(change, Some(SelectionChange::Replace(vec![])));
+
+        let change = gesture(&mut tool, &mut grid, point(28.0, 0.0), point(58.0, 9.0), Modifiers::SHIFT);
+        let mut shapes = match change {
+            Some(SelectionChange::Add(shapes)) => shapes,
+            other => panic!("Expected shapes to add, found {:?}", other),
+        };
+        shapes.sort();
+        let mut expected = vec![uuids[1].clone(), uuids[2].clone()];
+        expected.sort();
+        assert_eq!(shapes, expected);
+
+        // A band inside a filled shape touches it
+        let change = gesture(&mut tool, &mut grid, point(2.0, 2.0), point(8.0, 8.0), Modifiers::NONE);
+        assert_eq!(change, Some(SelectionChange::Replace(vec![uuids[0].clone()])));
+
+        // But not once the group it is in leaves it unfilled
+        grid.get_group_mut("shapes").unwrap().get_style_mut().set_fill("none");
+        let change = gesture(&mut tool, &mut grid, point(2.0, 2.0), point(8.0, 8.0), Modifiers::NONE);
+        assert_eq!(change, Some(SelectionChange::Replace(vec![])));
+    }
+
+    /// Verify in inside mode only shapes entirely within the band are picked, and escape abandons the band.
+    #[test]
+    fn band_inside() {
+        let (mut grid, uuids) = grid();
+        let mut tool = SelectTool::new(1.0, BandMode::Inside);
+
+        let change = gesture(&mut tool, &mut grid, point(-1.0, -1.0), point(36.0, 11.0), Modifiers::NONE);
+        let mut expected = vec![uuids[0].clone(), uuids[1].clone()];
+        expected.sort();
+        match change {
+            Some(SelectionChange::Replace(mut shapes)) => {
+                shapes.sort();
+                assert_eq!(shapes, expected);
+            },
+            other => panic!("Expected a new selection, found {:?}", other),
+        }
+
+        let change = gesture(&mut tool, &mut grid, point(2.0, 2.0), point(8.0, 8.0), Modifiers::NONE);
+        assert_eq!(change, Some(SelectionChange::Replace(vec![])));
+
+        tool.mouse_down(point(-1.0, -1.0), Modifiers::NONE, Some(&mut grid));
+        tool.mouse_move(point(36.0, 11.0), Modifiers::NONE, Some(&mut grid));
+        assert!(tool.key_down(Key::Escape, Some(&mut grid)).is_none());
+        assert!(tool.get_band().is_none());
+        assert!(tool.mouse_up(point(36.0, 11.0), Modifiers::NONE, Some(&mut grid)).is_none());
+    }
+}
@@ -0,0 +1,746 @@
+use std::collections::BTreeSet;
+
+use crate::svg::{Grid, Group, Rect, Point, SVG, Operation};
+use super::{PointerEvent, Key, UserInteraction, DrawTool, RectTool, CircleTool, EllipseTool, LineTool, PenTool};
+use super::{SelectTool, BandMode, MoveTool, ResizeTool};
+
+
+/// How far from a shape, in the grid's coordinates, a click can be and still pick it.
+const DEFAULT_TOLERANCE: f64 = 3.0;
+
+/// The name of the grid's overlay group, holding the selection outlines, rubber band and shape being drawn.
+const OVERLAY_NAME: &str = "overlay";
+
+/// The stroke of selection outlines and the rubber band.
+const OUTLINE_STROKE: &str = "#1e90ff";
+
+
+/// The kinds of shape the draw tool can draw.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DrawKind {
+    Rect,
+    Circle,
+    Ellipse,
+    Line,
+    Path,
+}
+
+/// What pointer events do in an editor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tool {
+    /// Clicking a shape selects it, and clicking empty space clears the selection. Shift-clicking toggles a shape,
+    /// and dragging a rubber band selects the shapes it picks out
+    Select,
+    /// Dragging moves the selection, or the shape under the mouse if it isn't selected. Escape puts it back
+    Move,
+    /// Dragging draws a new shape, which is selected once it is finished
+    Draw(DrawKind),
+    /// Dragging a handle of a selected shape resizes it, and pressing anywhere else selects the shape under the mouse.
+    /// Shift keeps the shape's proportions, alt resizes it from its centre, and Escape puts it back
+    Resize,
+}
+
+
+/// A draw tool along with the shape it is drawing, which stays out of the grid until it is finished.
+struct Drawing<T: DrawTool> {
+    tool: T,
+    shape: Option<T::Shape>,
+}
+
+impl<T> Drawing<T>
+where
+    T: DrawTool + UserInteraction<Arg = <T as DrawTool>::Shape, Result = <T as DrawTool>::Shape>,
+{
+    ///
+    fn new(tool: T) -> Drawing<T> {
+        Drawing { tool, shape: None }
+    }
+
+    /// Pass the event on to the tool, along with the shape being drawn. Returns the shape once the tool has finished it,
+    /// unless it ended up with no size, which draws nothing.
+    fn handle(&mut self, event: PointerEvent) -> Option<Box<dyn SVG>> {
+        let result = self.tool.handle(event, self.shape.as_mut());
+
+        if result.is_some() {
+            self.shape = result;
+        }
+
+        if self.tool.is_drawing() {
+            return None;
+        }
+
+        self.shape.take()
+            .filter(|shape| shape.bounding_box().is_some_and(|bounds| bounds.width() > 0.0 || bounds.height() > 0.0))
+            .map(|shape| Box::new(shape) as Box<dyn SVG>)
+    }
+
+    ///
+    fn preview(&self) -> Option<&dyn SVG> {
+        self.shape.as_ref().map(|shape| shape as &dyn SVG)
+    }
+}
+
+/// The draw tool for each kind of shape.
+enum Drawer {
+    Rect(Drawing<RectTool>),
+    Circle(Drawing<CircleTool>),
+    Ellipse(Drawing<EllipseTool>),
+    Line(Drawing<LineTool>),
+    Path(Drawing<PenTool>),
+}
+
+impl Drawer {
+    ///
+    fn new(kind: DrawKind) -> Drawer {
+        match kind {
+            DrawKind::Rect => Drawer::Rect(Drawing::new(RectTool::new())),
+            DrawKind::Circle => Drawer::Circle(Drawing::new(CircleTool::new())),
+            DrawKind::Ellipse => Drawer::Ellipse(Drawing::new(EllipseTool::new())),
+            DrawKind::Line => Drawer::Line(Drawing::new(LineTool::new())),
+            DrawKind::Path => Drawer::Path(Drawing::new(PenTool::new())),
+        }
+    }
+
+    ///
+    fn kind(&self) -> DrawKind {
+        match self {
+            Drawer::Rect(_) => DrawKind::Rect,
+            Drawer::Circle(_) => DrawKind::Circle,
+            Drawer::Ellipse(_) => DrawKind::Ellipse,
+            Drawer::Line(_) => DrawKind::Line,
+            Drawer::Path(_) => DrawKind::Path,
+        }
+    }
+
+    ///
+    fn handle(&mut self, event: PointerEvent) -> Option<Box<dyn SVG>> {
+        match self {
+            Drawer::Rect(drawing) => drawing.handle(event),
+            Drawer::Circle(drawing) => drawing.handle(event),
+            Drawer::Ellipse(drawing) => drawing.handle(event),
+            Drawer::Line(drawing) => drawing.handle(event),
+            Drawer::Path(drawing) => drawing.handle(event),
+        }
+    }
+
+    ///
+    fn preview(&self) -> Option<&dyn SVG> {
+        match self {
+            Drawer::Rect(drawing) => drawing.preview(),
+            Drawer::Circle(drawing) => drawing.preview(),
+            Drawer::Ellipse(drawing) => drawing.preview(),
+            Drawer::Line(drawing) => drawing.preview(),
+            Drawer::Path(drawing) => drawing.preview(),
+        }
+    }
+}
+
+
+/// A tool editing a shape in the grid, along with the shape's uuid.
+type Editing<T> = (String, T);
+
+/// The active tool, along with anything it is in the middle of.
+enum Active {
+    Select(SelectTool),
+    Move(Vec<Editing<MoveTool>>),
+    Draw(Box<Drawer>),
+    Resize(Option<Editing<ResizeTool>>),
+}
+
+
+/// An editing session on a grid. Front ends pass pointer events to `handle_event`, and the active tool turns them
+/// into changes to the grid. Each finished change is recorded in the grid's history, so it can be undone.
+pub struct Editor {
+    grid: Grid,
+    active: Active,
+    selection: BTreeSet<String>,
+    tolerance: f64,
+    band_mode: BandMode,
+}
+
+impl Editor {
+    /// Start editing `grid` with the select tool.
+    pub fn new(grid: Grid) -> Editor {
+        Editor {
+            grid,
+            active: Active::Select(SelectTool::new(DEFAULT_TOLERANCE, BandMode::Intersect)),
+            selection: BTreeSet::new(),
+            tolerance: DEFAULT_TOLERANCE,
+            band_mode: BandMode::Intersect,
+        }
+    }
+
+    ///
+    pub fn get_grid(&self) -> &Grid {
+        &self.grid
+    }
+
+    /// Changes made to the grid while shapes are being moved may be lost when they are released.
+    pub fn get_grid_mut(&mut self) -> &mut Grid {
+        &mut self.grid
+    }
+
+    /// Stop editing, putting back anything that was being moved and removing the overlay.
+    pub fn into_grid(mut self) -> Grid {
+        self.cancel();
+        self.grid.set_overlay(None);
+        self.grid
+    }
+
+    ///
+    pub fn get_tool(&self) -> Tool {
+        match &self.active {
+            Active::Select(_) => Tool::Select,
+            Active::Move(_) => Tool::Move,
+            Active::Draw(drawer) => Tool::Draw(drawer.kind()),
+            Active::Resize(_) => Tool::Resize,
+        }
+    }
+
+    /// Switch tools, abandoning anything being drawn and putting back anything being moved.
+    pub fn set_tool(&mut self, tool: Tool) {
+        self.cancel();
+
+        self.active = match tool {
+            Tool::Select => Active::Select(SelectTool::new(self.tolerance, self.band_mode)),
+            Tool::Move => Active::Move(vec![]),
+            Tool::Draw(kind) => Active::Draw(Box::new(Drawer::new(kind))),
+            Tool::Resize => Active::Resize(None),
+        };
+        self.refresh_overlay();
+    }
+
+    /// The uuids of the selected shapes.
+    pub fn get_selection(&self) -> &BTreeSet<String> {
+        &self.selection
+    }
+
+    ///
+    pub fn get_tolerance(&self) -> f64 {
+        self.tolerance
+    }
+
+    /// How far from a shape, in the grid's coordinates, a click can be and still pick it.
+    pub fn set_tolerance(&mut self, tolerance: f64) {
+        self.tolerance = tolerance;
+        self.reset_select_tool();
+    }
+
+    ///
+    pub fn get_band_mode(&self) -> BandMode {
+        self.band_mode
+    }
+
+    /// Whether a rubber band selects the shapes it touches or only those entirely inside it.
+    pub fn set_band_mode(&mut self, mode: BandMode) {
+        self.band_mode = mode;
+        self.reset_select_tool();
+    }
+
+    /// The shape being drawn, which isn't added to the grid until it is finished.
+    pub fn get_preview(&self) -> Option<&dyn SVG> {
+        match &self.active {
+            Active::Draw(drawer) => drawer.preview(),
+            _ => None,
+        }
+    }
+
+    /// Pass a pointer event to the active tool, applying anything it finishes to the grid.
+    pub fn handle_event(&mut self, event: PointerEvent) -> Result<(), String> {
+        // Shapes may have been removed through the grid, or by undoing, since the last event
+        let grid = &self.grid;
+        self.selection.retain(|uuid| grid.get_by_uuid(uuid).is_some());
+
+        match &mut self.active {
+            Active::Select(tool) => {
+                if let Some(change) = tool.handle(event, Some(&mut self.grid)) {
+                    change.apply(&mut self.selection);
+                }
+            },
+            Active::Move(_) => self.handle_move(event)?,
+            Active::Draw(drawer) => {
+                if let Some(shape) = drawer.handle(event) {
+                    self.selection = BTreeSet::from([shape.get_uuid()]);
+                    self.grid.add_shape(shape);
+                }
+            },
+            Active::Resize(_) => self.handle_resize(event)?,
+        }
+
+        self.refresh_overlay();
+        Ok(())
+    }
+
+    /// Show the selection outlines, along with their handles while resizing, the rubber band and the shape being drawn
+    /// in the grid's overlay, in the grid's coordinates. The overlay is removed when there is nothing to show.
+    pub fn refresh_overlay(&mut self) {
+        let mut overlay = Group::new(OVERLAY_NAME);
+
+        if let Some(preview) = self.get_preview() {
+            overlay.add_shape(preview.clone_box());
+        }
+
+        for uuid in &self.selection {
+            let bounds = self.grid.parent_transform(uuid)
+                .and_then(|parent| self.grid.get_by_uuid(uuid)?.bounding_box_in(&parent));
+            if let Some(bounds) = bounds {
+                overlay.add_shape(outline(bounds.min, bounds.max).box_ptr());
+            }
+
+            if let (Active::Resize(_), Some(parent), Some(shape)) =
+                (&self.active, self.grid.parent_transform(uuid), self.grid.get_by_uuid(uuid))
+            {
+                // Handles are drawn as big as the area that picks them
+                let size = self.tolerance;
+                for (_, at) in ResizeTool::new(parent, self.tolerance).handles(shape) {
+                    let mut handle = outline(Point { x: at.x - size, y: at.y - size }, Point { x: at.x + size, y: at.y + size });
+                    handle.get_style_mut().set_fill("#ffffff");
+                    overlay.add_shape(handle.box_ptr());
+                }
+            }
+        }
+
+        if let Active::Select(tool) = &self.active {
+            if let Some(band) = tool.get_band() {
+                let mut band = outline(band.min, band.max);
+                band.get_style_mut().set_fill(OUTLINE_STROKE);
+                band.get_style_mut().set_opacity(0.2).unwrap();
+                overlay.add_shape(band.box_ptr());
+            }
+        }
+
+        if overlay.is_empty() {
+            self.grid.set_overlay(None);
+            return;
+        }
+
+        // The overlay is drawn inside the grid's transform, which shapes in the grid's coordinates are already past
+        if let Some(inverse) = self.grid.get_transform().inverse() {
+            overlay.set_transform(inverse);
+        }
+        self.grid.set_overlay(Some(overlay));
+    }
+
+    /// Pick up a new tolerance or band mode, abandoning any rubber band being dragged.
+    fn reset_select_tool(&mut self) {
+        if let Active::Select(tool) = &mut self.active {
+            *tool = SelectTool::new(self.tolerance, self.band_mode);
+        }
+    }
+
+    /// The uuid of the topmost shape under `point`.
+    fn shape_at(&self, point: Point) -> Option<String> {
+        self.grid.shapes_at(point, self.tolerance).first().map(|shape| shape.get_uuid())
+    }
+
+    /// Drag the selection, following the mouse while it is down and recording the moves when it goes up.
+    fn handle_move(&mut self, event: PointerEvent) -> Result<(), String> {
+        if let PointerEvent::MouseDown(point, modifiers) = event {
+            self.cancel();
+
+            let uuid = match self.shape_at(point) {
+                Some(uuid) => uuid,
+                None => return Ok(()),
+            };
+            if !self.selection.contains(&uuid) {
+                self.selection = BTreeSet::from([uuid]);
+            }
+
+            let mut tools = vec![];
+            for uuid in &self.selection {
+                if let Some(parent) = self.grid.parent_transform(uuid) {
+                    let mut tool = MoveTool::new(parent);
+                    tool.mouse_down(point, modifiers, self.grid.get_by_uuid_mut(uuid));
+                    tools.push((uuid.clone(), tool));
+                }
+            }
+            self.active = Active::Move(tools);
+        } else if let Active::Move(tools) = &mut self.active {
+            let operations = forward(&mut self.grid, tools, event);
+            if is_finished(event) {
+                tools.clear();
+            }
+            perform_all(&mut self.grid, operations)?;
+        }
+
+        Ok(())
+    }
+
+    /// Drag a handle of a selected shape, resizing the shape while the mouse is down and recording the resize when it
+    /// goes up. Pressing anywhere other than a handle selects the shape under the mouse instead.
+    fn handle_resize(&mut self, event: PointerEvent) -> Result<(), String> {
+        if let PointerEvent::MouseDown(point, modifiers) = event {
+            self.cancel();
+
+            for uuid in &self.selection {
+                if let Some(parent) = self.grid.parent_transform(uuid) {
+                    let mut tool = ResizeTool::new(parent, self.tolerance);
+                    tool.mouse_down(point, modifiers, self.grid.get_by_uuid_mut(uuid));
+
+                    if tool.get_handle().is_some() {
+                        self.active = Active::Resize(Some((uuid.clone(), tool)));
+                        return Ok(());
+                    }
+                }
+            }
+
+            self.selection = self.shape_at(point).into_iter().collect();
+        } else if let Active::Resize(editing) = &mut self.active {
+            let operations = forward(&mut self.grid, editing.as_mut_slice(), event);
+            if is_finished(event) {
+                *editing = None;
+            }
+            perform_all(&mut self.grid, operations)?;
+        }
+
+        Ok(())
+    }
+
+    /// Abandon whatever the active tool is in the middle of, putting back any shapes being moved or resized.
+    fn cancel(&mut self) {
+        match &mut self.active {
+            Active::Select(tool) => *tool = SelectTool::new(self.tolerance, self.band_mode),
+            Active::Move(tools) => {
+                forward(&mut self.grid, tools, PointerEvent::KeyDown(Key::Escape));
+                tools.clear();
+            },
+            Active::Draw(drawer) => {
+                let kind = drawer.kind();
+                **drawer = Drawer::new(kind);
+            },
+            Active::Resize(editing) => {
+                forward(&mut self.grid, editing.as_mut_slice(), PointerEvent::KeyDown(Key::Escape));
+                *editing = None;
+            },
+        }
+    }
+}
+
+
+/// Pass `event` to tools editing shapes in the grid, each given its shape, collecting the changes they finish.
+/// Shapes are edited in place without being recorded until they are finished.
+fn forward<T>(grid: &mut Grid, tools: &mut [Editing<T>], event: PointerEvent) -> Vec<Operation>
+where
+    T: UserInteraction<Arg = Box<dyn SVG>, Result = Operation>,
+{
+    tools.iter_mut()
+        .filter_map(|(uuid, tool)| tool.handle(event, grid.get_by_uuid_mut(uuid)))
+        .collect()
+}
+
+/// Whether `event` ends a move or resize, either finishing it or putting the shapes back.
+fn is_finished(event: PointerEvent) -> bool {
+    matches!(event, PointerEvent::MouseUp(..) | PointerEvent::KeyDown(Key::Escape))
+}
+
+/// Record changes already made to shapes in the grid, so they can be undone. Changes finished by the same event,
+/// such as moving several selected shapes, are recorded as one operation so they are undone together.
+fn perform_all(grid: &mut Grid, mut operations: Vec<Operation>) -> Result<(), String> {
+    match operations.len() {
+        0 => Ok(()),
+        1 => grid.perform(operations.remove(0)),
+        _ => grid.perform(Operation::Batch { operations }),
+    }
+}
+
+/// An unfilled rectangle from `min` to `max`, outlining a selected shape.
+fn outline(min: Point, max: Point) -> Rect {
+    let mut rect = Rect::new(min.x, min.y, max.x - min.x, max.y - min.y);
+    rect.get_style_mut().set_fill("none");
+    rect.get_style_mut().set_stroke(OUTLINE_STROKE);
+    rect
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::Modifiers;
+    use crate::svg::{CoordinateSystem, Rect, Group, Transform, BoundingBox, SvgOptions};
+
+    ///
+    fn point(x: f64, y: f64) -> Point {
+        Point { x, y }
+    }
+
+    ///
+    fn click(editor: &mut Editor, at: Point, modifiers: Modifiers) {
+        editor.handle_event(PointerEvent::MouseDown(at, modifiers)).unwrap();
+        editor.handle_event(PointerEvent::MouseUp(at, modifiers)).unwrap();
+    }
+
+    ///
+    fn drag(editor: &mut Editor, from: Point, to: Point) {
+        editor.handle_event(PointerEvent::MouseDown(from, Modifiers::NONE)).unwrap();
+        editor.handle_event(PointerEvent::MouseMove(point((from.x + to.x) / 2.0, (from.y + to.y) / 2.0), Modifiers::NONE)).unwrap();
+        editor.handle_event(PointerEvent::MouseUp(to, Modifiers::NONE)).unwrap();
+    }
+
+    /// Verify drawn shapes stay out of the grid until finished, then are added as one undoable change and selected.
+    #[test]
+    fn draw() {
+        let mut editor = Editor::new(Grid::new(CoordinateSystem::TopLeftDownRight));
+        editor.set_tool(Tool::Draw(DrawKind::Rect));
+        assert_eq!(editor.get_tool(), Tool::Draw(DrawKind::Rect));
+
+        editor.handle_event(PointerEvent::MouseDown(point(10.0, 10.0), Modifiers::NONE)).unwrap();
+        editor.handle_event(PointerEvent::MouseMove(point(20.0, 20.0), Modifiers::NONE)).unwrap();
+        assert_eq!(editor.get_preview().unwrap().bounding_box(), Some(BoundingBox::new(10.0, 10.0, 10.0, 10.0)));
+        assert!(editor.get_grid().get_shape(0).is_none());
+
+        editor.handle_event(PointerEvent::MouseUp(point(30.0, 40.0), Modifiers::NONE)).unwrap();
+        assert!(editor.get_preview().is_none());
+        let rect = editor.get_grid().get_shape(0).unwrap();
+        assert_eq!(rect.bounding_box(), Some(BoundingBox::new(10.0, 10.0, 20.0, 30.0)));
+        assert_eq!(editor.get_selection().iter().collect::<Vec<&String>>(), vec![&rect.get_uuid()]);
+        assert_eq!(editor.get_grid().get_history().done().count(), 1);
+
+        // Switching tools abandons a shape being drawn
+        editor.handle_event(PointerEvent::MouseDown(point(0.0, 0.0), Modifiers::NONE)).unwrap();
+        editor.handle_event(PointerEvent::MouseMove(point(5.0, 5.0), Modifiers::NONE)).unwrap();
+        editor.set_tool(Tool::Draw(DrawKind::Rect));
+        assert!(editor.get_preview().is_none());
+        editor.handle_event(PointerEvent::MouseUp(point(5.0, 5.0), Modifiers::NONE)).unwrap();
+        assert!(editor.get_grid().get_shape(1).is_none());
+
+        editor.get_grid_mut().undo().unwrap();
+        assert!(editor.get_grid().get_shape(0).is_none());
+    }
+
+    /// Verify the pen's path is added once finished, and shapes drawn with no size are dropped.
+    #[test]
+    fn draw_path() {
+        let mut editor = Editor::new(Grid::new(CoordinateSystem::TopLeftDownRight));
+        editor.set_tool(Tool::Draw(DrawKind::Path));
+
+        for event in [
+            PointerEvent::MouseDown(point(0.0, 0.0), Modifiers::NONE),
+            PointerEvent::MouseUp(point(0.0, 0.0), Modifiers::NONE),
+            PointerEvent::MouseDown(point(10.0, 10.0), Modifiers::NONE),
+            PointerEvent::MouseUp(point(10.0, 10.0), Modifiers::NONE),
+            PointerEvent::MouseMove(point(20.0, 0.0), Modifiers::NONE),
+        ] {
+            editor.handle_event(event).unwrap();
+        }
+        assert_eq!(editor.get_preview().unwrap().to_html(), "<path d=\"M 0 0 L 10 10 L 20 0\"></path>");
+        assert!(editor.get_grid().get_shape(0).is_none());
+
+        editor.handle_event(PointerEvent::KeyDown(Key::Escape)).unwrap();
+        assert_eq!(editor.get_grid().get_shape(0).unwrap().to_html(), "<path d=\"M 0 0 L 10 10\"></path>");
+
+        // A single point, or a drag back to where it started, has nothing to show
+        editor.handle_event(PointerEvent::MouseDown(point(5.0, 5.0), Modifiers::NONE)).unwrap();
+        editor.handle_event(PointerEvent::KeyDown(Key::Escape)).unwrap();
+        editor.set_tool(Tool::Draw(DrawKind::Circle));
+        drag(&mut editor, point(5.0, 5.0), point(5.0, 5.0));
+        assert!(editor.get_grid().get_shape(1).is_none());
+
+        drag(&mut editor, point(5.0, 5.0), point(8.0, 9.0));
+        assert_eq!(editor.get_grid().get_shape(1).unwrap().to_html(), "<circle cx=\"5\" cy=\"5\" r=\"5\"></circle>");
+    }
+
+    /// Verify clicking selects the topmost shape under the mouse, and clicking empty space clears the selection.
+    #[test]
+    fn select() {
+        let mut grid = Grid::new(CoordinateSystem::TopLeftDownRight);
+        grid.add_shape(Rect::new(0.0, 0.0, 10.0, 10.0).box_ptr());
+        grid.add_shape(Rect::new(5.0, 5.0, 10.0, 10.0).box_ptr());
+        let top = grid.get_shape(1).unwrap().get_uuid();
+
+        let mut editor = Editor::new(grid);
+        assert_eq!(editor.get_tool(), Tool::Select);
+        click(&mut editor, point(7.0, 7.0), Modifiers::NONE);
+        assert_eq!(editor.get_selection(), &BTreeSet::from([top]));
+
+        click(&mut editor, point(50.0, 50.0), Modifiers::NONE);
+        assert!(editor.get_selection().is_empty());
+    }
+
+    /// Verify shift-clicking toggles shapes, and a rubber band selects what it touches or, in inside mode, encloses.
+    #[test]
+    fn multi_select() {
+        let mut grid = Grid::new(CoordinateSystem::TopLeftDownRight);
+        grid.add_shape(Rect::new(0.0, 0.0, 10.0, 10.0).box_ptr());
+        grid.add_shape(Rect::new(20.0, 0.0, 10.0, 10.0).box_ptr());
+        let (left, right) = (grid.get_shape(0).unwrap().get_uuid(), grid.get_shape(1).unwrap().get_uuid());
+
+        let mut editor = Editor::new(grid);
+        click(&mut editor, point(5.0, 5.0), Modifiers::NONE);
+        click(&mut editor, point(25.0, 5.0), Modifiers::SHIFT);
+        assert_eq!(editor.get_selection(), &BTreeSet::from([left.clone(), right.clone()]));
+        click(&mut editor, point(5.0, 5.0), Modifiers::SHIFT);
+        assert_eq!(editor.get_selection(), &BTreeSet::from([right.clone()]));
+
+        drag(&mut editor, point(-5.0, -5.0), point(25.0, 5.0));
+        assert_eq!(editor.get_selection(), &BTreeSet::from([left.clone(), right.clone()]));
+
+        editor.set_band_mode(BandMode::Inside);
+        drag(&mut editor, point(-5.0, -5.0), point(25.0, 15.0));
+        assert_eq!(editor.get_selection(), &BTreeSet::from([left.clone()]));
+
+        // Undoing away a selected shape drops it from the selection
+        click(&mut editor, point(25.0, 5.0), Modifiers::SHIFT);
+        editor.get_grid_mut().undo().unwrap();
+        click(&mut editor, point(5.0, 5.0), Modifiers::SHIFT);
+        assert!(editor.get_selection().is_empty());
+    }
+
+    /// Verify the selection outlines and rubber band are shown in the overlay, which is left out of saved and exported
+    /// drawings, and removed along with the editor.
+    #[test]
+    fn overlay() {
+        let mut grid = Grid::new(CoordinateSystem::TopLeftDownRight);
+        grid.add_shape(Rect::new(0.0, 0.0, 10.0, 10.0).box_ptr());
+        let mut editor = Editor::new(grid);
+        assert!(editor.get_grid().get_overlay().is_none());
+
+        editor.handle_event(PointerEvent::MouseDown(point(20.0, 20.0), Modifiers::NONE)).unwrap();
+        editor.handle_event(PointerEvent::MouseMove(point(-5.0, -5.0), Modifiers::NONE)).unwrap();
+        let band = editor.get_grid().get_overlay().unwrap().get_shape(0).unwrap();
+        assert_eq!(band.bounding_box(), Some(BoundingBox::new(-5.0, -5.0, 25.0, 25.0)));
+
+        editor.handle_event(PointerEvent::MouseUp(point(-5.0, -5.0), Modifiers::NONE)).unwrap();
+        let overlay = editor.get_grid().get_overlay().unwrap();
+        assert_eq!(overlay.get_shape_htmls(), vec![
+            "<rect x=\"0\" y=\"0\" width=\"10\" height=\"10\" fill=\"none\" stroke=\"#1e90ff\"></rect>",
+        ]);
+
+        let html = editor.get_grid().to_html();
+        assert!(html.contains("stroke=\"#1e90ff\""));
+        let mut svg = vec![];
+        editor.get_grid().write_svg(&mut svg, &SvgOptions::new()).unwrap();
+        assert!(!String::from_utf8(svg).unwrap().contains("#1e90ff"));
+        assert!(!editor.get_grid().to_json().unwrap().contains("#1e90ff"));
+
+        click(&mut editor, point(50.0, 50.0), Modifiers::NONE);
+        assert!(editor.get_grid().get_overlay().is_none());
+        click(&mut editor, point(5.0, 5.0), Modifiers::NONE);
+        assert!(editor.into_grid().get_overlay().is_none());
+    }
+
+    /// Verify dragging moves the shape under the mouse by the same offset in the grid's coordinates,
+    /// whatever transforms the shape and its groups have, as one undoable change.
+    #[test]
+    fn move_shapes() {
+        let mut grid = Grid::new(CoordinateSystem::TopLeftDownRight);
+        grid.add_shape(Rect::new(0.0, 0.0, 10.0, 10.0).box_ptr());
+        let mut rect = Rect::new(0.0, 0.0, 5.0, 5.0);
+        rect.set_transform(Transform::rotate(90.0));
+        let mut layer = Group::new("layer");
+        layer.add_shape(rect.box_ptr());
+        layer.set_transform(Transform::translate(100.0, 0.0).then(&Transform::scale(2.0, 2.0)));
+        grid.add_group(layer);
+        let plain = grid.get_shape(0).unwrap().get_uuid();
+        let transformed = grid.get_group("layer").unwrap().get_shape(0).unwrap().get_uuid();
+        let history = grid.get_history().done().count();
+
+        let mut editor = Editor::new(grid);
+        editor.set_tool(Tool::Move);
+        drag(&mut editor, point(5.0, 5.0), point(25.0, 15.0));
+        let bounds = |editor: &Editor, uuid: &str| {
+            let transform = editor.get_grid().parent_transform(uuid).unwrap();
+            editor.get_grid().get_by_uuid(uuid).unwrap().bounding_box_in(&transform).unwrap()
+        };
+        assert_eq!(bounds(&editor, &plain), BoundingBox::new(20.0, 10.0, 10.0, 10.0));
+        assert_eq!(editor.get_selection(), &BTreeSet::from([plain.clone()]));
+
+        let before = bounds(&editor, &transformed);
+        let start = point(before.min.x + 1.0, before.min.y + 1.0);
+        drag(&mut editor, start, point(start.x - 50.0, start.y + 4.0));
+        let after = bounds(&editor, &transformed);
+        assert_eq!(after.min, point(before.min.x - 50.0, before.min.y + 4.0));
+        assert_eq!(after.max, point(before.max.x - 50.0, before.max.y + 4.0));
+        assert_eq!(editor.get_grid().get_history().done().count(), history + 2);
+
+        editor.get_grid_mut().undo().unwrap();
+        assert_eq!(bounds(&editor, &transformed), before);
+
+        // Clicking without dragging, or on empty space, changes nothing
+        drag(&mut editor, start, start);
+        drag(&mut editor, point(500.0, 500.0), point(510.0, 510.0));
+        assert_eq!(editor.get_grid().get_history().done().count(), history + 1);
+    }
+
+    /// Verify dragging a selection of several shapes moves them all, as one change undone in one step.
+    #[test]
+    fn move_selection() {
+        let mut grid = Grid::new(CoordinateSystem::TopLeftDownRight);
+        grid.add_shape(Rect::new(0.0, 0.0, 10.0, 10.0).box_ptr());
+        grid.add_shape(Rect::new(20.0, 0.0, 10.0, 10.0).box_ptr());
+        let history = grid.get_history().done().count();
+
+        let mut editor = Editor::new(grid);
+        drag(&mut editor, point(-5.0, -5.0), point(25.0, 5.0));
+        assert_eq!(editor.get_selection().len(), 2);
+
+        editor.set_tool(Tool::Move);
+        drag(&mut editor, point(5.0, 5.0), point(10.0, 55.0));
+        let bounds = |editor: &Editor, i: usize| editor.get_grid().get_shape(i).unwrap().bounding_box();
+        assert_eq!(bounds(&editor, 0), Some(BoundingBox::new(5.0, 50.0, 10.0, 10.0)));
+        assert_eq!(bounds(&editor, 1), Some(BoundingBox::new(25.0, 50.0, 10.0, 10.0)));
+        assert_eq!(editor.get_grid().get_history().done().count(), history + 1);
+
+        let description = editor.get_grid_mut().undo().unwrap();
+        assert_eq!(description.matches("move #").count(), 2, "{}", description);
+        assert_eq!(bounds(&editor, 0), Some(BoundingBox::new(0.0, 0.0, 10.0, 10.0)));
+        assert_eq!(bounds(&editor, 1), Some(BoundingBox::new(20.0, 0.0, 10.0, 10.0)));
+
+        editor.get_grid_mut().redo().unwrap();
+        assert_eq!(bounds(&editor, 1), Some(BoundingBox::new(25.0, 50.0, 10.0, 10.0)));
+    }
+
+    /// Verify dragging a selected shape's handle resizes it as one undoable change, pressing elsewhere selects the
+    /// shape under the mouse, and the handles are shown in the overlay.
+    #[test]
+    fn resize() {
+        let mut grid = Grid::new(CoordinateSystem::TopLeftDownRight);
+        grid.add_shape(Rect::new(0.0, 0.0, 10.0, 10.0).box_ptr());
+        grid.add_shape(Rect::new(20.0, 0.0, 10.0, 10.0).box_ptr());
+        let (left, right) = (grid.get_shape(0).unwrap().get_uuid(), grid.get_shape(1).unwrap().get_uuid());
+
+        let mut editor = Editor::new(grid);
+        editor.set_tool(Tool::Resize);
+        assert!(editor.get_grid().get_overlay().is_none());
+        click(&mut editor, point(5.0, 5.0), Modifiers::NONE);
+        assert_eq!(editor.get_selection(), &BTreeSet::from([left.clone()]));
+        assert_eq!(editor.get_grid().get_overlay().unwrap().len(), 9);
+
+        drag(&mut editor, point(10.0, 10.0), point(15.0, 12.0));
+        assert_eq!(editor.get_grid().get_shape(0).unwrap().bounding_box(), Some(BoundingBox::new(0.0, 0.0, 15.0, 12.0)));
+        assert_eq!(editor.get_grid().get_history().done().count(), 3);
+
+        // Pressing away from the selected shape's handles selects the shape under the mouse, and then its handles
+        editor.handle_event(PointerEvent::MouseDown(point(22.0, 5.0), Modifiers::NONE)).unwrap();
+        assert_eq!(editor.get_selection(), &BTreeSet::from([right.clone()]));
+        drag(&mut editor, point(20.0, 5.0), point(18.0, 5.0));
+        assert_eq!(editor.get_grid().get_shape(1).unwrap().bounding_box(), Some(BoundingBox::new(18.0, 0.0, 12.0, 10.0)));
+
+        editor.handle_event(PointerEvent::MouseDown(point(30.0, 10.0), Modifiers::NONE)).unwrap();
+        editor.handle_event(PointerEvent::MouseMove(point(40.0, 40.0), Modifiers::NONE)).unwrap();
+        editor.handle_event(PointerEvent::KeyDown(Key::Escape)).unwrap();
+        assert_eq!(editor.get_grid().get_shape(1).unwrap().bounding_box(), Some(BoundingBox::new(18.0, 0.0, 12.0, 10.0)));
+
+        editor.get_grid_mut().undo().unwrap();
+        editor.get_grid_mut().undo().unwrap();
+        assert_eq!(editor.get_grid().get_shape(0).unwrap().bounding_box(), Some(BoundingBox::new(0.0, 0.0, 10.0, 10.0)));
+        assert_eq!(editor.get_grid().get_shape(1).unwrap().bounding_box(), Some(BoundingBox::new(20.0, 0.0, 10.0, 10.0)));
+    }
+
+    /// Verify switching tools mid-drag puts the shape back where it was, without recording anything.
+    #[test]
+    fn cancel_move() {
+        let mut grid = Grid::new(CoordinateSystem::TopLeftDownRight);
+        grid.add_shape(Rect::new(0.0, 0.0, 10.0, 10.0).box_ptr());
+        let mut editor = Editor::new(grid);
+        editor.set_tool(Tool::Move);
+
+        editor.handle_event(PointerEvent::MouseDown(point(5.0, 5.0), Modifiers::NONE)).unwrap();
+        editor.handle_event(PointerEvent::MouseMove(point(50.0, 5.0), Modifiers::NONE)).unwrap();
+        assert_eq!(editor.get_grid().get_shape(0).unwrap().bounding_box(), Some(BoundingBox::new(45.0, 0.0, 10.0, 10.0)));
+
+        editor.set_tool(Tool::Select);
+        assert_eq!(editor.get_grid().get_shape(0).unwrap().bounding_box(), Some(BoundingBox::new(0.0, 0.0, 10.0, 10.0)));
+        assert_eq!(editor.get_grid().get_history().done().count(), 1);
+        assert_eq!(editor.into_grid().shapes_at(point(5.0, 5.0), 0.0).len(), 1);
+    }
+}
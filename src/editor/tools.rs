@@ -0,0 +1,524 @@
+use std::marker::PhantomData;
+
+use crate::svg::{Point, SVG, Rect, Circle, Ellipse, Line, Path, Dimensions};
+use super::{UserInteraction, DrawTool, DrawState, Modifiers, Key};
+
+
+/// Follows the mouse from where it went down until it goes up, for the tools that draw a shape by dragging.
+struct Dragging {
+    origin: Option<Point>,
+    current: Option<Point>,
+    state: DrawState,
+}
+
+impl Dragging {
+    ///
+    fn new() -> Dragging {
+        Dragging { origin: None, current: None, state: DrawState::Waiting }
+    }
+
+    /// Start a new drag at `point`, abandoning any drag in progress.
+    fn start(&mut self, point: Point) {
+        self.origin = Some(point);
+        self.current = None;
+        self.state = DrawState::Ready;
+    }
+
+    /// Follow the mouse to `point`, returning whether there is a drag to follow.
+    fn follow(&mut self, point: Point) -> bool {
+        match self.state {
+            DrawState::Ready | DrawState::Drawing => {
+                self.current = Some(point);
+                self.state = DrawState::Drawing;
+                true
+            },
+            DrawState::Waiting => false,
+        }
+    }
+
+    /// End the drag at `point`, returning whether the mouse moved since it went down. A click without moving draws nothing.
+    /// The points are kept for drawing the final shape until `reset`.
+    fn finish(&mut self, point: Point) -> bool {
+        let moved = self.state == DrawState::Drawing;
+        if moved {
+            self.current = Some(point);
+        }
+
+        self.state = DrawState::Waiting;
+        moved
+    }
+
+    ///
+    fn reset(&mut self) {
+        self.origin = None;
+        self.current = None;
+        self.state = DrawState::Waiting;
+    }
+
+    /// Where the mouse went down and where it is now, once it has moved.
+    fn span(&self) -> Option<(Point, Point)> {
+        Some((self.origin?, self.current?))
+    }
+}
+
+
+/// Reshape `item` if there is one, otherwise make a new shape.
+fn draw<T: DrawTool>(tool: &T, item: Option<&mut T::Shape>) -> Option<T::Shape> {
+    match item {
+        Some(shape) => {
+            tool.update_shape(shape);
+            None
+        },
+        None => tool.create_shape(),
+    }
+}
+
+
+/// A shape that can be drawn by dragging from where the mouse went down to where it is now.
+pub trait DragShape: SVG + Sized {
+    /// A new shape spanning the drag from `origin` to `current`.
+    fn from_drag(origin: Point, current: Point) -> Self;
+
+    /// Reshape the shape to span the drag from `origin` to `current`, keeping its uuid, style and transform.
+    fn follow_drag(&mut self, origin: Point, current: Point);
+}
+
+/// Draws a rect with one corner where the mouse went down and the opposite corner following the mouse.
+impl DragShape for Rect {
+    fn from_drag(origin: Point, current: Point) -> Rect {
+        let (corner, width, height) = rect_span(origin, current);
+        Rect::new(corner.x, corner.y, width, height)
+    }
+
+    fn follow_drag(&mut self, origin: Point, current: Point) {
+        let (corner, width, height) = rect_span(origin, current);
+        self.move_to(corner.x, corner.y);
+        resize_drawn(self, Dimensions::Double(width, height));
+    }
+}
+
+/// The top left and size of the rect between `origin` and `current`.
+fn rect_span(origin: Point, current: Point) -> (Point, f64, f64) {
+    let corner = Point { x: origin.x.min(current.x), y: origin.y.min(current.y) };
+    (corner, (current.x - origin.x).abs(), (current.y - origin.y).abs())
+}
+
+/// Draws a circle centred where the mouse went down, reaching out to the mouse.
+impl DragShape for Circle {
+    fn from_drag(origin: Point, current: Point) -> Circle {
+        Circle::new(origin.x, origin.y, (current.x - origin.x).hypot(current.y - origin.y))
+    }
+
+    fn follow_drag(&mut self, origin: Point, current: Point) {
+        self.move_to(origin.x, origin.y);
+        resize_drawn(self, Dimensions::Single((current.x - origin.x).hypot(current.y - origin.y)));
+    }
+}
+
+/// Draws an ellipse centred where the mouse went down, with its box's corner following the mouse.
+impl DragShape for Ellipse {
+    fn from_drag(origin: Point, current: Point) -> Ellipse {
+        Ellipse::new(origin.x, origin.y, (current.x - origin.x).abs(), (current.y - origin.y).abs())
+    }
+
+    fn follow_drag(&mut self, origin: Point, current: Point) {
+        self.move_to(origin.x, origin.y);
+        resize_drawn(self, Dimensions::Double((current.x - origin.x).abs(), (current.y - origin.y).abs()));
+    }
+}
+
+/// Draws a line from where the mouse went down to the mouse.
+impl DragShape for Line {
+    fn from_drag(origin: Point, current: Point) -> Line {
+        Line::new(origin.x, origin.y, current.x, current.y)
+    }
+
+    fn follow_drag(&mut self, origin: Point, current: Point) {
+        resize_drawn(self, Dimensions::IndexPosition(0, origin.x, origin.y));
+        resize_drawn(self, Dimensions::IndexPosition(1, current.x, current.y));
+    }
+}
+
+/// Resize a shape being drawn to dimensions taken from a drag, which are always of the kind the shape accepts,
+/// with non-negative sizes. A shape rejecting them is a bug in its `DragShape` impl rather than something to handle.
+fn resize_drawn<S: DragShape>(shape: &mut S, dim: Dimensions) {
+    let resized = shape.resize(dim);
+    debug_assert!(resized.is_ok(), "{:?}", resized);
+}
+
+
+/// Draws a shape by dragging, creating it on the first move after the mouse goes down and reshaping it to follow
+/// the mouse until it comes up.
+pub struct DragTool<S: DragShape> {
+    drag: Dragging,
+    shape: PhantomData<S>,
+}
+
+///
+pub type RectTool = DragTool<Rect>;
+///
+pub type CircleTool = DragTool<Circle>;
+///
+pub type EllipseTool = DragTool<Ellipse>;
+///
+pub type LineTool = DragTool<Line>;
+
+impl<S: DragShape> DragTool<S> {
+    ///
+    pub fn new() -> DragTool<S> {
+        DragTool { drag: Dragging::new(), shape: PhantomData }
+    }
+}
+
+impl<S: DragShape> Default for DragTool<S> {
+    fn default() -> DragTool<S> {
+        DragTool::new()
+    }
+}
+
+impl<S: DragShape> UserInteraction for DragTool<S> {
+    type Arg = S;
+    type Result = S;
+
+    /// Start a new shape at `point`, abandoning any shape that was being drawn.
+    fn mouse_down(&mut self, point: Point, _: Modifiers, _: Option<&mut Self::Arg>) -> Option<Self::Result> {
+        self.drag.start(point);
+        None
+    }
+
+    ///
+    fn mouse_move(&mut self, point: Point, _: Modifiers, item: Option<&mut Self::Arg>) -> Option<Self::Result> {
+        if self.drag.follow(point) {
+            draw(self, item)
+        } else {
+            None
+        }
+    }
+
+    ///
+    fn mouse_up(&mut self, point: Point, _: Modifiers, item: Option<&mut Self::Arg>) -> Option<Self::Result> {
+        let result = if self.drag.finish(point) { draw(self, item) } else { None };
+        self.drag.reset();
+        result
+    }
+}
+
+impl<S: DragShape> DrawTool for DragTool<S> {
+    type Shape = S;
+
+    ///
+    fn create_shape(&self) -> Option<S> {
+        self.drag.span().map(|(origin, current)| S::from_drag(origin, current))
+    }
+
+    ///
+    fn update_shape(&self, shape: &mut S) {
+        if let Some((origin, current)) = self.drag.span() {
+            shape.follow_drag(origin, current);
+        }
+    }
+
+    ///
+    fn is_drawing(&self) -> bool {
+        self.drag.state == DrawState::Drawing
+    }
+}
+
+
+/// Draws a path of straight segments, adding a point with each click. Shift-clicking starts a new subpath,
+/// and double-clicking or pressing escape finishes the path. While drawing, the path reaches on to the mouse.
+pub struct PenTool {
+    subpaths: Vec<Vec<Point>>,
+    cursor: Option<Point>,
+    state: DrawState,
+}
+
+impl PenTool {
+    ///
+    pub fn new() -> PenTool {
+        PenTool { subpaths: vec![], cursor: None, state: DrawState::Waiting }
+    }
+
+    /// The points drawn so far, with the mouse's position at the end of the last subpath.
+    fn points(&self) -> Vec<Vec<Point>> {
+        let mut subpaths = self.subpaths.clone();
+
+        if let (Some(cursor), Some(last)) = (self.cursor, subpaths.last_mut()) {
+            last.push(cursor);
+        }
+
+        subpaths
+    }
+
+    /// Stop drawing, producing or reshaping the path one last time.
+    fn finish(&mut self, item: Option<&mut Path>) -> Option<Path> {
+        if self.state != DrawState::Drawing {
+            return None;
+        }
+
+        self.cursor = None;
+
+        // The second click of a double click lands on the point added by the first
+        if let Some(last) = self.subpaths.last_mut() {
+            if last.len() > 1 && last[last.len() - 1] == last[last.len() - 2] {
+                last.pop();
+            }
+        }
+
+        self.state = DrawState::Waiting;
+        let result = draw(self, item);
+        self.subpaths.clear();
+        result
+    }
+}
+
+impl Default for PenTool {
+    fn default() -> PenTool {
+        PenTool::new()
+    }
+}
+
+impl UserInteraction for PenTool {
+    type Arg = Path;
+    type Result = Path;
+
+    /// Add a point to the path, starting a new path if none is being drawn, or a new subpath if shift is held.
+    fn mouse_down(&mut self, point: Point, modifiers: Modifiers, item: Option<&mut Self::Arg>) -> Option<Self::Result> {
+        match self.subpaths.last_mut() {
+            Some(last) if self.state == DrawState::Drawing && !modifiers.shift => last.push(point),
+            _ if self.state == DrawState::Drawing => self.subpaths.push(vec![point]),
+            _ => {
+                self.subpaths = vec![vec![point]];
+                self.state = DrawState::Drawing;
+            },
+        }
+
+        self.cursor = None;
+        draw(self, item)
+    }
+
+    ///
+    fn mouse_move(&mut self, point: Point, _: Modifiers, item: Option<&mut Self::Arg>) -> Option<Self::Result> {
+        if self.state == DrawState::Drawing {
+            self.cursor = Some(point);
+            draw(self, item)
+        } else {
+            None
+        }
+    }
+
+    /// Points are added when the mouse goes down, so there is nothing to do when it comes up.
+    fn mouse_up(&mut self, _: Point, _: Modifiers, _: Option<&mut Self::Arg>) -> Option<Self::Result> {
+        None
+    }
+
+    ///
+    fn double_click(&mut self, _: Point, _: Modifiers, item: Option<&mut Self::Arg>) -> Option<Self::Result> {
+        self.finish(item)
+    }
+
+    ///
+    fn key_down(&mut self, key: Key, item: Option<&mut Self::Arg>) -> Option<Self::Result> {
+        match key {
+            Key::Escape => self.finish(item),
+        }
+    }
+}
+
+impl DrawTool for PenTool {
+    type Shape = Path;
+
+    ///
+    fn create_shape(&self) -> Option<Path> {
+        let mut subpaths = self.points().into_iter();
+        let mut path = Path::from_points(subpaths.next()?).ok()?;
+
+        for subpath in subpaths {
+            path.add_nested_path(subpath, false);
+        }
+
+        Some(path)
+    }
+
+    /// Paths can't drop points, so the path is rebuilt, keeping its uuid, style and transform.
+    fn update_shape(&self, shape: &mut Path) {
+        if let Some(mut path) = self.create_shape() {
+            path.uuid = shape.uuid.clone();
+            path.set_style(shape.get_style().clone());
+            path.set_transform(*shape.get_transform());
+            *shape = path;
+        }
+    }
+
+    ///
+    fn is_drawing(&self) -> bool {
+        self.state == DrawState::Drawing
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::svg::BoundingBox;
+    use super::super::PointerEvent;
+
+    ///
+    fn point(x: f64, y: f64) -> Point {
+        Point { x, y }
+    }
+
+    /// Play `events` to `tool`, keeping the shape it draws as the item for later events, like an editor does.
+    /// Returns the shape after each event.
+    fn script<T>(tool: &mut T, events: &[PointerEvent]) -> Vec<Option<T::Shape>>
+    where
+        T: DrawTool + UserInteraction<Arg = <T as DrawTool>::Shape, Result = <T as DrawTool>::Shape>,
+        T::Shape: Clone,
+    {
+        let mut shape = None;
+
+        events.iter().map(|event| {
+            if let Some(created) = tool.handle(*event, shape.as_mut()) {
+                shape = Some(created);
+            }
+            shape.clone()
+        }).collect()
+    }
+
+    ///
+    fn down(x: f64, y: f64) -> PointerEvent {
+        PointerEvent::MouseDown(point(x, y), Modifiers::NONE)
+    }
+
+    ///
+    fn shift_down(x: f64, y: f64) -> PointerEvent {
+        PointerEvent::MouseDown(point(x, y), Modifiers::SHIFT)
+    }
+
+    ///
+    fn to(x: f64, y: f64) -> PointerEvent {
+        PointerEvent::MouseMove(point(x, y), Modifiers::NONE)
+    }
+
+    ///
+    fn up(x: f64, y: f64) -> PointerEvent {
+        PointerEvent::MouseUp(point(x, y), Modifiers::NONE)
+    }
+
+    /// Verify dragging creates a rect on the first move, then reshapes it from the origin to the mouse until the mouse goes up.
+    #[test]
+    fn drag_rect() {
+        let mut tool = RectTool::new();
+        assert!(tool.mouse_move(point(5.0, 5.0), Modifiers::NONE, None).is_none());
+
+        assert!(tool.mouse_down(point(10.0, 20.0), Modifiers::NONE, None).is_none());
+        assert!(!tool.is_drawing());
+
+        let mut rect = tool.mouse_move(point(15.0, 30.0), Modifiers::NONE, None).unwrap();
+        assert!(tool.is_drawing());
+        assert_eq!(rect.bounding_box(), Some(BoundingBox::new(10.0, 20.0, 5.0, 10.0)));
+        let uuid = rect.get_uuid();
+
+        // Dragging back past the origin flips the rect to the other side
+        assert!(tool.mouse_move(point(4.0, 8.0), Modifiers::NONE, Some(&mut rect)).is_none());
+        assert_eq!(rect.bounding_box(), Some(BoundingBox::new(4.0, 8.0, 6.0, 12.0)));
+
+        assert!(tool.mouse_up(point(30.0, 25.0), Modifiers::NONE, Some(&mut rect)).is_none());
+        assert_eq!(rect.bounding_box(), Some(BoundingBox::new(10.0, 20.0, 20.0, 5.0)));
+        assert_eq!(rect.get_uuid(), uuid);
+        assert!(!tool.is_drawing());
+
+        // Once finished, moving no longer draws
+        assert!(tool.mouse_move(point(50.0, 50.0), Modifiers::NONE, Some(&mut rect)).is_none());
+        assert_eq!(rect.bounding_box(), Some(BoundingBox::new(10.0, 20.0, 20.0, 5.0)));
+    }
+
+    /// Verify a click without moving draws nothing, and events without an item produce a new rect.
+    #[test]
+    fn click_and_no_item() {
+        let mut tool = RectTool::new();
+        tool.mouse_down(point(1.0, 1.0), Modifiers::NONE, None);
+        assert!(tool.mouse_up(point(1.0, 1.0), Modifiers::NONE, None).is_none());
+        assert!(tool.create_shape().is_none());
+
+        tool.mouse_down(point(1.0, 1.0), Modifiers::NONE, None);
+        tool.mouse_move(point(2.0, 2.0), Modifiers::NONE, None);
+        let rect = tool.mouse_up(point(3.0, 5.0), Modifiers::NONE, None).unwrap();
+        assert_eq!(rect.bounding_box(), Some(BoundingBox::new(1.0, 1.0, 2.0, 4.0)));
+        assert!(tool.create_shape().is_none());
+    }
+
+    /// Verify circles are centred on the mouse down, with the radius reaching the mouse, and ellipses fit the drag's box.
+    #[test]
+    fn drag_circle_and_ellipse() {
+        let mut tool = CircleTool::new();
+        let shapes = script(&mut tool, &[down(10.0, 10.0), to(13.0, 14.0), to(10.0, 0.0), up(16.0, 18.0)]);
+        assert!(shapes[0].is_none());
+        assert_eq!(shapes[1].as_ref().unwrap().bounding_box(), Some(BoundingBox::new(5.0, 5.0, 10.0, 10.0)));
+        assert_eq!(shapes[2].as_ref().unwrap().bounding_box(), Some(BoundingBox::new(0.0, 0.0, 20.0, 20.0)));
+        assert_eq!(shapes[3].as_ref().unwrap().bounding_box(), Some(BoundingBox::new(0.0, 0.0, 20.0, 20.0)));
+        assert_eq!(shapes[1].as_ref().unwrap().get_uuid(), shapes[3].as_ref().unwrap().get_uuid());
+        assert!(!tool.is_drawing());
+
+        let mut tool = EllipseTool::new();
+        let shapes = script(&mut tool, &[down(10.0, 10.0), to(0.0, 15.0), up(13.0, 6.0)]);
+        assert_eq!(shapes[1].as_ref().unwrap().bounding_box(), Some(BoundingBox::new(0.0, 5.0, 20.0, 10.0)));
+        assert_eq!(shapes[2].as_ref().unwrap().bounding_box(), Some(BoundingBox::new(7.0, 6.0, 6.0, 8.0)));
+    }
+
+    /// Verify lines run from the mouse down to the mouse, in the direction dragged.
+    #[test]
+    fn drag_line() {
+        let mut tool = LineTool::new();
+        let shapes = script(&mut tool, &[down(10.0, 10.0), to(0.0, 0.0), up(20.0, 5.0)]);
+        assert_eq!(shapes[1].as_ref().unwrap().to_html(), "<line x1=\"10\" y1=\"10\" x2=\"0\" y2=\"0\"></line>");
+        assert_eq!(shapes[2].as_ref().unwrap().to_html(), "<line x1=\"10\" y1=\"10\" x2=\"20\" y2=\"5\"></line>");
+    }
+
+    /// Verify the pen adds a point per click, follows the mouse between clicks, starts subpaths on shift-click,
+    /// and finishes on a double click without keeping the point the second click added.
+    #[test]
+    fn pen_clicks() {
+        let mut tool = PenTool::new();
+        let shapes = script(&mut tool, &[
+            down(0.0, 0.0), up(0.0, 0.0),
+            to(5.0, 5.0),
+            down(10.0, 0.0), up(10.0, 0.0),
+            shift_down(20.0, 20.0), up(20.0, 20.0),
+            down(30.0, 20.0), up(30.0, 20.0),
+            to(40.0, 40.0),
+            down(30.0, 30.0), up(30.0, 30.0),
+            down(30.0, 30.0), up(30.0, 30.0),
+            PointerEvent::DoubleClick(point(30.0, 30.0), Modifiers::NONE),
+        ]);
+        let data = |i: usize| shapes[i].as_ref().unwrap().to_path_data();
+
+        assert_eq!(data(0), "M 0 0");
+        assert_eq!(data(2), "M 0 0 L 5 5");
+        assert_eq!(data(3), "M 0 0 L 10 0");
+        assert_eq!(data(5), "M 0 0 L 10 0 M 20 20");
+        assert_eq!(data(9), "M 0 0 L 10 0 M 20 20 L 30 20 L 40 40");
+        assert_eq!(data(12), "M 0 0 L 10 0 M 20 20 L 30 20 L 30 30 L 30 30");
+
+        assert_eq!(data(14), "M 0 0 L 10 0 M 20 20 L 30 20 L 30 30");
+        assert!(!tool.is_drawing());
+        assert!(tool.create_shape().is_none());
+        assert_eq!(shapes[0].as_ref().unwrap().get_uuid(), shapes[14].as_ref().unwrap().get_uuid());
+
+        // Once finished, clicking starts a new path
+        let shapes = script(&mut tool, &[down(1.0, 1.0)]);
+        assert_eq!(shapes[0].as_ref().unwrap().to_path_data(), "M 1 1");
+    }
+
+    /// Verify escape finishes the path where it is, leaving out the point following the mouse.
+    #[test]
+    fn pen_escape() {
+        let mut tool = PenTool::new();
+        assert!(tool.key_down(Key::Escape, None).is_none());
+
+        let shapes = script(&mut tool, &[down(0.0, 0.0), down(10.0, 0.0), to(50.0, 50.0), PointerEvent::KeyDown(Key::Escape)]);
+        assert_eq!(shapes[2].as_ref().unwrap().to_path_data(), "M 0 0 L 10 0 L 50 50");
+        assert_eq!(shapes[3].as_ref().unwrap().to_path_data(), "M 0 0 L 10 0");
+        assert!(!tool.is_drawing());
+    }
+}
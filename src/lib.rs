@@ -1,3 +1,6 @@
+#![allow(clippy::empty_docs)]
+
 pub mod cli;
+pub mod editor;
 pub mod svg;
-pub mod utils;
\ No newline at end of file
+pub mod utils;
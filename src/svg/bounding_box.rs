@@ -0,0 +1,139 @@
+use super::{Point, Transform};
+
+
+/// An axis aligned rectangle, from its smallest to its largest corner.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoundingBox {
+    pub min: Point,
+    pub max: Point,
+}
+
+impl BoundingBox {
+    ///
+    pub fn new(x: f64, y: f64, width: f64, height: f64) -> BoundingBox {
+        BoundingBox {
+            min: Point { x: x.min(x + width), y: y.min(y + height) },
+            max: Point { x: x.max(x + width), y: y.max(y + height) },
+        }
+    }
+
+    /// The smallest box containing every point, or `None` if there are no points.
+    pub fn from_points<I: IntoIterator<Item = Point>>(points: I) -> Option<BoundingBox> {
+        points.into_iter().fold(None, |bounds: Option<BoundingBox>, point| match bounds {
+            Some(bounds) => Some(bounds.include(point)),
+            None => Some(BoundingBox { min: point, max: point }),
+        })
+    }
+
+    ///
+    pub fn width(&self) -> f64 {
+        self.max.x - self.min.x
+    }
+
+    ///
+    pub fn height(&self) -> f64 {
+        self.max.y - self.min.y
+    }
+
+    ///
+    pub fn center(&self) -> Point {
+        Point { x: (self.min.x + self.max.x) / 2.0, y: (self.min.y + self.max.y) / 2.0 }
+    }
+
+    /// The four corners, clockwise from `min` in svg's y-down space.
+    pub fn corners(&self) -> [Point; 4] {
+        [
+            self.min,
+            Point { x: self.max.x, y: self.min.y },
+            self.max,
+            Point { x: self.min.x, y: self.max.y },
+        ]
+    }
+
+    /// The smallest box containing both this box and `point`.
+    pub fn include(&self, point: Point) -> BoundingBox {
+        BoundingBox {
+            min: Point { x: self.min.x.min(point.x), y: self.min.y.min(point.y) },
+            max: Point { x: self.max.x.max(point.x), y: self.max.y.max(point.y) },
+        }
+    }
+
+    ///
+    pub fn union(&self, other: &BoundingBox) -> BoundingBox {
+        self.include(other.min).include(other.max)
+    }
+
+    /// The box grown by `margin` on every side.
+    pub fn expand(&self, margin: f64) -> BoundingBox {
+        BoundingBox {
+            min: Point { x: self.min.x - margin, y: self.min.y - margin },
+            max: Point { x: self.max.x + margin, y: self.max.y + margin },
+        }
+    }
+
+    /// Whether the boxes overlap, including only touching at an edge.
+    pub fn intersects(&self, other: &BoundingBox) -> bool {
+        self.min.x <= other.max.x && other.min.x <= self.max.x && self.min.y <= other.max.y && other.min.y <= self.max.y
+    }
+
+    /// The smallest box containing this box's corners after `transform`.
+    pub fn transform(&self, transform: &Transform) -> BoundingBox {
+        BoundingBox::from_points(self.corners().iter().map(|&corner| transform.apply(corner))).unwrap()
+    }
+}
+
+
+/// The union of a list of optional boxes, ignoring the missing ones.
+pub(super) fn union_all<I: IntoIterator<Item = Option<BoundingBox>>>(boxes: I) -> Option<BoundingBox> {
+    boxes.into_iter()
+        .flatten()
+        .fold(None, |bounds: Option<BoundingBox>, next| match bounds {
+            Some(bounds) => Some(bounds.union(&next)),
+            None => Some(next),
+        })
+}
+
+
+/// Assert two boxes are equal, allowing for floating point error in computing the actual box.
+#[cfg(test)]
+pub(super) fn assert_close(actual: BoundingBox, expected: BoundingBox) {
+    let close = |a: f64, b: f64| (a - b).abs() < 1e-9;
+    assert!(
+        close(actual.min.x, expected.min.x) && close(actual.min.y, expected.min.y)
+            && close(actual.max.x, expected.max.x) && close(actual.max.y, expected.max.y),
+        "{:?} != {:?}", actual, expected
+    );
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verify boxes are normalized, and grow to contain points and other boxes.
+    #[test]
+    fn union() {
+        let bounds = BoundingBox::new(4.0, 4.0, -2.0, 1.0);
+        assert_eq!(bounds, BoundingBox { min: Point{ x: 2.0, y: 4.0 }, max: Point{ x: 4.0, y: 5.0 } });
+        assert_eq!(bounds.width(), 2.0);
+        assert_eq!(bounds.height(), 1.0);
+        assert_eq!(bounds.center(), Point{ x: 3.0, y: 4.5 });
+
+        assert_eq!(bounds.include(Point{ x: 0.0, y: 6.0 }), BoundingBox::new(0.0, 4.0, 4.0, 2.0));
+        assert_eq!(bounds.union(&BoundingBox::new(3.0, -1.0, 0.5, 0.5)), BoundingBox::new(2.0, -1.0, 2.0, 6.0));
+
+        assert_eq!(union_all(vec![None, Some(bounds), None]), Some(bounds));
+        assert_eq!(union_all(vec![None]), None);
+        assert_eq!(BoundingBox::from_points(vec![]), None);
+        assert_eq!(BoundingBox::from_points(vec![Point{ x: 1.0, y: 5.0 }, Point{ x: -1.0, y: 2.0 }]), Some(BoundingBox::new(-1.0, 2.0, 2.0, 3.0)));
+    }
+
+    /// Verify transformed boxes contain every transformed corner.
+    #[test]
+    fn transform() {
+        let bounds = BoundingBox::new(0.0, 0.0, 2.0, 1.0);
+        assert_eq!(bounds.transform(&Transform::translate(1.0, 1.0)), BoundingBox::new(1.0, 1.0, 2.0, 1.0));
+        assert_eq!(bounds.transform(&Transform::rotate(90.0)), BoundingBox::new(-1.0, 0.0, 1.0, 2.0));
+        assert_eq!(bounds.transform(&Transform::scale(-2.0, 1.0)), BoundingBox::new(-4.0, 0.0, 4.0, 1.0));
+    }
+}
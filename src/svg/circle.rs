@@ -1,11 +1,19 @@
+use serde::{Serialize, Deserialize};
+
 use crate::utils;
-use super::{Point, SVG, Dimensions, html_element};
+use super::{Point, SVG, Dimensions, Style, Transform, html_element};
+use super::curve::{Curve, EllipticalArc, Outline};
 
 
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Circle {
     uuid: String,
     origin: Point,
     radius: f64,
+    #[serde(default)]
+    style: Style,
+    #[serde(default)]
+    transform: Transform,
 }
 
 impl Circle {
@@ -14,7 +22,9 @@ impl Circle {
         Circle {
             uuid: utils::gen_uuid(),
             origin: Point { x, y },
-            radius
+            radius,
+            style: Style::new(),
+            transform: Transform::identity(),
         }
     }
 
@@ -30,13 +40,47 @@ impl SVG for Circle {
         self.uuid.clone()
     }
 
+    ///
+    fn clone_box(&self) -> Box<dyn SVG> {
+        Box::new(self.clone())
+    }
+
+    ///
+    fn duplicate(&self) -> Box<dyn SVG> {
+        Box::new(Circle { uuid: utils::gen_uuid(), ..self.clone() })
+    }
+
+    ///
+    fn get_style(&self) -> &Style {
+        &self.style
+    }
+
+    ///
+    fn get_style_mut(&mut self) -> &mut Style {
+        &mut self.style
+    }
+
+    ///
+    fn get_transform(&self) -> &Transform {
+        &self.transform
+    }
+
+    ///
+    fn get_transform_mut(&mut self) -> &mut Transform {
+        &mut self.transform
+    }
+
     ///
     fn to_html(&self) -> String {
-        html_element("circle", vec![
+        let mut attrs = vec![
             ("cx", self.origin.x.to_string()),
             ("cy", self.origin.y.to_string()),
             ("r", self.radius.to_string()),
-        ])
+        ];
+        attrs.extend(self.transform.to_attrs());
+        attrs.extend(self.style.to_attrs());
+
+        html_element("circle", attrs)
     }
 
     ///
@@ -45,6 +89,19 @@ impl SVG for Circle {
         self.origin.y = y;
     }
 
+    ///
+    fn move_by(&mut self, dx: f64, dy: f64) {
+        self.origin.x += dx;
+        self.origin.y += dy;
+    }
+
+    ///
+    fn outlines_in(&self, outer: &Transform) -> Vec<Outline> {
+        let arc = EllipticalArc::ellipse(self.origin, self.radius, self.radius);
+        let outline = Outline { start: arc.point_at_angle(0.0), curves: vec![Curve::Arc(arc)] };
+        vec![outline.transform(&self.transform.then(outer))]
+    }
+
     ///
     fn resize(&mut self, dim: Dimensions) -> Result<(), String> {
         if let Dimensions::Single(r) = dim {
@@ -72,6 +129,14 @@ mod tests {
         
         circle.resize(Dimensions::Single(3.0)).unwrap();
         assert_eq!(circle.to_html(),"<circle cx=\"-1.5\" cy=\"4\" r=\"3\"></circle>");
+
+        circle.get_style_mut().set_fill("#f00");
+        circle.get_style_mut().set_stroke_width(2.0).unwrap();
+        assert_eq!(circle.to_html(),"<circle cx=\"-1.5\" cy=\"4\" r=\"3\" fill=\"#f00\" stroke-width=\"2\"></circle>");
+
+        circle.rotate(90.0, 0.0, 0.0);
+        circle.translate(1.0, 2.0);
+        assert_eq!(circle.to_html(),"<circle cx=\"-1.5\" cy=\"4\" r=\"3\" transform=\"matrix(0 1 -1 0 1 2)\" fill=\"#f00\" stroke-width=\"2\"></circle>");
     }
 
     /// Verify the `move_to` function changes the origin of the circle, and the radius stays the same.
@@ -0,0 +1,479 @@
+use std::f64::consts::PI;
+
+use super::{Point, Transform, BoundingBox};
+
+
+/// How far flattened curves may stray from the real curve, in user units.
+const FLATTEN_TOLERANCE: f64 = 0.01;
+
+/// The most line segments a single curve is flattened into.
+const MAX_FLATTEN_SEGMENTS: f64 = 1024.0;
+
+
+/// A single piece of an outline with every point resolved to an absolute position,
+/// so it can be measured without knowing the pieces around it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Curve {
+    Line(Point, Point),
+    Quadratic(Point, Point, Point),
+    Cubic(Point, Point, Point, Point),
+    Arc(EllipticalArc),
+}
+
+/// A subpath of a shape's outline, starting at `start` and following each curve in turn.
+/// A subpath made of a single point has no curves.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Outline {
+    pub start: Point,
+    pub curves: Vec<Curve>,
+}
+
+/// The points `center + u * cos(angle) + v * sin(angle)` for angles from `start` to `start + sweep`.
+/// Describing the ellipse by the images of its axes keeps it closed under affine transforms.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EllipticalArc {
+    pub center: Point,
+    pub u: Point,
+    pub v: Point,
+    pub start: f64,
+    pub sweep: f64,
+}
+
+impl EllipticalArc {
+    /// A whole, axis aligned ellipse.
+    pub fn ellipse(center: Point, rx: f64, ry: f64) -> EllipticalArc {
+        EllipticalArc {
+            center,
+            u: Point { x: rx, y: 0.0 },
+            v: Point { x: 0.0, y: ry },
+            start: 0.0,
+            sweep: 2.0 * PI,
+        }
+    }
+
+    /// Convert an svg arc command from `from` to `to` into its center parameterization,
+    /// scaling up radii that are too small to reach, as described in the svg implementation notes.
+    /// Returns `None` if the arc is drawn as a straight line or not at all.
+    pub fn from_endpoints(
+        from: Point, to: Point, rx: f64, ry: f64, rotation: f64, large_arc: bool, sweep: bool
+    ) -> Option<EllipticalArc> {
+        let (mut rx, mut ry) = (rx.abs(), ry.abs());
+        if from == to || rx == 0.0 || ry == 0.0 {
+            return None;
+        }
+
+        let (sin, cos) = rotation.to_radians().sin_cos();
+        let (hx, hy) = ((from.x - to.x) / 2.0, (from.y - to.y) / 2.0);
+        let x1 = cos * hx + sin * hy;
+        let y1 = -sin * hx + cos * hy;
+
+        let lambda = (x1 * x1) / (rx * rx) + (y1 * y1) / (ry * ry);
+        if lambda > 1.0 {
+            rx *= lambda.sqrt();
+            ry *= lambda.sqrt();
+        }
+
+        let numerator = rx * rx * ry * ry - rx * rx * y1 * y1 - ry * ry * x1 * x1;
+        let denominator = rx * rx * y1 * y1 + ry * ry * x1 * x1;
+        let mut coefficient = (numerator / denominator).max(0.0).sqrt();
+        if large_arc == sweep {
+            coefficient = -coefficient;
+        }
+
+        let cx1 = coefficient * rx * y1 / ry;
+        let cy1 = -coefficient * ry * x1 / rx;
+
+        let start = ((y1 - cy1) / ry).atan2((x1 - cx1) / rx);
+        let end = ((-y1 - cy1) / ry).atan2((-x1 - cx1) / rx);
+        let mut delta = end - start;
+        if sweep && delta < 0.0 {
+            delta += 2.0 * PI;
+        } else if !sweep && delta > 0.0 {
+            delta -= 2.0 * PI;
+        }
+
+        Some(EllipticalArc {
+            center: Point {
+                x: cos * cx1 - sin * cy1 + (from.x + to.x) / 2.0,
+                y: sin * cx1 + cos * cy1 + (from.y + to.y) / 2.0,
+            },
+            u: Point { x: rx * cos, y: rx * sin },
+            v: Point { x: -ry * sin, y: ry * cos },
+            start,
+            sweep: delta,
+        })
+    }
+
+    ///
+    pub fn point_at_angle(&self, angle: f64) -> Point {
+        let (sin, cos) = angle.sin_cos();
+        Point {
+            x: self.center.x + self.u.x * cos + self.v.x * sin,
+            y: self.center.y + self.u.y * cos + self.v.y * sin,
+        }
+    }
+
+    /// Whether `angle` is passed through between the start and end of the arc.
+    fn contains_angle(&self, angle: f64) -> bool {
+        let offset = if self.sweep >= 0.0 { angle - self.start } else { self.start - angle };
+        offset.rem_euclid(2.0 * PI) <= self.sweep.abs()
+    }
+
+    ///
+    pub fn transform(&self, transform: &Transform) -> EllipticalArc {
+        let (ux, uy) = transform.apply_vector(self.u.x, self.u.y);
+        let (vx, vy) = transform.apply_vector(self.v.x, self.v.y);
+
+        EllipticalArc {
+            center: transform.apply(self.center),
+            u: Point { x: ux, y: uy },
+            v: Point { x: vx, y: vy },
+            ..*self
+        }
+    }
+
+    /// The ends of the arc, along with every point where it turns back horizontally or vertically.
+    fn extrema(&self) -> Vec<Point> {
+        let mut points = vec![self.point_at_angle(self.start), self.point_at_angle(self.start + self.sweep)];
+
+        // The derivative of `u.x * cos + v.x * sin` is zero at `atan2(v.x, u.x)`, and again half a turn later
+        for base in [self.v.x.atan2(self.u.x), self.v.y.atan2(self.u.y)] {
+            for angle in [base, base + PI] {
+                if self.contains_angle(angle) {
+                    points.push(self.point_at_angle(angle));
+                }
+            }
+        }
+
+        points
+    }
+}
+
+
+impl Curve {
+    ///
+    pub fn start(&self) -> Point {
+        match self {
+            Curve::Line(start, _) | Curve::Quadratic(start, _, _) | Curve::Cubic(start, _, _, _) => *start,
+            Curve::Arc(arc) => arc.point_at_angle(arc.start),
+        }
+    }
+
+    ///
+    pub fn end(&self) -> Point {
+        match self {
+            Curve::Line(_, end) | Curve::Quadratic(_, _, end) | Curve::Cubic(_, _, _, end) => *end,
+            Curve::Arc(arc) => arc.point_at_angle(arc.start + arc.sweep),
+        }
+    }
+
+    /// The same curve after `transform`, which is exact because affine transforms map Béziers and ellipses onto themselves.
+    pub fn transform(&self, transform: &Transform) -> Curve {
+        match self {
+            Curve::Line(p0, p1) => Curve::Line(transform.apply(*p0), transform.apply(*p1)),
+            Curve::Quadratic(p0, p1, p2) => Curve::Quadratic(transform.apply(*p0), transform.apply(*p1), transform.apply(*p2)),
+            Curve::Cubic(p0, p1, p2, p3) => Curve::Cubic(
+                transform.apply(*p0), transform.apply(*p1), transform.apply(*p2), transform.apply(*p3)
+            ),
+            Curve::Arc(arc) => Curve::Arc(arc.transform(transform)),
+        }
+    }
+
+    ///
+    pub fn point_at(&self, t: f64) -> Point {
+        let mt = 1.0 - t;
+        let blend = |weights: &[f64], points: &[Point]| Point {
+            x: weights.iter().zip(points).map(|(w, p)| w * p.x).sum(),
+            y: weights.iter().zip(points).map(|(w, p)| w * p.y).sum(),
+        };
+
+        match self {
+            Curve::Line(p0, p1) => blend(&[mt, t], &[*p0, *p1]),
+            Curve::Quadratic(p0, p1, p2) => blend(&[mt * mt, 2.0 * mt * t, t * t], &[*p0, *p1, *p2]),
+            Curve::Cubic(p0, p1, p2, p3) => blend(
+                &[mt * mt * mt, 3.0 * mt * mt * t, 3.0 * mt * t * t, t * t * t],
+                &[*p0, *p1, *p2, *p3],
+            ),
+            Curve::Arc(arc) => arc.point_at_angle(arc.start + arc.sweep * t),
+        }
+    }
+
+    /// The exact box of the curve, found from its ends and the points where it turns back on either axis.
+    pub fn bounding_box(&self) -> BoundingBox {
+        let points = match self {
+            Curve::Line(p0, p1) => vec![*p0, *p1],
+            Curve::Quadratic(p0, p1, p2) => {
+                let mut roots = quadratic_extrema(p0.x, p1.x, p2.x);
+                roots.extend(quadratic_extrema(p0.y, p1.y, p2.y));
+                self.with_points_at(&roots)
+            },
+            Curve::Cubic(p0, p1, p2, p3) => {
+                let mut roots = cubic_extrema(p0.x, p1.x, p2.x, p3.x);
+                roots.extend(cubic_extrema(p0.y, p1.y, p2.y, p3.y));
+                self.with_points_at(&roots)
+            },
+            Curve::Arc(arc) => arc.extrema(),
+        };
+
+        BoundingBox::from_points(points).unwrap()
+    }
+
+    /// Approximate the curve with straight lines no further than `tolerance` from it,
+    /// returning the points after the start, up to and including the end.
+    pub fn flatten(&self, tolerance: f64) -> Vec<Point> {
+        let length = |p: Point| p.x.hypot(p.y);
+        let second_difference = |p0: &Point, p1: &Point, p2: &Point| length(Point {
+            x: p0.x - 2.0 * p1.x + p2.x,
+            y: p0.y - 2.0 * p1.y + p2.y,
+        });
+
+        // Uniform subdivision counts that guarantee the tolerance, from Wang's formula for Béziers
+        let segments = match self {
+            Curve::Line(_, _) => 1.0,
+            Curve::Quadratic(p0, p1, p2) => (second_difference(p0, p1, p2) / (4.0 * tolerance)).sqrt().ceil(),
+            Curve::Cubic(p0, p1, p2, p3) => {
+                let most = second_difference(p0, p1, p2).max(second_difference(p1, p2, p3));
+                (3.0 * most / (4.0 * tolerance)).sqrt().ceil()
+            },
+            Curve::Arc(arc) => {
+                let radius = length(arc.u).max(length(arc.v));
+                if radius <= tolerance {
+                    1.0
+                } else {
+                    (arc.sweep.abs() / (2.0 * (1.0 - tolerance / radius).acos())).ceil()
+                }
+            },
+        };
+
+        let segments = segments.clamp(1.0, MAX_FLATTEN_SEGMENTS) as usize;
+        let mut points = (1..segments).map(|i| self.point_at(i as f64 / segments as f64)).collect::<Vec<Point>>();
+        points.push(self.end());
+        points
+    }
+
+    /// The ends of the curve, along with its points at each parameter in `ts`.
+    fn with_points_at(&self, ts: &[f64]) -> Vec<Point> {
+        let mut points = vec![self.start(), self.end()];
+        points.extend(ts.iter().map(|&t| self.point_at(t)));
+        points
+    }
+}
+
+
+impl Outline {
+    ///
+    pub fn transform(&self, transform: &Transform) -> Outline {
+        Outline {
+            start: transform.apply(self.start),
+            curves: self.curves.iter().map(|curve| curve.transform(transform)).collect(),
+        }
+    }
+
+    ///
+    pub fn bounding_box(&self) -> BoundingBox {
+        self.curves.iter().fold(
+            BoundingBox { min: self.start, max: self.start },
+            |bounds, curve| bounds.union(&curve.bounding_box()),
+        )
+    }
+
+    /// The outline as a list of points joined by straight lines, starting with `start`.
+    pub fn flatten(&self, tolerance: f64) -> Vec<Point> {
+        let mut points = vec![self.start];
+        points.extend(self.curves.iter().flat_map(|curve| curve.flatten(tolerance)));
+        points
+    }
+}
+
+
+/// The distance from `point` to the nearest part of any of the outlines, or infinity if there are none.
+pub(super) fn distance_to_outlines(outlines: &[Outline], point: Point) -> f64 {
+    outlines.iter()
+        .map(|outline| {
+            let points = outline.flatten(FLATTEN_TOLERANCE);
+            let to_start = distance_to_segment(point, points[0], points[0]);
+
+            points.windows(2)
+                .map(|pair| distance_to_segment(point, pair[0], pair[1]))
+                .fold(to_start, f64::min)
+        })
+        .fold(f64::INFINITY, f64::min)
+}
+
+/// Whether `point` is inside the area enclosed by the outlines, treating each one as closed like svg fills do.
+/// The nonzero rule fills wherever the outlines wind around the point, and the evenodd rule wherever they wind an odd number of times.
+pub(super) fn encloses(outlines: &[Outline], point: Point, even_odd: bool) -> bool {
+    let winding: i32 = outlines.iter()
+        .map(|outline| {
+            let mut points = outline.flatten(FLATTEN_TOLERANCE);
+            points.push(outline.start);
+            points.windows(2).map(|pair| winding_contribution(point, pair[0], pair[1])).sum::<i32>()
+        })
+        .sum();
+
+    if even_odd { winding % 2 != 0 } else { winding != 0 }
+}
+
+/// How the edge from `a` to `b` changes the winding number around `point`, counting upward crossings
+/// to the left of the point as +1 and downward crossings as -1.
+fn winding_contribution(point: Point, a: Point, b: Point) -> i32 {
+    let side = (b.x - a.x) * (point.y - a.y) - (point.x - a.x) * (b.y - a.y);
+
+    if a.y <= point.y && b.y > point.y && side > 0.0 {
+        1
+    } else if a.y > point.y && b.y <= point.y && side < 0.0 {
+        -1
+    } else {
+        0
+    }
+}
+
+///
+fn distance_to_segment(point: Point, a: Point, b: Point) -> f64 {
+    let (dx, dy) = (b.x - a.x, b.y - a.y);
+    let length_squared = dx * dx + dy * dy;
+
+    let t = if length_squared == 0.0 {
+        0.0
+    } else {
+        (((point.x - a.x) * dx + (point.y - a.y) * dy) / length_squared).clamp(0.0, 1.0)
+    };
+
+    (point.x - (a.x + t * dx)).hypot(point.y - (a.y + t * dy))
+}
+
+
+/// Parameters strictly between 0 and 1 where a quadratic Bézier's coordinate stops increasing or decreasing.
+fn quadratic_extrema(p0: f64, p1: f64, p2: f64) -> Vec<f64> {
+    let denominator = p0 - 2.0 * p1 + p2;
+    if denominator == 0.0 {
+        return vec![];
+    }
+
+    let t = (p0 - p1) / denominator;
+    if t > 0.0 && t < 1.0 { vec![t] } else { vec![] }
+}
+
+/// Parameters strictly between 0 and 1 where a cubic Bézier's coordinate stops increasing or decreasing,
+/// which are the roots of its derivative `a t^2 + b t + c` (divided by 3).
+fn cubic_extrema(p0: f64, p1: f64, p2: f64, p3: f64) -> Vec<f64> {
+    let a = -p0 + 3.0 * p1 - 3.0 * p2 + p3;
+    let b = 2.0 * (p0 - 2.0 * p1 + p2);
+    let c = p1 - p0;
+
+    let roots = if a.abs() < 1e-12 {
+        if b == 0.0 { vec![] } else { vec![-c / b] }
+    } else {
+        let discriminant = b * b - 4.0 * a * c;
+        if discriminant < 0.0 {
+            vec![]
+        } else {
+            let root = discriminant.sqrt();
+            vec![(-b + root) / (2.0 * a), (-b - root) / (2.0 * a)]
+        }
+    };
+
+    roots.into_iter().filter(|&t| t > 0.0 && t < 1.0).collect()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::bounding_box::assert_close;
+
+    /// Verify Bézier boxes reach the curve's extrema rather than its control points.
+    #[test]
+    fn bezier_bounding_box() {
+        let quadratic = Curve::Quadratic(Point{ x: 0.0, y: 0.0 }, Point{ x: 5.0, y: 10.0 }, Point{ x: 10.0, y: 0.0 });
+        assert_close(quadratic.bounding_box(), BoundingBox::new(0.0, 0.0, 10.0, 5.0));
+
+        let cubic = Curve::Cubic(
+            Point{ x: 0.0, y: 0.0 }, Point{ x: 0.0, y: 10.0 }, Point{ x: 10.0, y: 10.0 }, Point{ x: 10.0, y: 0.0 }
+        );
+        assert_close(cubic.bounding_box(), BoundingBox::new(0.0, 0.0, 10.0, 7.5));
+
+        // An s-curve turns back on the x axis twice
+        let cubic = Curve::Cubic(
+            Point{ x: 0.0, y: 0.0 }, Point{ x: 10.0, y: 0.0 }, Point{ x: -10.0, y: 10.0 }, Point{ x: 0.0, y: 10.0 }
+        );
+        let offset = 5.0 * 3.0_f64.sqrt() / 3.0;
+        assert_close(cubic.bounding_box(), BoundingBox::new(-offset, 0.0, 2.0 * offset, 10.0));
+    }
+
+    /// Verify svg arc commands are converted to the right center and sweep, including radii that are too small.
+    #[test]
+    fn from_endpoints() {
+        let from = Point{ x: 0.0, y: 0.0 };
+        let to = Point{ x: 10.0, y: 0.0 };
+
+        // A half circle sweeping through positive angles is clockwise, so passes above the chord in svg's y-down space
+        let arc = EllipticalArc::from_endpoints(from, to, 5.0, 5.0, 0.0, false, false).unwrap();
+        assert_close(Curve::Arc(arc).bounding_box(), BoundingBox::new(0.0, 0.0, 10.0, 5.0));
+        let arc = EllipticalArc::from_endpoints(from, to, 1.0, 1.0, 0.0, false, true).unwrap();
+        assert_close(Curve::Arc(arc).bounding_box(), BoundingBox::new(0.0, -5.0, 10.0, 5.0));
+
+        // The large arc of a circle bigger than the chord goes most of the way around
+        let arc = EllipticalArc::from_endpoints(from, to, 10.0, 10.0, 0.0, true, true).unwrap();
+        let height = 10.0 + 75.0_f64.sqrt();
+        assert_close(Curve::Arc(arc).bounding_box(), BoundingBox::new(-5.0, -height, 20.0, height));
+        assert!((arc.sweep - 5.0 * PI / 3.0).abs() < 1e-9);
+
+        assert_eq!(EllipticalArc::from_endpoints(from, from, 5.0, 5.0, 0.0, false, false), None);
+        assert_eq!(EllipticalArc::from_endpoints(from, to, 0.0, 5.0, 0.0, false, false), None);
+    }
+
+    /// Verify transformed curves have the exact box of the transformed shape, not of the transformed box.
+    #[test]
+    fn transformed_bounding_box() {
+        let circle = Curve::Arc(EllipticalArc::ellipse(Point{ x: 0.0, y: 0.0 }, 1.0, 1.0));
+        assert_close(circle.transform(&Transform::rotate(45.0)).bounding_box(), BoundingBox::new(-1.0, -1.0, 2.0, 2.0));
+
+        let ellipse = Curve::Arc(EllipticalArc::ellipse(Point{ x: 0.0, y: 0.0 }, 2.0, 1.0));
+        let extent = 2.5_f64.sqrt();
+        assert_close(ellipse.transform(&Transform::rotate(45.0)).bounding_box(), BoundingBox::new(-extent, -extent, 2.0 * extent, 2.0 * extent));
+        assert_close(ellipse.transform(&Transform::rotate(90.0)).bounding_box(), BoundingBox::new(-1.0, -2.0, 2.0, 4.0));
+    }
+
+    /// Verify flattened curves stay within the tolerance, and end exactly at the curve's end.
+    #[test]
+    fn flatten() {
+        let arc = Curve::Arc(EllipticalArc::ellipse(Point{ x: 0.0, y: 0.0 }, 10.0, 10.0));
+        let points = arc.flatten(0.01);
+        assert!(points.len() > 20);
+        assert_eq!(points.last(), Some(&arc.end()));
+        for pair in points.windows(2) {
+            let middle = Point{ x: (pair[0].x + pair[1].x) / 2.0, y: (pair[0].y + pair[1].y) / 2.0 };
+            assert!(10.0 - middle.x.hypot(middle.y) <= 0.01);
+        }
+
+        assert_eq!(Curve::Line(Point{ x: 0.0, y: 0.0 }, Point{ x: 5.0, y: 5.0 }).flatten(0.01), vec![Point{ x: 5.0, y: 5.0 }]);
+    }
+
+    /// Verify the fill rules, and distances to open and closed outlines.
+    #[test]
+    fn encloses_and_distance() {
+        let square = |size: f64| Outline {
+            start: Point{ x: 0.0, y: 0.0 },
+            curves: vec![
+                Curve::Line(Point{ x: 0.0, y: 0.0 }, Point{ x: size, y: 0.0 }),
+                Curve::Line(Point{ x: size, y: 0.0 }, Point{ x: size, y: size }),
+                Curve::Line(Point{ x: size, y: size }, Point{ x: 0.0, y: size }),
+            ],
+        };
+        let outlines = vec![square(10.0), square(5.0)];
+
+        // Overlapping outlines wound the same way fill their overlap with nonzero, but leave a hole with evenodd
+        let overlap = Point{ x: 2.0, y: 2.0 };
+        let outside_inner = Point{ x: 8.0, y: 8.0 };
+        assert!(encloses(&outlines, overlap, false));
+        assert!(!encloses(&outlines, overlap, true));
+        assert!(encloses(&outlines, outside_inner, true));
+        assert!(!encloses(&outlines, Point{ x: 11.0, y: 2.0 }, false));
+
+        // The implicit closing line is filled, but isn't part of the outline
+        assert_eq!(distance_to_outlines(&outlines, Point{ x: -3.0, y: 5.0 }), 3.0);
+        assert_eq!(distance_to_outlines(&[square(10.0)], Point{ x: -3.0, y: 5.0 }), 3.0_f64.hypot(5.0));
+        assert_eq!(distance_to_outlines(&[], overlap), f64::INFINITY);
+    }
+}
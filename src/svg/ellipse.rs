@@ -0,0 +1,180 @@
+use serde::{Serialize, Deserialize};
+
+use crate::utils;
+use super::{Point, SVG, Dimensions, Style, Transform, html_element};
+use super::curve::{Curve, EllipticalArc, Outline};
+
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Ellipse {
+    uuid: String,
+    origin: Point,
+    rx: f64,
+    ry: f64,
+    #[serde(default)]
+    style: Style,
+    #[serde(default)]
+    transform: Transform,
+}
+
+impl Ellipse {
+    ///
+    pub fn new(x: f64, y: f64, rx: f64, ry: f64) -> Ellipse {
+        Ellipse {
+            uuid: utils::gen_uuid(),
+            origin: Point { x, y },
+            rx,
+            ry,
+            style: Style::new(),
+            transform: Transform::identity(),
+        }
+    }
+
+    ///
+    pub fn box_ptr(self) -> Box<Self> {
+        Box::new(self)
+    }
+}
+
+impl SVG for Ellipse {
+    ///
+    fn get_uuid(&self) -> String {
+        self.uuid.clone()
+    }
+
+    ///
+    fn clone_box(&self) -> Box<dyn SVG> {
+        Box::new(self.clone())
+    }
+
+    ///
+    fn duplicate(&self) -> Box<dyn SVG> {
+        Box::new(Ellipse { uuid: utils::gen_uuid(), ..self.clone() })
+    }
+
+    ///
+    fn get_style(&self) -> &Style {
+        &self.style
+    }
+
+    ///
+    fn get_style_mut(&mut self) -> &mut Style {
+        &mut self.style
+    }
+
+    ///
+    fn get_transform(&self) -> &Transform {
+        &self.transform
+    }
+
+    ///
+    fn get_transform_mut(&mut self) -> &mut Transform {
+        &mut self.transform
+    }
+
+    ///
+    fn to_html(&self) -> String {
+        let mut attrs = vec![
+            ("cx", self.origin.x.to_string()),
+            ("cy", self.origin.y.to_string()),
+            ("rx", self.rx.to_string()),
+            ("ry", self.ry.to_string()),
+        ];
+        attrs.extend(self.transform.to_attrs());
+        attrs.extend(self.style.to_attrs());
+
+        html_element("ellipse", attrs)
+    }
+
+    ///
+    fn move_to(&mut self, x: f64, y: f64) {
+        self.origin.x = x;
+        self.origin.y = y;
+    }
+
+    ///
+    fn move_by(&mut self, dx: f64, dy: f64) {
+        self.origin.x += dx;
+        self.origin.y += dy;
+    }
+
+    ///
+    fn outlines_in(&self, outer: &Transform) -> Vec<Outline> {
+        let arc = EllipticalArc::ellipse(self.origin, self.rx, self.ry);
+        let outline = Outline { start: arc.point_at_angle(0.0), curves: vec![Curve::Arc(arc)] };
+        vec![outline.transform(&self.transform.then(outer))]
+    }
+
+    ///
+    fn resize(&mut self, dim: Dimensions) -> Result<(), String> {
+        if let Dimensions::Double(rx, ry) = dim {
+            self.rx = rx;
+            self.ry = ry;
+            Ok(())
+        } else {
+            Err(format!("Cannot resize Ellipse with dimensions {}", dim))
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verify the `to_html` function outputs an svg string reflecting the current state of the ellipse.
+    #[test]
+    fn to_html() {
+        let mut ellipse = Ellipse::new(0.0, 1.5, 5.2, 2.0);
+        assert_eq!(ellipse.to_html(),"<ellipse cx=\"0\" cy=\"1.5\" rx=\"5.2\" ry=\"2\"></ellipse>");
+
+        ellipse.move_to(-1.5, 4.0);
+        assert_eq!(ellipse.to_html(),"<ellipse cx=\"-1.5\" cy=\"4\" rx=\"5.2\" ry=\"2\"></ellipse>");
+
+        ellipse.resize(Dimensions::Double(3.0, 0.5)).unwrap();
+        assert_eq!(ellipse.to_html(),"<ellipse cx=\"-1.5\" cy=\"4\" rx=\"3\" ry=\"0.5\"></ellipse>");
+    }
+
+    /// Verify the `move_to` function changes the origin of the ellipse, and the radii stay the same.
+    #[test]
+    fn move_to() {
+        let mut ellipse = Ellipse::new(5.0, 10.0, 20.0, 8.0);
+        assert_eq!(ellipse.origin, Point{x: 5.0, y: 10.0});
+        assert_eq!(ellipse.rx, 20.0);
+        assert_eq!(ellipse.ry, 8.0);
+
+        ellipse.move_to(12.0, -16.0);
+        assert_eq!(ellipse.origin, Point{x: 12.0, y: -16.0});
+        assert_eq!(ellipse.rx, 20.0);
+        assert_eq!(ellipse.ry, 8.0);
+    }
+
+    /// Verify the `resize` function changes the radii of the ellipse independently, and the origin stays the same.
+    #[test]
+    fn resize() {
+        let mut ellipse = Ellipse::new(5.0, 10.0, 20.0, 8.0);
+        assert_eq!(ellipse.origin, Point{x: 5.0, y: 10.0});
+        assert_eq!(ellipse.rx, 20.0);
+        assert_eq!(ellipse.ry, 8.0);
+
+        // Verify resizing with a valid dimension
+        ellipse.resize(Dimensions::Double(3.5, 1.25)).unwrap();
+        assert_eq!(ellipse.origin, Point{x: 5.0, y: 10.0});
+        assert_eq!(ellipse.rx, 3.5);
+        assert_eq!(ellipse.ry, 1.25);
+
+        // Verify attempting to resize with invalid Single dimensions
+        let err = ellipse.resize(Dimensions::Single(1.0)).unwrap_err();
+        assert_eq!(err, "Cannot resize Ellipse with dimensions Single(1.0)");
+        assert_eq!(ellipse.origin, Point{x: 5.0, y: 10.0});
+        assert_eq!(ellipse.rx, 3.5);
+        assert_eq!(ellipse.ry, 1.25);
+
+        // Verify attempting to resize with invalid IndexPosition dimensions
+        let err = ellipse.resize(Dimensions::IndexPosition(3, 1.0, 2.0)).unwrap_err();
+        assert_eq!(err, "Cannot resize Ellipse with dimensions IndexPosition(3, 1.0, 2.0)");
+        assert_eq!(ellipse.origin, Point{x: 5.0, y: 10.0});
+        assert_eq!(ellipse.rx, 3.5);
+        assert_eq!(ellipse.ry, 1.25);
+    }
+}
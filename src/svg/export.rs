@@ -0,0 +1,184 @@
+use super::{SVG, Group, html_attrs, escape_xml};
+use super::group::as_group;
+use super::import::SVG_NAMESPACE;
+
+
+/// What to write into an exported svg document besides the drawing itself.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SvgOptions {
+    /// Written as the document's `<title>`, which viewers show as its name
+    pub title: Option<String>,
+    /// Written as the document's `<desc>`
+    pub description: Option<String>,
+}
+
+impl SvgOptions {
+    ///
+    pub fn new() -> SvgOptions {
+        SvgOptions::default()
+    }
+
+    ///
+    pub fn with_title(mut self, title: &str) -> SvgOptions {
+        self.title = Some(String::from(title));
+        self
+    }
+
+    ///
+    pub fn with_description(mut self, description: &str) -> SvgOptions {
+        self.description = Some(String::from(description));
+        self
+    }
+}
+
+
+/// A standalone svg 1.1 document around `content`, with the root element's own attributes in `attrs`.
+/// The namespace and version are added here, so `attrs` only needs the dimensions and styling.
+pub(super) fn svg_document(attrs: Vec<(&str, String)>, content: &str, options: &SvgOptions) -> String {
+    let mut root_attrs = vec![("xmlns", String::from(SVG_NAMESPACE)), ("version", String::from("1.1"))];
+    root_attrs.extend(attrs);
+
+    let mut children = vec![];
+    if let Some(title) = &options.title {
+        children.push(format!("<title>{}</title>", escape_xml(title)));
+    }
+    if let Some(description) = &options.description {
+        children.push(format!("<desc>{}</desc>", escape_xml(description)));
+    }
+    children.push(String::from(content));
+
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"no\"?>\n<svg {}>\n{}\n</svg>\n",
+        html_attrs(&root_attrs),
+        children.join("\n"),
+    )
+}
+
+/// The markup for a group in an exported document. Unlike `to_html`, the group's name is written as its `class`,
+/// since svg has no `name` attribute and ids would have to be unique.
+pub(super) fn group_html(group: &Group) -> String {
+    let mut attrs = vec![("class", group.get_name())];
+    attrs.extend(group.get_transform().to_attrs());
+    attrs.extend(group.get_style().to_attrs());
+
+    let children = (0..group.len())
+        .filter_map(|i| group.get_shape(i))
+        .map(shape_html)
+        .collect::<Vec<String>>();
+
+    format!("<g {}>\n{}\n</g>", html_attrs(&attrs), children.join("\n"))
+}
+
+///
+fn shape_html(shape: &dyn SVG) -> String {
+    match as_group(shape) {
+        Some(group) => group_html(group),
+        None => shape.to_html(),
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::{Grid, CoordinateSystem, Point, Rect, Text, Transform};
+    use roxmltree::Document;
+
+    /// Verify exported documents are well formed svg 1.1, with the canvas size, title and description.
+    #[test]
+    fn write_svg() {
+        let mut grid = Grid::new(CoordinateSystem::TopLeftDownRight);
+        grid.get_style_mut().set("fill", "none").unwrap();
+        grid.add_shape(Rect::new(1.0, 2.0, 3.0, 4.0).box_ptr());
+        grid.add_shape(Text::new(0.0, 0.0, "a < b").box_ptr());
+
+        let mut nested = Group::new("nested & named");
+        nested.add_shape(Rect::new(5.0, 5.0, 1.0, 1.0).box_ptr());
+        nested.set_transform(Transform::translate(1.0, 1.0));
+        grid.add_shape(nested.box_ptr());
+
+        let mut out = vec![];
+        let options = SvgOptions::new().with_title("Plan & section").with_description("Two \"views\"");
+        grid.write_svg(&mut out, &options).unwrap();
+        let source = String::from_utf8(out).unwrap();
+        assert!(source.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"no\"?>\n<svg "));
+
+        let document = Document::parse(&source).unwrap();
+        let root = document.root_element();
+        assert_eq!(root.tag_name().namespace(), Some(SVG_NAMESPACE));
+        assert_eq!(root.attribute("version"), Some("1.1"));
+        assert_eq!(root.attribute("width"), Some(grid.get_width().to_string().as_str()));
+        assert_eq!(root.attribute("height"), Some(grid.get_height().to_string().as_str()));
+        assert!(root.attribute("viewBox").is_some());
+        assert_eq!(root.attribute("fill"), Some("none"));
+
+        let elements = root.children().filter(|node| node.is_element()).collect::<Vec<_>>();
+        assert_eq!(elements[0].tag_name().name(), "title");
+        assert_eq!(elements[0].text(), Some("Plan & section"));
+        assert_eq!(elements[1].tag_name().name(), "desc");
+        assert_eq!(elements[1].text(), Some("Two \"views\""));
+
+        // Every element is in the svg namespace, and groups are named without the non-standard attribute
+        assert!(document.descendants().filter(|node| node.is_element()).all(|node| node.tag_name().namespace() == Some(SVG_NAMESPACE)));
+        assert!(document.descendants().all(|node| node.attribute("name").is_none()));
+        let nested = document.descendants().find(|node| node.attribute("class") == Some("nested & named")).unwrap();
+        assert_eq!(nested.attribute("transform"), Some("matrix(1 0 0 1 1 1)"));
+        assert_eq!(nested.first_element_child().unwrap().tag_name().name(), "rect");
+
+        // Exported documents import back as the same drawing
+        let (imported, warnings) = Grid::from_svg_str(&source).unwrap();
+        assert!(warnings.is_empty(), "{:?}", warnings);
+        assert_eq!(imported.content_bounding_box(), grid.content_bounding_box());
+        assert_eq!(imported.get_style(), grid.get_style());
+        assert!(imported.to_html().contains("<g name=\"nested &amp; named\" transform=\"matrix(1 0 0 1 1 1)\">"));
+    }
+
+    /// Verify the title and description are left out unless asked for, and up-positive grids keep their flip,
+    /// importing back with the same groups.
+    #[test]
+    fn options() {
+        let mut grid = Grid::new(CoordinateSystem::BottomLeftUpRight);
+        grid.add_shape(Rect::new(10.0, 20.0, 30.0, 40.0).box_ptr());
+        let mut nested = Group::new("nested");
+        nested.add_shape(Rect::new(0.0, 0.0, 1.0, 1.0).box_ptr());
+        grid.add_shape(nested.box_ptr());
+        let mut labels = Group::new("labels");
+        labels.get_style_mut().set_fill("red");
+        labels.add_shape(Text::new(5.0, 5.0, "A").box_ptr());
+        grid.add_group(labels);
+        let mut out = vec![];
+        grid.write_svg(&mut out, &SvgOptions::new()).unwrap();
+        let source = String::from_utf8(out).unwrap();
+
+        let document = Document::parse(&source).unwrap();
+        assert!(document.descendants().all(|node| !["title", "desc"].contains(&node.tag_name().name())));
+        let first = document.root_element().first_element_child().unwrap();
+        assert_eq!(first.tag_name().name(), "g");
+        assert!(first.attribute("transform").is_some());
+
+        // The non-zero view box origin survives the round trip, so the rect imports where it was drawn on the canvas
+        let (imported, warnings) = Grid::from_svg_str(&source).unwrap();
+        assert!(warnings.is_empty(), "{:?}", warnings);
+        let to_canvas = CoordinateSystem::BottomLeftUpRight.to_canvas(grid.get_width(), grid.get_height());
+        assert_eq!(imported.shapes_at(to_canvas.apply(Point{ x: 25.0, y: 40.0 }), 0.0).len(), 1);
+        assert!(imported.shapes_at(Point{ x: 25.0, y: 40.0 }, 0.0).is_empty());
+
+        // The grid's groups come back as groups rather than nested in the shapes group, so exporting again
+        // writes the same groups
+        let shapes = imported.get_group("shapes").unwrap();
+        assert_eq!(shapes.len(), 2);
+        assert!(shapes.get_shape(0).unwrap().to_html().starts_with("<rect "));
+        assert!(shapes.get_shape(1).unwrap().to_html().starts_with("<g name=\"nested\""));
+        let labels = imported.get_group("labels").unwrap();
+        assert_eq!(labels.len(), 1);
+        assert_eq!(labels.get_style(), grid.get_group("labels").unwrap().get_style());
+
+        let mut out = vec![];
+        imported.write_svg(&mut out, &SvgOptions::new()).unwrap();
+        let classes = |source: &str| Document::parse(source).unwrap().descendants()
+            .filter_map(|node| node.attribute("class").map(String::from))
+            .collect::<Vec<String>>();
+        assert_eq!(classes(&String::from_utf8(out).unwrap()), vec!["shapes", "nested", "labels"]);
+        assert_eq!(classes(&source), vec!["shapes", "nested", "labels"]);
+    }
+}
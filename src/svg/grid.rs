@@ -1,9 +1,24 @@
-use std::collections::HashMap;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::convert::TryFrom;
+use std::fs;
+use std::io::Write;
+
+use serde::{Serialize, Deserialize};
 
 use crate::utils;
-use super::{SVG, Dimensions, Group};
+use super::{Point, SVG, Dimensions, Group, Style, Transform, BoundingBox, Outline, html_attrs, move_child_by};
+use super::bounding_box::union_all;
+use super::group::{as_group, as_group_mut};
+use super::text::upright_text;
+use super::import::import_svg;
+use super::export::{SvgOptions, svg_document, group_html};
+use super::spatial_index::{SpatialIndex, ShapeKey};
+use super::history::{History, Operation, DEFAULT_DEPTH};
+use super::serialize;
 
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum CoordinateSystem {
     TopLeftDownRight, // Center (x,y) is top-left. Positive y is down. Positive x is right.
     BottomLeftUpRight, // Center (x,y) is bottom-left. Positive y is up. Positive x is right.
@@ -11,16 +26,97 @@ pub enum CoordinateSystem {
     MidMidDownRight, // Center (x, y) is absolute center. Positive y is down. Positive x is right.
 }
 
+impl CoordinateSystem {
+    ///
+    pub const ALL: [CoordinateSystem; 4] = [
+        CoordinateSystem::TopLeftDownRight,
+        CoordinateSystem::BottomLeftUpRight,
+        CoordinateSystem::MidMidUpRight,
+        CoordinateSystem::MidMidDownRight,
+    ];
+
+    /// The transform taking coordinates in this system to svg's own top-left, y-down coordinates
+    /// on a canvas of the given size.
+    pub fn to_canvas(&self, width: f64, height: f64) -> Transform {
+        match self {
+            CoordinateSystem::TopLeftDownRight => Transform::identity(),
+            CoordinateSystem::BottomLeftUpRight => Transform::new(1.0, 0.0, 0.0, -1.0, 0.0, height),
+            CoordinateSystem::MidMidUpRight => Transform::new(1.0, 0.0, 0.0, -1.0, width / 2.0, height / 2.0),
+            CoordinateSystem::MidMidDownRight => Transform::translate(width / 2.0, height / 2.0),
+        }
+    }
+}
+
+
+/// Shapes whose boxes may have changed since the spatial index was last brought up to date.
+enum Stale {
+    Keys(HashSet<ShapeKey>),
+    All,
+}
+
 
 /// Stores shapes and handles user interaction to make modifications
+#[derive(Serialize, Deserialize)]
+#[serde(try_from = "GridData")]
 pub struct Grid {
     uuid: String,
     width: f64,
     height: f64,
     coordinate_system: CoordinateSystem,
+    #[serde(skip)]
     view_box: String,
-    groups: HashMap<String, Group>,
-    shapes: HashMap<String, usize>
+    groups: Vec<Group>,
+    // Where each group's own shapes are, so they can be found by uuid without searching every group
+    #[serde(skip)]
+    shapes: HashMap<String, ShapeKey>,
+    // Painted boxes of each group's shapes, in the grid's coordinates before its own transform
+    #[serde(skip)]
+    index: RefCell<SpatialIndex>,
+    #[serde(skip)]
+    stale: RefCell<Stale>,
+    #[serde(skip)]
+    history: History,
+    #[serde(skip)]
+    overlay: Option<Group>,
+    style: Style,
+    transform: Transform,
+}
+
+/// The saved fields of a grid. Everything else is rebuilt from them when the grid is loaded.
+#[derive(Deserialize)]
+struct GridData {
+    uuid: String,
+    width: f64,
+    height: f64,
+    coordinate_system: CoordinateSystem,
+    groups: Vec<Group>,
+    #[serde(default)]
+    style: Style,
+    #[serde(default)]
+    transform: Transform,
+}
+
+impl TryFrom<GridData> for Grid {
+    type Error = String;
+
+    /// Rebuild a grid from its saved fields, which must include the shapes group new shapes are added to.
+    fn try_from(data: GridData) -> Result<Grid, String> {
+        if !data.groups.iter().any(|group| group.get_name() == "shapes") {
+            return Err(format!("Drawing {} has no \"shapes\" group", data.uuid));
+        }
+
+        let mut grid = Grid::new(data.coordinate_system);
+        grid.uuid = data.uuid;
+        grid.width = data.width;
+        grid.height = data.height;
+        grid.view_box = grid.init_view_box();
+        grid.style = data.style;
+        grid.transform = data.transform;
+        grid.groups = data.groups;
+        grid.rebuild_uuids();
+        grid.mark_all_stale();
+        Ok(grid)
+    }
 }
 
 impl Grid {
@@ -32,79 +128,810 @@ impl Grid {
             height: 1000.0,
             coordinate_system: c,
             view_box: String::new(),
-            groups: HashMap::new(),
+            groups: vec![],
             shapes: HashMap::new(),
+            index: RefCell::new(SpatialIndex::new()),
+            stale: RefCell::new(Stale::Keys(HashSet::new())),
+            history: History::new(DEFAULT_DEPTH),
+            overlay: None,
+            style: Style::new(),
+            transform: Transform::identity(),
         };
 
         grid.view_box = grid.init_view_box();
-        grid.groups.insert(String::from("shapes"), Group::new("shapes"));
+        grid.groups.push(Group::new("shapes"));
         grid
     }
 
+    /// Build a grid from the contents of an svg document, along with warnings for anything that couldn't be imported.
+    /// Documents written by `write_svg` import with the same groups they were exported with.
+    pub fn from_svg_str(source: &str) -> Result<(Grid, Vec<String>), String> {
+        let document = import_svg(source)?;
+        let mut grid = Grid::new(CoordinateSystem::TopLeftDownRight);
+
+        grid.width = document.width.unwrap_or(grid.width);
+        grid.height = document.height.unwrap_or(grid.height);
+        grid.view_box = grid.init_view_box();
+        grid.style = document.style;
+        grid.transform = document.transform;
+
+        if !document.layers.is_empty() {
+            grid.groups = document.layers;
+            // New shapes are added to the shapes group, which a document from elsewhere may not have
+            if grid.get_group("shapes").is_none() {
+                grid.groups.insert(0, Group::new("shapes"));
+            }
+            grid.rebuild_uuids();
+            grid.mark_all_stale();
+        }
+
+        for shape in document.shapes {
+            grid.add_shape(shape);
+        }
+
+        // The imported document is where editing starts, rather than something to undo
+        grid.history.clear();
+
+        Ok((grid, document.warnings))
+    }
+
+    ///
+    pub fn from_svg_file(path: &str) -> Result<(Grid, Vec<String>), String> {
+        let source = fs::read_to_string(path).map_err(|e| format!("Unable to read {}: {}", path, e))?;
+        Grid::from_svg_str(&source)
+    }
+
+    /// Save the grid as a json document, which keeps everything `from_json_str` needs to load it again as it is.
+    pub fn to_json(&self) -> Result<String, String> {
+        serialize::to_json(self)
+    }
+
+    /// Load a grid saved by `to_json`. The loaded grid starts with an empty history.
+    pub fn from_json_str(source: &str) -> Result<Grid, String> {
+        serialize::from_json(source)
+    }
+
+    ///
+    pub fn save_json_file(&self, path: &str) -> Result<(), String> {
+        fs::write(path, self.to_json()?).map_err(|e| format!("Unable to write {}: {}", path, e))
+    }
+
+    ///
+    pub fn from_json_file(path: &str) -> Result<Grid, String> {
+        let source = fs::read_to_string(path).map_err(|e| format!("Unable to read {}: {}", path, e))?;
+        Grid::from_json_str(&source)
+    }
+
+    /// A group drawn above the others by `to_html`, for things such as selection outlines that aren't part of the drawing.
+    /// The overlay isn't hit tested, recorded in the history, saved or exported.
+    pub fn set_overlay(&mut self, overlay: Option<Group>) {
+        self.overlay = overlay;
+    }
+
+    ///
+    pub fn get_overlay(&self) -> Option<&Group> {
+        self.overlay.as_ref()
+    }
+
+    /// Write the grid as a standalone svg 1.1 document, which can be opened on its own by other svg editors and viewers.
+    pub fn write_svg<W: Write>(&self, out: &mut W, options: &SvgOptions) -> Result<(), String> {
+        let mut attrs = vec![
+            ("width", self.width.to_string()),
+            ("height", self.height.to_string()),
+            ("viewBox", self.view_box.clone()),
+        ];
+        attrs.extend(self.style.to_attrs());
+
+        let document = svg_document(attrs, &self.content_html(self.groups.iter(), group_html), options);
+        out.write_all(document.as_bytes()).map_err(|e| format!("Unable to write svg: {}", e))
+    }
+
     ///
+    pub fn save_svg_file(&self, path: &str, options: &SvgOptions) -> Result<(), String> {
+        let mut file = fs::File::create(path).map_err(|e| format!("Unable to write {}: {}", path, e))?;
+        self.write_svg(&mut file, options)
+    }
+
+    /// The groups as markup inside the root element, wrapped in the root transform if the coordinate system needs one.
+    /// Text would be drawn upside down by an up-positive system's flip, so it is flipped back.
+    fn content_html<'a, I: Iterator<Item = &'a Group>>(&self, groups: I, group_html: fn(&Group) -> String) -> String {
+        let flipped = matches!(self.coordinate_system, CoordinateSystem::BottomLeftUpRight | CoordinateSystem::MidMidUpRight);
+        let groups_str = groups
+            .map(|group| if flipped {
+                let mut group = group.clone();
+                upright_text(&mut group);
+                group_html(&group)
+            } else {
+                group_html(group)
+            })
+            .collect::<Vec<String>>()
+            .join("\n");
+
+        let root_transform = self.root_transform();
+        if root_transform.is_identity() {
+            groups_str
+        } else {
+            format!("<g {}>\n{}\n</g>", html_attrs(&root_transform.to_attrs()), groups_str)
+        }
+    }
+
+    /// The viewBox placing the coordinate system's origin where its doc comment promises.
+    /// Up-positive systems also need `root_transform` to flip the y axis.
     fn init_view_box(&self) -> String {
         match self.coordinate_system {
             CoordinateSystem::TopLeftDownRight => {
-                return format!("0 0 {} {}", self.width, self.height);
+                format!("0 0 {} {}", self.width, self.height)
             },
             CoordinateSystem::BottomLeftUpRight => {
-                return format!("0 {} {} {}", self.height, self.width, self.height);
+                format!("0 {} {} {}", -self.height, self.width, self.height)
             },
             CoordinateSystem::MidMidDownRight | CoordinateSystem::MidMidUpRight => {
-                return format!("{} {} {} {}", self.width / 2.0, self.height / 2.0, self.width, self.height);
+                format!("{} {} {} {}", -self.width / 2.0, -self.height / 2.0, self.width, self.height)
             },
         }
     }
 
+    /// The transform from the grid's coordinates to the viewBox's, applied after the grid's own transform.
+    fn root_transform(&self) -> Transform {
+        let flip = match self.coordinate_system {
+            CoordinateSystem::TopLeftDownRight | CoordinateSystem::MidMidDownRight => Transform::identity(),
+            CoordinateSystem::BottomLeftUpRight | CoordinateSystem::MidMidUpRight => Transform::scale(1.0, -1.0),
+        };
+
+        self.transform.then(&flip)
+    }
+
     ///
-    fn transform_coordinates(&self, x: f64, y: f64, from: &CoordinateSystem) -> (f64, f64) {
-        let half_width = self.width / 2.0;
-        let half_height = self.height / 2.0;
+    pub fn get_coordinate_system(&self) -> CoordinateSystem {
+        self.coordinate_system
+    }
 
-        let mut result_x = x;
-        let mut result_y = y;
+    /// The transform re-expressing coordinates in `from` as coordinates in `to`, on this grid's canvas.
+    pub fn conversion(&self, from: CoordinateSystem, to: CoordinateSystem) -> Transform {
+        let to_canvas = to.to_canvas(self.width, self.height);
+        // Every system's transform is a translation and possibly a flip, so it can always be inverted
+        from.to_canvas(self.width, self.height).then(&to_canvas.inverse().unwrap())
+    }
 
-        match (from, &self.coordinate_system) {
-            (CoordinateSystem::TopLeftDownRight, CoordinateSystem::MidMidDownRight) => {
-                result_x = x - half_width;
-                result_y = y - half_height;
-            },
-            (CoordinateSystem::TopLeftDownRight, CoordinateSystem::MidMidUpRight) => {
-                result_x = x - half_width;
-                result_y = half_height - y;
-            },
-            _ => panic!("Support for other transformations has not been implemented."),
+    /// Re-express a point given in `from` in the grid's coordinate system.
+    pub fn transform_coordinates(&self, x: f64, y: f64, from: CoordinateSystem) -> (f64, f64) {
+        let point = self.conversion(from, self.coordinate_system).apply(Point { x, y });
+        (point.x, point.y)
+    }
+
+    /// Switch the grid to a new coordinate system, re-expressing the existing shapes so they stay in place on the canvas.
+    pub fn set_coordinate_system(&mut self, c: CoordinateSystem) {
+        let conversion = self.conversion(self.coordinate_system, c);
+
+        for group in &mut self.groups {
+            convert_shape(group, conversion);
         }
 
-        (result_x, result_y)
+        self.coordinate_system = c;
+        self.view_box = self.init_view_box();
+        self.mark_all_stale();
+
+        // Recorded shapes are in the old system's coordinates, so undoing would put them in the wrong place
+        self.history.clear();
     }
 
-    /// Grid should own the group.
+    /// Grid should own the group. A group with the same name as an existing one replaces it in place.
     pub fn add_group(&mut self, group: Group) -> &Group {
-        let key = group.get_name();
-        self.groups.insert(key.clone(), group);
-        self.groups.get(&key).unwrap()
+        // Groups are drawn in the order they were first added, and replaced in place by name
+        let (index, before) = match self.groups.iter().position(|existing| existing.get_name() == group.get_name()) {
+            Some(i) => (i, Some(self.groups[i].clone().box_ptr())),
+            None => (self.groups.len(), None),
+        };
+
+        self.perform(Operation::Group { index, before, after: Some(group.box_ptr()) }).unwrap();
+        &self.groups[index]
+    }
+
+    /// Add a shape whose coordinates are given in `from` rather than the grid's coordinate system.
+    pub fn add_shape_in(&mut self, mut shape: Box<dyn SVG>, from: CoordinateSystem) -> usize {
+        convert_shape(shape.as_mut(), self.conversion(from, self.coordinate_system));
+        self.add_shape(shape)
+    }
+
+    ///
+    pub fn get_group(&self, name: &str) -> Option<&Group> {
+        self.groups.iter().find(|group| group.get_name() == name)
+    }
+
+    /// Since the group's shapes may be changed in any way, the whole spatial index is rebuilt before the next query.
+    /// Changes made through the group aren't recorded in the history.
+    pub fn get_group_mut(&mut self, name: &str) -> Option<&mut Group> {
+        self.mark_all_stale();
+        self.groups.iter_mut().find(|group| group.get_name() == name)
     }
 
     /// Group should own the shape, Grid should reference the shape;
     pub fn add_shape(&mut self, shape: Box<dyn SVG>) -> usize {
-        let id = shape.get_uuid();
-        let shapes_group = self.groups.get_mut("shapes").unwrap();
+        let group = &self.groups[self.shapes_group_index()];
+        let (parent, index) = (group.get_uuid(), group.len());
 
-        let shape_idx = shapes_group.add_shape(shape);
-        self.shapes.insert(id, shape_idx);
+        self.perform(Operation::Add { parent, index, shape }).unwrap();
+        index
+    }
 
-        shape_idx
+    /// Move a shape in the shapes group.
+    pub fn move_shape(&mut self, i: usize, x: f64, y: f64) -> Result<(), String> {
+        let uuid = self.uuid_at(i)?;
+        self.move_shape_by_uuid(&uuid, x, y)
     }
 
-    /// 
-    pub fn get_shape(&self, i: usize) -> Option<&Box<dyn SVG>> {
-        self.groups.get("shapes").unwrap().get_shape(i)
+    /// Resize a shape in the shapes group.
+    pub fn resize_shape(&mut self, i: usize, dim: Dimensions) -> Result<(), String> {
+        let uuid = self.uuid_at(i)?;
+        self.resize_shape_by_uuid(&uuid, dim)
+    }
+
+    /// Move the shape with `uuid` to (x, y), in its parent's coordinates.
+    pub fn move_shape_by_uuid(&mut self, uuid: &str, x: f64, y: f64) -> Result<(), String> {
+        let before = self.clone_by_uuid(uuid)?;
+        let mut after = before.clone_box();
+        after.move_to(x, y);
+
+        self.perform(Operation::Move { before, after })
+    }
+
+    /// Resize the shape with `uuid`. Dimensions the shape can't take leave it, and the history, unchanged.
+    pub fn resize_shape_by_uuid(&mut self, uuid: &str, dim: Dimensions) -> Result<(), String> {
+        let before = self.clone_by_uuid(uuid)?;
+        let mut after = before.clone_box();
+        after.resize(dim)?;
+
+        self.perform(Operation::Resize { before, after })
+    }
+
+    ///
+    pub fn set_style_by_uuid(&mut self, uuid: &str, style: Style) -> Result<(), String> {
+        let before = self.clone_by_uuid(uuid)?.get_style().clone();
+        self.perform(Operation::Style { uuid: String::from(uuid), before, after: style })
+    }
+
+    /// Apply `transform` on top of any transform the shape with `uuid` already has.
+    pub fn apply_transform_by_uuid(&mut self, uuid: &str, transform: Transform) -> Result<(), String> {
+        let before = *self.clone_by_uuid(uuid)?.get_transform();
+        self.perform(Operation::Transform { uuid: String::from(uuid), before, after: before.then(&transform) })
+    }
+
+    /// The shape with `uuid` in any group, including shapes inside nested groups.
+    pub fn get_by_uuid(&self, uuid: &str) -> Option<&dyn SVG> {
+        let (group_idx, shape_idx) = self.locate(uuid)?;
+        let shape = self.groups[group_idx].get_shape(shape_idx)?;
+
+        if shape.get_uuid() == uuid {
+            Some(shape)
+        } else {
+            as_group(shape)?.find(uuid)
+        }
+    }
+
+    /// The shape may be changed in any way, so the entry of the shape containing it is refreshed before the next query.
+    /// Changes made through it aren't recorded in the history.
+    pub fn get_by_uuid_mut(&mut self, uuid: &str) -> Option<&mut Box<dyn SVG>> {
+        let key = self.locate(uuid)?;
+        self.mark_stale(key);
+        self.groups[key.0].find_mut(uuid)
+    }
+
+    /// The uuid of the group directly holding the shape with `uuid`, and the shape's index in it.
+    pub fn parent_of(&self, uuid: &str) -> Option<(String, usize)> {
+        let (group_idx, shape_idx) = self.locate(uuid)?;
+        let group = &self.groups[group_idx];
+        let shape = group.get_shape(shape_idx)?;
+
+        if shape.get_uuid() == uuid {
+            return Some((group.get_uuid(), shape_idx));
+        }
+
+        as_group(shape)?.parent_of(uuid).map(|(parent, i)| (parent.get_uuid(), i))
+    }
+
+    /// The transform from the coordinates of the group directly holding the shape with `uuid` to the grid's coordinates.
+    pub fn parent_transform(&self, uuid: &str) -> Option<Transform> {
+        let (group_idx, shape_idx) = self.locate(uuid)?;
+        let group = &self.groups[group_idx];
+        let mut transform = group.get_transform().then(&self.transform);
+        let mut shape = group.get_shape(shape_idx)?;
+
+        // Descend through the nested groups containing the shape, which each add their own transform
+        while shape.get_uuid() != uuid {
+            let nested = as_group(shape)?;
+            transform = nested.get_transform().then(&transform);
+            shape = nested.get_shape(nested.position(uuid)?)?;
+        }
+
+        Some(transform)
+    }
+
+    /// The style the shape with `uuid` inherits from the grid and the groups enclosing it.
+    pub fn parent_style(&self, uuid: &str) -> Option<Style> {
+        let (group_idx, shape_idx) = self.locate(uuid)?;
+        let group = &self.groups[group_idx];
+        let mut style = group.get_style().inherit(&self.style);
+        let mut shape = group.get_shape(shape_idx)?;
+
+        while shape.get_uuid() != uuid {
+            let nested = as_group(shape)?;
+            style = nested.get_style().inherit(&style);
+            shape = nested.get_shape(nested.position(uuid)?)?;
+        }
+
+        Some(style)
+    }
+
+    /// Remove the shape with `uuid` from whichever group holds it.
+    pub fn remove_by_uuid(&mut self, uuid: &str) -> Option<Box<dyn SVG>> {
+        let (parent, index) = self.parent_of(uuid)?;
+        let shape = self.clone_by_uuid(uuid).ok()?;
+
+        self.perform(Operation::Delete { parent, index, shape: shape.clone_box() }).ok()?;
+        Some(shape)
+    }
+
+    /// Copy the shape with `uuid`, placing the copy just above it and offset by `dx` and `dy`. Returns the copy's uuid.
+    pub fn duplicate_by_uuid(&mut self, uuid: &str, dx: f64, dy: f64) -> Result<String, String> {
+        let mut copy = self.clone_by_uuid(uuid)?.duplicate();
+        move_child_by(copy.as_mut(), dx, dy);
+
+        let id = copy.get_uuid();
+        let (parent, index) = self.parent_of(uuid).unwrap();
+        self.perform(Operation::Add { parent, index: index + 1, shape: copy })?;
+
+        Ok(id)
+    }
+
+    /// Move the shape with `uuid` one step up the stacking order of its group, drawing it above the next shape.
+    pub fn raise(&mut self, uuid: &str) -> Result<(), String> {
+        self.restack(uuid, |i, len| (i + 1).min(len - 1))
+    }
+
+    /// Move the shape with `uuid` one step down the stacking order of its group, drawing it below the previous shape.
+    pub fn lower(&mut self, uuid: &str) -> Result<(), String> {
+        self.restack(uuid, |i, _| i.saturating_sub(1))
+    }
+
+    /// Draw the shape with `uuid` above every other shape in its group.
+    pub fn to_front(&mut self, uuid: &str) -> Result<(), String> {
+        self.restack(uuid, |_, len| len - 1)
+    }
+
+    /// Draw the shape with `uuid` below every other shape in its group.
+    pub fn to_back(&mut self, uuid: &str) -> Result<(), String> {
+        self.restack(uuid, |_, _| 0)
+    }
+
+    /// Move the shape with `uuid` to the index `target` picks from its current index and the length of its group.
+    /// Shapes already where they would be moved to are left alone, without recording anything.
+    fn restack<F>(&mut self, uuid: &str, target: F) -> Result<(), String>
+        where F: FnOnce(usize, usize) -> usize
+    {
+        let (parent, from) = match self.parent_of(uuid) {
+            Some(found) => found,
+            None => return Err(format!("No shape found with uuid #{}", uuid)),
+        };
+        let to = target(from, self.group_by_uuid(&parent).unwrap().len());
+
+        if from == to {
+            return Ok(());
+        }
+
+        self.perform(Operation::Restack { uuid: String::from(uuid), from, to })
+    }
+
+    /// Apply an operation and record it, so it can be undone.
+    pub fn perform(&mut self, operation: Operation) -> Result<(), String> {
+        operation.apply(self)?;
+        self.history.record(operation);
+        Ok(())
+    }
+
+    /// Revert the most recent operation, returning what it was.
+    pub fn undo(&mut self) -> Result<String, String> {
+        let operation = match self.history.pop_done() {
+            Some(operation) => operation,
+            None => return Err(String::from("Nothing to undo")),
+        };
+
+        match operation.inverse().apply(self) {
+            Ok(()) => {
+                let description = operation.to_string();
+                self.history.push_undone(operation);
+                Ok(description)
+            },
+            Err(e) => {
+                self.history.push_done(operation);
+                Err(e)
+            },
+        }
+    }
+
+    /// Apply the most recently undone operation again, returning what it was.
+    pub fn redo(&mut self) -> Result<String, String> {
+        let operation = match self.history.pop_undone() {
+            Some(operation) => operation,
+            None => return Err(String::from("Nothing to redo")),
+        };
+
+        match operation.apply(self) {
+            Ok(()) => {
+                let description = operation.to_string();
+                self.history.push_done(operation);
+                Ok(description)
+            },
+            Err(e) => {
+                self.history.push_undone(operation);
+                Err(e)
+            },
+        }
+    }
+
+    ///
+    pub fn get_history(&self) -> &History {
+        &self.history
+    }
+
+    /// Change how many operations can be undone, forgetting the oldest ones if there are now too many.
+    pub fn set_history_depth(&mut self, depth: usize) {
+        self.history.set_depth(depth);
+    }
+
+    /// Insert a shape into the group with uuid `parent`, without recording it.
+    pub(super) fn insert_into(&mut self, parent: &str, index: usize, shape: Box<dyn SVG>) -> Result<(), String> {
+        self.edit_group(parent, |group| {
+            group.insert_shape(index, shape);
+            index
+        })
+    }
+
+    /// Remove the shape with `uuid`, without recording it.
+    pub(super) fn take_by_uuid(&mut self, uuid: &str) -> Result<Box<dyn SVG>, String> {
+        let mut removed = None;
+        self.edit_parent(uuid, |group, i| {
+            removed = group.remove_shape(i);
+            i
+        })?;
+
+        self.shapes.remove(uuid);
+        Ok(removed.unwrap())
+    }
+
+    /// Put `shape` in place of the shape with the same uuid, without recording it.
+    pub(super) fn replace_by_uuid(&mut self, shape: Box<dyn SVG>) -> Result<(), String> {
+        let uuid = shape.get_uuid();
+
+        match self.get_by_uuid_mut(&uuid) {
+            Some(existing) => {
+                *existing = shape;
+                Ok(())
+            },
+            None => Err(format!("No shape found with uuid #{}", uuid)),
+        }
+    }
+
+    /// Move the shape with `uuid` to index `to` within its group, without recording it.
+    pub(super) fn reorder_by_uuid(&mut self, uuid: &str, to: usize) -> Result<(), String> {
+        let mut result = Ok(());
+        self.edit_parent(uuid, |group, i| {
+            result = group.reorder_shape(i, to);
+            i.min(to)
+        })?;
+
+        result
+    }
+
+    /// Insert one of the grid's own groups, without recording it.
+    pub(super) fn insert_group(&mut self, index: usize, group: Group) {
+        self.groups.insert(index.min(self.groups.len()), group);
+        self.rebuild_uuids();
+        self.mark_all_stale();
+    }
+
+    /// Remove one of the grid's own groups, without recording it.
+    pub(super) fn remove_group(&mut self, index: usize) -> Result<Group, String> {
+        if index >= self.groups.len() {
+            return Err(format!("No group found at index {}", index));
+        }
+
+        let group = self.groups.remove(index);
+        self.rebuild_uuids();
+        self.mark_all_stale();
+        Ok(group)
+    }
+
+    /// Edit the group directly holding the shape with `uuid`, given the shape's index in it.
+    fn edit_parent<F>(&mut self, uuid: &str, edit: F) -> Result<(), String>
+        where F: FnOnce(&mut Group, usize) -> usize
+    {
+        match self.parent_of(uuid) {
+            Some((parent, i)) => self.edit_group(&parent, |group| edit(group, i)),
+            None => Err(format!("No shape found with uuid #{}", uuid)),
+        }
+    }
+
+    /// Edit the group with uuid `group_uuid`, which may be one of the grid's groups or a group nested in one. `edit`
+    /// returns the first index whose shape may have changed, so the uuids and spatial index only need updating from there.
+    fn edit_group<F>(&mut self, group_uuid: &str, edit: F) -> Result<(), String>
+        where F: FnOnce(&mut Group) -> usize
+    {
+        let group_idx = match self.groups.iter().position(|group| group.get_uuid() == group_uuid) {
+            Some(group_idx) => group_idx,
+            None => {
+                // Shapes in nested groups aren't indexed themselves, only the group containing them is
+                let key = match self.locate(group_uuid) {
+                    Some(key) => key,
+                    None => return Err(format!("No group found with uuid #{}", group_uuid)),
+                };
+                self.mark_stale(key);
+
+                return match self.groups[key.0].find_mut(group_uuid).and_then(|shape| as_group_mut(shape.as_mut())) {
+                    Some(group) => {
+                        edit(group);
+                        Ok(())
+                    },
+                    None => Err(format!("Shape #{} is not a group", group_uuid)),
+                };
+            },
+        };
+
+        let old_len = self.groups[group_idx].len();
+        let from = edit(&mut self.groups[group_idx]);
+        let len = self.groups[group_idx].len();
+        self.update_uuids(group_idx, from);
+
+        // Every shape from `from` on may have moved to a different index, so their entries move with them
+        let group = &self.groups[group_idx];
+        let style = group.get_style().inherit(&self.style);
+        let index = self.index.get_mut();
+        for i in from..old_len.max(len) {
+            let bounds = group.get_shape(i).and_then(|shape| shape.painted_bounding_box_in(group.get_transform(), &style));
+            index.update((group_idx, i), bounds);
+        }
+
+        Ok(())
+    }
+
+    /// One of the grid's groups, or a group nested in one.
+    fn group_by_uuid(&self, uuid: &str) -> Option<&Group> {
+        match self.groups.iter().find(|group| group.get_uuid() == uuid) {
+            Some(group) => Some(group),
+            None => as_group(self.get_by_uuid(uuid)?),
+        }
+    }
+
+    ///
+    fn clone_by_uuid(&self, uuid: &str) -> Result<Box<dyn SVG>, String> {
+        match self.get_by_uuid(uuid) {
+            Some(shape) => Ok(shape.clone_box()),
+            None => Err(format!("No shape found with uuid #{}", uuid)),
+        }
+    }
+
+    ///
+    fn uuid_at(&self, i: usize) -> Result<String, String> {
+        match self.get_shape(i) {
+            Some(shape) => Ok(shape.get_uuid()),
+            None => Err(format!("No shape found at index {}", i)),
+        }
+    }
+
+    /// The uuid of the only shape whose uuid starts with `prefix`.
+    pub fn find_uuid(&self, prefix: &str) -> Result<String, String> {
+        let mut matches = self.groups.iter()
+            .flat_map(|group| group.uuids())
+            .filter(|uuid| uuid.starts_with(prefix))
+            .collect::<Vec<String>>();
+
+        match matches.len() {
+            0 => Err(format!("No shape found with uuid #{}", prefix)),
+            1 => Ok(matches.remove(0)),
+            n => Err(format!("Uuid #{} is ambiguous, it matches {} shapes", prefix, n)),
+        }
+    }
+
+    /// The key of the shape in a group that is, or is a nested group containing, the shape with `uuid`.
+    fn locate(&self, uuid: &str) -> Option<ShapeKey> {
+        if let Some(&(group_idx, shape_idx)) = self.shapes.get(uuid) {
+            // Shapes added through `get_group_mut` aren't recorded, so entries are checked before they're trusted
+            let recorded = self.groups.get(group_idx).and_then(|group| group.get_shape(shape_idx));
+            if recorded.is_some_and(|shape| shape.get_uuid() == uuid) {
+                return Some((group_idx, shape_idx));
+            }
+        }
+
+        self.groups.iter().enumerate()
+            .find_map(|(group_idx, group)| group.position(uuid).map(|shape_idx| (group_idx, shape_idx)))
+    }
+
+    /// A grid with the same canvas, coordinate system, style and transform as this one, holding `groups`.
+    fn with_groups(&self, groups: Vec<Group>) -> Grid {
+        let mut grid = Grid::new(self.coordinate_system);
+        grid.uuid = self.uuid.clone();
+        grid.width = self.width;
+        grid.height = self.height;
+        grid.view_box = self.view_box.clone();
+        grid.style = self.style.clone();
+        grid.transform = self.transform;
+        grid.groups = groups;
+        grid.rebuild_uuids();
+        grid.mark_all_stale();
+        grid
+    }
+
+    ///
+    fn rebuild_uuids(&mut self) {
+        self.shapes.clear();
+
+        for group_idx in 0..self.groups.len() {
+            self.update_uuids(group_idx, 0);
+        }
+    }
+
+    /// Record where the shapes of a group are, from index `from` onwards.
+    fn update_uuids(&mut self, group_idx: usize, from: usize) {
+        let group = &self.groups[group_idx];
+
+        for shape_idx in from..group.len() {
+            self.shapes.insert(group.get_shape(shape_idx).unwrap().get_uuid(), (group_idx, shape_idx));
+        }
+    }
+
+    ///
+    fn shapes_group_index(&self) -> usize {
+        self.groups.iter().position(|group| group.get_name() == "shapes").unwrap()
+    }
+
+    /// The shape with the given key, along with the transform from its parent to the grid's coordinates
+    /// and the style it inherits from its parent.
+    fn shape_with_transform(&self, key: ShapeKey) -> Option<(&dyn SVG, Transform, Style)> {
+        let group = self.groups.get(key.0)?;
+        Some((group.get_shape(key.1)?, group.get_transform().then(&self.transform), group.get_style().inherit(&self.style)))
+    }
+
+    /// The painted box of a shape, in the coordinates the spatial index uses.
+    fn painted_bounds(&self, key: ShapeKey) -> Option<BoundingBox> {
+        let group = self.groups.get(key.0)?;
+        group.get_shape(key.1)?.painted_bounding_box_in(group.get_transform(), &group.get_style().inherit(&self.style))
+    }
+
+    ///
+    fn mark_stale(&self, key: ShapeKey) {
+        if let Stale::Keys(keys) = &mut *self.stale.borrow_mut() {
+            keys.insert(key);
+        }
+    }
+
+    ///
+    fn mark_all_stale(&self) {
+        *self.stale.borrow_mut() = Stale::All;
+    }
+
+    /// Bring the spatial index up to date with any shapes that may have changed since it was last used.
+    fn refresh_index(&self) {
+        let stale = std::mem::replace(&mut *self.stale.borrow_mut(), Stale::Keys(HashSet::new()));
+
+        match stale {
+            Stale::Keys(keys) => {
+                let mut index = self.index.borrow_mut();
+                for key in keys {
+                    index.update(key, self.painted_bounds(key));
+                }
+            },
+            Stale::All => {
+                let boxes = self.groups.iter().enumerate()
+                    .flat_map(|(group_idx, group)| (0..group.len()).map(move |shape_idx| (group_idx, shape_idx)))
+                    .filter_map(|key| self.painted_bounds(key).map(|bounds| (key, bounds)))
+                    .collect();
+
+                *self.index.borrow_mut() = SpatialIndex::bulk_load(boxes);
+            },
+        }
+    }
+
+    /// Convert a box in the grid's coordinates into one containing it in the spatial index's coordinates.
+    fn to_index_coordinates(&self, bounds: &BoundingBox) -> BoundingBox {
+        match self.transform.inverse() {
+            Some(inverse) => bounds.transform(&inverse),
+            // Every shape is flattened onto a line, so any of them might be in the box
+            None => BoundingBox { min: Point { x: f64::MIN, y: f64::MIN }, max: Point { x: f64::MAX, y: f64::MAX } },
+        }
+    }
+
+    /// Look up candidate shapes in the index, and keep those `keep` accepts, topmost first.
+    fn query_index<F>(&self, bounds: &BoundingBox, keep: F) -> Vec<&dyn SVG>
+    where
+        F: Fn(&dyn SVG, &Transform, &Style) -> bool,
+    {
+        self.refresh_index();
+
+        let mut keys = self.index.borrow().query_rect(&self.to_index_coordinates(bounds));
+        keys.sort_unstable_by(|a, b| b.cmp(a));
+
+        keys.into_iter()
+            .filter_map(|key| self.shape_with_transform(key))
+            .filter(|(shape, transform, style)| keep(*shape, transform, style))
+            .map(|(shape, _, _)| shape)
+            .collect()
+    }
+
+    /// The shapes whose painted area's bounding box touches `bounds`, topmost first.
+    pub fn query_rect(&self, bounds: BoundingBox) -> Vec<&dyn SVG> {
+        self.query_index(&bounds, |shape, transform, style| {
+            shape.painted_bounding_box_in(transform, style).is_some_and(|painted| painted.intersects(&bounds))
+        })
+    }
+
+    /// The shape whose painted area is closest to `point`, preferring the topmost shape when several are as close.
+    pub fn nearest(&self, point: Point) -> Option<&dyn SVG> {
+        self.refresh_index();
+
+        let index_point = match self.transform.inverse() {
+            Some(inverse) => inverse.apply(point),
+            None => return None,
+        };
+        // Distances in the index are at most this much shorter than the same distances in the grid's coordinates
+        let min_scale = self.transform.min_scale();
+
+        let index = self.index.borrow();
+        let mut best: Option<(ShapeKey, f64)> = None;
+
+        for (key, lower_bound) in index.nearest(index_point) {
+            if let Some((_, best_distance)) = best {
+                if lower_bound * min_scale > best_distance {
+                    break;
+                }
+            }
+
+            if let Some((shape, transform, style)) = self.shape_with_transform(key) {
+                let distance = shape.distance_in(point, &transform, &style);
+                let closer = match best {
+                    Some((best_key, best_distance)) => distance < best_distance || (distance == best_distance && key > best_key),
+                    None => true,
+                };
+
+                if closer {
+                    best = Some((key, distance));
+                }
+            }
+        }
+
+        best.and_then(|(key, _)| self.shape_with_transform(key)).map(|(shape, _, _)| shape)
+    }
+
+    ///
+    pub fn get_width(&self) -> f64 {
+        self.width
+    }
+
+    ///
+    pub fn get_height(&self) -> f64 {
+        self.height
+    }
+
+    /// The box containing every group, in the grid's own coordinates before its transform.
+    pub fn content_bounding_box(&self) -> Option<BoundingBox> {
+        union_all(self.groups.iter().map(|group| group.bounding_box()))
+    }
+
+    /// The shapes within `tolerance` of `point`, topmost first. Later groups are drawn above earlier ones,
+    /// and nested groups are returned as a whole rather than as their children.
+    pub fn shapes_at(&self, point: Point, tolerance: f64) -> Vec<&dyn SVG> {
+        let bounds = BoundingBox::new(point.x - tolerance, point.y - tolerance, 2.0 * tolerance, 2.0 * tolerance);
+        self.query_index(&bounds, |shape, transform, style| shape.distance_in(point, transform, style) <= tolerance)
     }
 
     /// 
+    pub fn get_shape(&self, i: usize) -> Option<&dyn SVG> {
+        self.get_group("shapes").unwrap().get_shape(i)
+    }
+
+    /// The shape may be changed in any way, so its entry in the spatial index is refreshed before the next query.
     pub fn get_shape_mut(&mut self, i: usize) -> Option<&mut Box<dyn SVG>> {
-        self.groups.get_mut("shapes").unwrap().get_shape_mut(i)
+        let group_idx = self.shapes_group_index();
+        self.mark_stale((group_idx, i));
+        self.groups[group_idx].get_shape_mut(i)
     }
 }
 
@@ -113,18 +940,627 @@ impl SVG for Grid {
     fn get_uuid(&self) -> String {
         self.uuid.clone()
     }
+
+    /// The copy starts with an empty history.
+    fn clone_box(&self) -> Box<dyn SVG> {
+        Box::new(self.with_groups(self.groups.clone()))
+    }
+
+    /// The copy keeps the canvas and the names of the groups, but everything in it gets a new uuid.
+    fn duplicate(&self) -> Box<dyn SVG> {
+        let mut grid = self.with_groups(self.groups.iter().map(|group| group.duplicate_group()).collect());
+        grid.uuid = utils::gen_uuid();
+        Box::new(grid)
+    }
+
+    ///
+    fn get_style(&self) -> &Style {
+        &self.style
+    }
+
+    /// Every shape inherits the grid's style, so the whole spatial index is rebuilt before the next query.
+    fn get_style_mut(&mut self) -> &mut Style {
+        self.mark_all_stale();
+        &mut self.style
+    }
+
+    ///
+    fn get_transform(&self) -> &Transform {
+        &self.transform
+    }
+
+    ///
+    fn get_transform_mut(&mut self) -> &mut Transform {
+        &mut self.transform
+    }
     
     ///
     fn to_html(&self) -> String {
-        let groups_str = self.groups.values()
-            .map(|group| group.to_html())
-            .collect::<Vec<String>>()
-            .join("\n");
+        let mut attrs = vec![("viewBox", self.view_box.clone())];
+        attrs.extend(self.style.to_attrs());
 
-        format!("<svg viewBox=\"{}\">\n{}\n</svg>", self.view_box, groups_str)
+        let groups = self.groups.iter().chain(self.overlay.as_ref());
+        format!("<svg {}>\n{}\n</svg>", html_attrs(&attrs), self.content_html(groups, Group::to_html))
     }
 
-    
-    fn move_to(&mut self, _: f64, _: f64) { unimplemented!() }
-    fn resize(&mut self, _: Dimensions) -> Result<(), String> { unimplemented!() }
-}
\ No newline at end of file
+    /// Moves the top left of the content's bounding box to (x, y), keeping every shape relative to it.
+    fn move_to(&mut self, x: f64, y: f64) {
+        if let Some(bounds) = self.content_bounding_box() {
+            self.move_by(x - bounds.min.x, y - bounds.min.y);
+        }
+    }
+
+    ///
+    fn move_by(&mut self, dx: f64, dy: f64) {
+        for group in &mut self.groups {
+            move_child_by(group, dx, dy);
+        }
+
+        self.mark_all_stale();
+    }
+
+    /// Changes the size of the canvas, leaving the shapes where they are.
+    fn resize(&mut self, dim: Dimensions) -> Result<(), String> {
+        match dim {
+            Dimensions::Double(width, height) if width > 0.0 && height > 0.0 => {
+                self.width = width;
+                self.height = height;
+                self.view_box = self.init_view_box();
+                Ok(())
+            },
+            Dimensions::Double(_, _) => {
+                Err(format!("Cannot resize Grid with dimensions {}, width and height must be positive", dim))
+            },
+            _ => Err(format!("Cannot resize Grid with dimensions {}", dim)),
+        }
+    }
+
+    ///
+    fn outlines_in(&self, outer: &Transform) -> Vec<Outline> {
+        let transform = self.transform.then(outer);
+        self.groups.iter().flat_map(|group| group.outlines_in(&transform)).collect()
+    }
+
+    ///
+    fn distance_in(&self, point: Point, outer: &Transform, inherited: &Style) -> f64 {
+        let transform = self.transform.then(outer);
+        let style = self.style.inherit(inherited);
+        self.groups.iter()
+            .map(|group| group.distance_in(point, &transform, &style))
+            .fold(f64::INFINITY, f64::min)
+    }
+}
+
+
+/// Re-express a shape using a coordinate system conversion. Translations move the shape,
+/// while flips are added to its transform so that it still renders the same way.
+fn convert_shape(shape: &mut dyn SVG, conversion: Transform) {
+    if conversion.is_identity() {
+        return;
+    }
+
+    if conversion.a == 1.0 && conversion.b == 0.0 && conversion.c == 0.0 && conversion.d == 1.0 {
+        move_child_by(shape, conversion.e, conversion.f);
+    } else {
+        shape.apply_transform(conversion);
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::{Circle, Line, Rect, Text};
+    use super::super::path_data::parse_number_list;
+    use super::super::bounding_box::assert_close;
+    use proptest::prelude::*;
+
+    /// Verify `from_svg_str` sizes the grid from the viewBox, and adds the document's shapes in order.
+    #[test]
+    fn from_svg_str() {
+        let (grid, warnings) = Grid::from_svg_str(r#"
+            <svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 200 100">
+                <rect x="1" y="2" width="3" height="4"/>
+                <g id="nested"><circle cx="5" cy="6" r="7"/></g>
+                <script>alert(1)</script>
+            </svg>
+        "#).unwrap();
+
+        assert_eq!(warnings, vec!["Unsupported element <script> was skipped"]);
+        assert_eq!(grid.to_html(), "\
+<svg viewBox=\"0 0 200 100\">
+<g name=\"shapes\">
+<rect x=\"1\" y=\"2\" width=\"3\" height=\"4\"></rect>
+<g name=\"nested\">
+<circle cx=\"5\" cy=\"6\" r=\"7\"></circle>
+</g>
+</g>
+</svg>");
+        assert_eq!(grid.shapes.len(), 2);
+        assert!(grid.get_shape(1).is_some());
+    }
+
+    /// Verify drawings in every coordinate system import back at the same place on the canvas and in the same groups,
+    /// whatever viewBox origin and flip they were exported with.
+    #[test]
+    fn export_import_round_trip() {
+        for system in CoordinateSystem::ALL {
+            let mut grid = Grid::new(system);
+            grid.add_shape(Rect::new(10.0, 20.0, 30.0, 40.0).box_ptr());
+            grid.add_shape(Circle::new(-15.0, -5.0, 5.0).box_ptr());
+            let mut out = vec![];
+            grid.write_svg(&mut out, &SvgOptions::new()).unwrap();
+
+            let (imported, warnings) = Grid::from_svg_str(&String::from_utf8(out).unwrap()).unwrap();
+            assert!(warnings.is_empty(), "{:?}: {:?}", system, warnings);
+            assert_eq!((imported.get_width(), imported.get_height()), (grid.get_width(), grid.get_height()));
+
+            // The imported grid is top left, down right, so shapes land where the export drew them on the canvas
+            let to_canvas = system.to_canvas(grid.get_width(), grid.get_height());
+            let expected = BoundingBox::from_points(grid.content_bounding_box().unwrap().corners().map(|c| to_canvas.apply(c)));
+            assert_close(imported.bounding_box().unwrap(), expected.unwrap());
+            assert_eq!(imported.groups.iter().map(Group::len).collect::<Vec<usize>>(), vec![2], "{:?}", system);
+            for centre in [Point{ x: 25.0, y: 40.0 }, Point{ x: -15.0, y: -5.0 }] {
+                assert_eq!(imported.shapes_at(to_canvas.apply(centre), 0.0).len(), 1, "{:?}", system);
+            }
+        }
+    }
+
+    /// Verify `from_svg_file` reports files that can't be read.
+    #[test]
+    fn from_svg_file() {
+        let err = Grid::from_svg_file("does/not/exist.svg").err().unwrap();
+        assert!(err.starts_with("Unable to read does/not/exist.svg: "));
+    }
+
+    /// Verify `resize` changes the canvas and view box, and `move_to` moves the shapes in every group.
+    #[test]
+    fn move_to_and_resize() {
+        let mut grid = Grid::new(CoordinateSystem::TopLeftDownRight);
+        grid.add_shape(Rect::new(10.0, 20.0, 5.0, 5.0).box_ptr());
+
+        let mut nested = Group::new("nested");
+        nested.add_shape(Circle::new(0.0, 0.0, 2.0).box_ptr());
+        nested.translate(50.0, 50.0);
+        grid.add_group(nested);
+        assert_eq!(grid.bounding_box(), Some(BoundingBox::new(10.0, 20.0, 42.0, 32.0)));
+
+        grid.move_to(0.0, 0.0);
+        assert_eq!(grid.bounding_box(), Some(BoundingBox::new(0.0, 0.0, 42.0, 32.0)));
+        assert_eq!(grid.get_shape(0).unwrap().to_html(), "<rect x=\"0\" y=\"0\" width=\"5\" height=\"5\"></rect>");
+        assert_eq!(grid.get_group("nested").unwrap().to_html(), "\
+<g name=\"nested\" transform=\"matrix(1 0 0 1 50 50)\">
+<circle cx=\"-10\" cy=\"-20\" r=\"2\"></circle>
+</g>");
+
+        grid.resize(Dimensions::Double(300.0, 200.0)).unwrap();
+        assert_eq!((grid.get_width(), grid.get_height()), (300.0, 200.0));
+        assert!(grid.to_html().starts_with("<svg viewBox=\"0 0 300 200\">"));
+
+        let err = grid.resize(Dimensions::Double(-1.0, 200.0)).unwrap_err();
+        assert_eq!(err, "Cannot resize Grid with dimensions Double(-1.0, 200.0), width and height must be positive");
+        let err = grid.resize(Dimensions::Single(1.0)).unwrap_err();
+        assert_eq!(err, "Cannot resize Grid with dimensions Single(1.0)");
+        assert_eq!((grid.get_width(), grid.get_height()), (300.0, 200.0));
+    }
+
+    /// Verify each coordinate system maps its origin and axes to the places its doc comment promises.
+    #[test]
+    fn to_canvas() {
+        let corners = |c: CoordinateSystem| {
+            let t = c.to_canvas(200.0, 100.0);
+            (t.apply(Point{ x: 0.0, y: 0.0 }), t.apply_vector(1.0, 1.0))
+        };
+
+        assert_eq!(corners(CoordinateSystem::TopLeftDownRight), (Point{ x: 0.0, y: 0.0 }, (1.0, 1.0)));
+        assert_eq!(corners(CoordinateSystem::BottomLeftUpRight), (Point{ x: 0.0, y: 100.0 }, (1.0, -1.0)));
+        assert_eq!(corners(CoordinateSystem::MidMidUpRight), (Point{ x: 100.0, y: 50.0 }, (1.0, -1.0)));
+        assert_eq!(corners(CoordinateSystem::MidMidDownRight), (Point{ x: 100.0, y: 50.0 }, (1.0, 1.0)));
+    }
+
+    /// Verify `transform_coordinates` re-expresses points from every system in the grid's system.
+    #[test]
+    fn transform_coordinates() {
+        let mut grid = Grid::new(CoordinateSystem::MidMidUpRight);
+        grid.resize(Dimensions::Double(200.0, 100.0)).unwrap();
+
+        assert_eq!(grid.transform_coordinates(10.0, 20.0, CoordinateSystem::TopLeftDownRight), (-90.0, 30.0));
+        assert_eq!(grid.transform_coordinates(10.0, 20.0, CoordinateSystem::BottomLeftUpRight), (-90.0, -30.0));
+        assert_eq!(grid.transform_coordinates(10.0, 20.0, CoordinateSystem::MidMidUpRight), (10.0, 20.0));
+        assert_eq!(grid.transform_coordinates(10.0, 20.0, CoordinateSystem::MidMidDownRight), (10.0, -20.0));
+    }
+
+    /// Verify shapes added in another system, and existing shapes after `set_coordinate_system`, stay in place on the canvas.
+    #[test]
+    fn set_coordinate_system() {
+        let mut grid = Grid::new(CoordinateSystem::TopLeftDownRight);
+        grid.resize(Dimensions::Double(200.0, 100.0)).unwrap();
+        grid.add_shape(Rect::new(10.0, 20.0, 5.0, 5.0).box_ptr());
+        grid.add_shape_in(Circle::new(0.0, 0.0, 1.0).box_ptr(), CoordinateSystem::MidMidDownRight);
+        assert_eq!(grid.get_shape(1).unwrap().to_html(), "<circle cx=\"100\" cy=\"50\" r=\"1\"></circle>");
+
+        grid.set_coordinate_system(CoordinateSystem::MidMidDownRight);
+        assert_eq!(grid.get_coordinate_system(), CoordinateSystem::MidMidDownRight);
+        assert_eq!(grid.get_shape(0).unwrap().to_html(), "<rect x=\"-90\" y=\"-30\" width=\"5\" height=\"5\"></rect>");
+        assert_eq!(grid.get_shape(1).unwrap().to_html(), "<circle cx=\"0\" cy=\"0\" r=\"1\"></circle>");
+
+        // Flipping systems flips the group rather than the shapes, so they render the same way
+        grid.set_coordinate_system(CoordinateSystem::BottomLeftUpRight);
+        assert_eq!(grid.get_group("shapes").unwrap().get_transform(), &Transform::new(1.0, 0.0, 0.0, -1.0, 100.0, 50.0));
+        assert_eq!(grid.bounding_box(), Some(BoundingBox::new(10.0, 49.0, 91.0, 31.0)));
+
+        grid.set_coordinate_system(CoordinateSystem::TopLeftDownRight);
+        assert_eq!(grid.get_group("shapes").unwrap().get_transform(), &Transform::translate(100.0, 50.0));
+        assert_eq!(grid.bounding_box(), Some(BoundingBox::new(10.0, 20.0, 91.0, 31.0)));
+    }
+
+    proptest! {
+        /// Verify converting a point from any system to another and back again returns the original point.
+        #[test]
+        fn round_trip(
+            from in 0..4usize,
+            to in 0..4usize,
+            width in 1.0..5000.0f64,
+            height in 1.0..5000.0f64,
+            x in -1e4..1e4f64,
+            y in -1e4..1e4f64,
+        ) {
+            let (from, to) = (CoordinateSystem::ALL[from], CoordinateSystem::ALL[to]);
+            let mut grid = Grid::new(from);
+            grid.resize(Dimensions::Double(width, height)).unwrap();
+
+            let there = grid.conversion(from, to).apply(Point { x, y });
+            let back = grid.conversion(to, from).apply(there);
+            prop_assert!((back.x - x).abs() < 1e-9 && (back.y - y).abs() < 1e-9);
+
+            if from == to {
+                prop_assert!(grid.conversion(from, to).is_identity());
+            }
+        }
+    }
+
+    /// Compare the output against a file in `tests/golden`, or rewrite the file when `UPDATE_GOLDEN` is set.
+    fn assert_golden(name: &str, actual: &str) {
+        let path = format!("{}/tests/golden/{}", env!("CARGO_MANIFEST_DIR"), name);
+
+        if std::env::var_os("UPDATE_GOLDEN").is_some() {
+            fs::write(&path, actual).unwrap();
+        }
+
+        let expected = fs::read_to_string(&path).unwrap_or_else(|e| panic!("Unable to read {}: {}", path, e));
+        assert_eq!(actual, expected, "Output differs from {}", path);
+    }
+
+    /// Verify each coordinate system renders its origin and axes as its doc comment promises,
+    /// by drawing the same shapes in each and comparing against golden files.
+    #[test]
+    fn golden_coordinate_systems() {
+        let cases = [
+            (CoordinateSystem::TopLeftDownRight, "top_left_down_right.svg"),
+            (CoordinateSystem::BottomLeftUpRight, "bottom_left_up_right.svg"),
+            (CoordinateSystem::MidMidUpRight, "mid_mid_up_right.svg"),
+            (CoordinateSystem::MidMidDownRight, "mid_mid_down_right.svg"),
+        ];
+
+        for (c, name) in cases {
+            let mut grid = Grid::new(c);
+            grid.resize(Dimensions::Double(200.0, 100.0)).unwrap();
+
+            // A marker at the origin, and arrows along the positive axes
+            grid.add_shape(Circle::new(0.0, 0.0, 5.0).box_ptr());
+            grid.add_shape(Line::new(0.0, 0.0, 40.0, 0.0).box_ptr());
+            grid.add_shape(Line::new(0.0, 0.0, 0.0, 20.0).box_ptr());
+            grid.add_shape(Rect::new(10.0, 10.0, 20.0, 10.0).box_ptr());
+            // A label reading left to right, upright on the canvas
+            grid.add_shape(Text::new(10.0, 10.0, "Hi").box_ptr());
+
+            assert_golden(name, &grid.to_html());
+        }
+
+        // Verify the grid's own transform is applied in the grid's coordinates, before the y axis is flipped
+        let mut grid = Grid::new(CoordinateSystem::MidMidUpRight);
+        grid.resize(Dimensions::Double(200.0, 100.0)).unwrap();
+        grid.add_shape(Rect::new(10.0, 10.0, 20.0, 10.0).box_ptr());
+        grid.translate(5.0, 5.0);
+        assert_golden("mid_mid_up_right_translated.svg", &grid.to_html());
+    }
+
+    /// Verify the viewBox and root transform place points on the canvas where `to_canvas` says they belong.
+    #[test]
+    fn view_box_matches_to_canvas() {
+        for c in CoordinateSystem::ALL {
+            let mut grid = Grid::new(c);
+            grid.resize(Dimensions::Double(200.0, 100.0)).unwrap();
+
+            let view_box = parse_number_list(&grid.view_box).unwrap();
+            let to_canvas = c.to_canvas(200.0, 100.0);
+
+            for point in [Point{ x: 0.0, y: 0.0 }, Point{ x: 30.0, y: -20.0 }] {
+                let user = grid.root_transform().apply(point);
+                assert_eq!(Point{ x: user.x - view_box[0], y: user.y - view_box[1] }, to_canvas.apply(point), "{:?}", c);
+            }
+        }
+    }
+
+    /// Verify `shapes_at` finds every shape under a point topmost first, including nested groups as a whole.
+    #[test]
+    fn shapes_at() {
+        let mut grid = Grid::new(CoordinateSystem::TopLeftDownRight);
+        grid.add_shape(Rect::new(0.0, 0.0, 10.0, 10.0).box_ptr());
+        grid.add_shape(Circle::new(12.0, 5.0, 3.0).box_ptr());
+        let mut line = Line::new(0.0, 5.0, 20.0, 5.0);
+        line.get_style_mut().set_stroke("black");
+        line.get_style_mut().set_stroke_width(4.0).unwrap();
+        grid.add_shape(line.box_ptr());
+
+        let mut nested = Group::new("nested");
+        nested.add_shape(Rect::new(0.0, 0.0, 1.0, 1.0).box_ptr());
+        nested.scale(5.0, 5.0);
+        grid.add_shape(nested.box_ptr());
+
+        // Groups added to the grid are layers drawn above the shapes group
+        let mut overlay = Group::new("overlay");
+        overlay.add_shape(Circle::new(4.0, 4.0, 0.5).box_ptr());
+        grid.add_group(overlay);
+
+        let uuids = |shapes: Vec<&dyn SVG>| shapes.iter().map(|shape| shape.get_uuid()).collect::<Vec<String>>();
+        let shape_uuids = (0..4).map(|i| grid.get_shape(i).unwrap().get_uuid()).collect::<Vec<String>>();
+        let shape = |i: usize| shape_uuids[i].clone();
+        let overlay_uuid = grid.get_group("overlay").unwrap().get_shape(0).unwrap().get_uuid();
+
+        let expected = vec![overlay_uuid, shape(3), shape(2), shape(0)];
+        assert_eq!(uuids(grid.shapes_at(Point{ x: 4.0, y: 4.0 }, 0.0)), expected);
+        assert_eq!(uuids(grid.shapes_at(Point{ x: 9.5, y: 8.0 }, 0.0)), vec![shape(0)]);
+        assert_eq!(uuids(grid.shapes_at(Point{ x: 12.0, y: 7.5 }, 0.0)), vec![shape(1)]);
+        assert_eq!(uuids(grid.shapes_at(Point{ x: 12.0, y: 7.5 }, 1.0)), vec![shape(2), shape(1)]);
+        assert!(grid.shapes_at(Point{ x: 30.0, y: 30.0 }, 1.0).is_empty());
+
+        // Points are in the grid's coordinates, after its transform
+        grid.translate(100.0, 0.0);
+        assert_eq!(uuids(grid.shapes_at(Point{ x: 104.0, y: 4.0 }, 0.0)), expected);
+    }
+
+    /// Verify the spatial index follows shapes as they are added, moved, resized and edited directly.
+    #[test]
+    fn spatial_index() {
+        let mut grid = Grid::new(CoordinateSystem::TopLeftDownRight);
+        for i in 0..100 {
+            grid.add_shape(Rect::new((i % 10) as f64 * 10.0, (i / 10) as f64 * 10.0, 5.0, 5.0).box_ptr());
+        }
+        let uuid = |grid: &Grid, i: usize| grid.get_shape(i).unwrap().get_uuid();
+        let uuids = |shapes: Vec<&dyn SVG>| shapes.iter().map(|shape| shape.get_uuid()).collect::<Vec<String>>();
+
+        assert_eq!(uuids(grid.query_rect(BoundingBox::new(12.0, 12.0, 10.0, 1.0))), vec![uuid(&grid, 12), uuid(&grid, 11)]);
+        assert_eq!(uuids(grid.shapes_at(Point{ x: 52.0, y: 33.0 }, 0.0)), vec![uuid(&grid, 35)]);
+
+        grid.move_shape(35, 500.0, 500.0).unwrap();
+        assert!(grid.shapes_at(Point{ x: 52.0, y: 33.0 }, 0.0).is_empty());
+        grid.resize_shape(35, Dimensions::Double(50.0, 50.0)).unwrap();
+        assert_eq!(uuids(grid.shapes_at(Point{ x: 540.0, y: 540.0 }, 0.0)), vec![uuid(&grid, 35)]);
+
+        grid.get_shape_mut(0).unwrap().move_to(530.0, 530.0);
+        assert_eq!(uuids(grid.shapes_at(Point{ x: 532.0, y: 532.0 }, 0.0)), vec![uuid(&grid, 35), uuid(&grid, 0)]);
+        assert!(grid.shapes_at(Point{ x: 2.0, y: 2.0 }, 0.0).is_empty());
+
+        let err = grid.move_shape(100, 0.0, 0.0).unwrap_err();
+        assert_eq!(err, "No shape found at index 100");
+        let err = grid.resize_shape(1, Dimensions::Single(1.0)).unwrap_err();
+        assert_eq!(err, "Cannot resize Rect with dimensions Single(1.0)");
+
+        // Moving the whole grid, or editing a group directly, rebuilds the index
+        grid.move_by(1000.0, 0.0);
+        assert_eq!(uuids(grid.shapes_at(Point{ x: 1011.0, y: 1.0 }, 0.0)), vec![uuid(&grid, 1)]);
+        grid.get_group_mut("shapes").unwrap().get_shape_mut(1).unwrap().move_to(0.0, 0.0);
+        assert_eq!(uuids(grid.shapes_at(Point{ x: 1.0, y: 1.0 }, 0.0)), vec![uuid(&grid, 1)]);
+    }
+
+    /// Verify `nearest` measures to the painted area in the grid's coordinates, preferring the topmost shape.
+    #[test]
+    fn nearest() {
+        let mut grid = Grid::new(CoordinateSystem::TopLeftDownRight);
+        assert!(grid.nearest(Point{ x: 0.0, y: 0.0 }).is_none());
+
+        grid.add_shape(Rect::new(0.0, 0.0, 10.0, 10.0).box_ptr());
+        grid.add_shape(Rect::new(0.0, 0.0, 10.0, 10.0).box_ptr());
+        let mut line = Line::new(30.0, 0.0, 30.0, 10.0);
+        line.get_style_mut().set_stroke("black");
+        line.get_style_mut().set_stroke_width(10.0).unwrap();
+        grid.add_shape(line.box_ptr());
+
+        let nearest = |grid: &Grid, x: f64, y: f64| grid.nearest(Point{ x, y }).unwrap().get_uuid();
+        assert_eq!(nearest(&grid, 5.0, 5.0), grid.get_shape(1).unwrap().get_uuid());
+        // The stroke of the line is closer than the outline of the rects
+        assert_eq!(nearest(&grid, 19.0, 5.0), grid.get_shape(2).unwrap().get_uuid());
+
+        // Stretching the grid makes the rects closer than the line's stroke
+        grid.scale(1.0, 0.1);
+        assert_eq!(nearest(&grid, 19.0, 5.0), grid.get_shape(1).unwrap().get_uuid());
+    }
+
+    /// Verify hit testing paints unstyled shapes with the fill and stroke they inherit from the grid and the groups
+    /// enclosing them, as they are drawn.
+    #[test]
+    fn inherited_style() {
+        let mut grid = Grid::new(CoordinateSystem::TopLeftDownRight);
+        grid.get_style_mut().set_stroke("black");
+        grid.add_shape(Circle::new(5.0, 5.0, 1.0).box_ptr());
+
+        let mut icon = Group::new("icon");
+        icon.get_style_mut().set_fill("none");
+        icon.get_style_mut().set_stroke_width(4.0).unwrap();
+        let rect = Rect::new(0.0, 0.0, 10.0, 10.0);
+        let rect_id = rect.get_uuid();
+        icon.add_shape(rect.box_ptr());
+        let mut filled = Rect::new(20.0, 0.0, 10.0, 10.0);
+        filled.get_style_mut().set_fill("red");
+        icon.add_shape(filled.box_ptr());
+        grid.add_shape(icon.box_ptr());
+
+        let uuids = |shapes: Vec<&dyn SVG>| shapes.iter().map(|shape| shape.get_uuid()).collect::<Vec<String>>();
+        let (circle_id, icon_id) = (grid.get_shape(0).unwrap().get_uuid(), grid.get_shape(1).unwrap().get_uuid());
+
+        assert_eq!(grid.parent_style(&rect_id).unwrap().to_attrs(), vec![
+            ("fill", String::from("none")),
+            ("stroke", String::from("black")),
+            ("stroke-width", String::from("4")),
+        ]);
+
+        // The rect isn't filled, so only the circle is under its middle, but its stroke reaches 2 beyond its outline
+        assert_eq!(uuids(grid.shapes_at(Point{ x: 5.0, y: 5.0 }, 0.0)), vec![circle_id.clone()]);
+        assert_eq!(uuids(grid.shapes_at(Point{ x: 11.5, y: 5.0 }, 0.0)), vec![icon_id.clone()]);
+        assert_eq!(uuids(grid.query_rect(BoundingBox::new(11.5, 11.5, 0.1, 0.1))), vec![icon_id.clone()]);
+        assert_eq!(grid.nearest(Point{ x: 5.0, y: 5.0 }).unwrap().get_uuid(), circle_id);
+
+        // A child's own style still takes precedence over the group's
+        assert_eq!(uuids(grid.shapes_at(Point{ x: 25.0, y: 5.0 }, 0.0)), vec![icon_id.clone()]);
+
+        // Widening the grid's stroke widens the painted box the shapes are found by
+        grid.get_style_mut().set_stroke_width(40.0).unwrap();
+        assert_eq!(uuids(grid.shapes_at(Point{ x: 5.0, y: 25.0 }, 0.0)), vec![circle_id]);
+    }
+
+    /// Verify the transform of a shape's parent includes every group above it, and the grid's own transform.
+    #[test]
+    fn parent_transform() {
+        let mut grid = Grid::new(CoordinateSystem::TopLeftDownRight);
+        grid.add_shape(Rect::new(0.0, 0.0, 1.0, 1.0).box_ptr());
+        let rect = grid.get_shape(0).unwrap().get_uuid();
+
+        let circle = Circle::new(0.0, 0.0, 1.0);
+        let circle_id = circle.get_uuid();
+        let mut nested = Group::new("nested");
+        nested.add_shape(circle.box_ptr());
+        nested.set_transform(Transform::scale(2.0, 2.0));
+        let mut layer = Group::new("layer");
+        layer.add_shape(nested.box_ptr());
+        layer.set_transform(Transform::translate(10.0, 0.0));
+        grid.add_group(layer);
+        grid.set_transform(Transform::translate(0.0, 5.0));
+
+        assert_eq!(grid.parent_transform(&rect), Some(Transform::translate(0.0, 5.0)));
+        let transform = grid.parent_transform(&circle_id).unwrap();
+        assert_eq!(transform.apply(Point{ x: 1.0, y: 1.0 }), Point{ x: 12.0, y: 7.0 });
+        assert!(grid.parent_transform("missing").is_none());
+    }
+
+    /// Verify the overlay is drawn above the groups but isn't hit tested, found by uuid or recorded in the history.
+    #[test]
+    fn overlay() {
+        let mut grid = Grid::new(CoordinateSystem::TopLeftDownRight);
+        let mut overlay = Group::new("overlay");
+        let rect = Rect::new(0.0, 0.0, 10.0, 10.0);
+        let rect_id = rect.get_uuid();
+        overlay.add_shape(rect.box_ptr());
+        grid.set_overlay(Some(overlay));
+
+        assert!(grid.to_html().ends_with("<g name=\"overlay\">\n<rect x=\"0\" y=\"0\" width=\"10\" height=\"10\"></rect>\n</g>\n</svg>"));
+        assert!(grid.shapes_at(Point{ x: 5.0, y: 5.0 }, 0.0).is_empty());
+        assert!(grid.get_by_uuid(&rect_id).is_none());
+        assert_eq!(grid.get_history().done().count(), 0);
+
+        grid.set_overlay(None);
+        assert!(grid.get_overlay().is_none());
+    }
+
+    /// Verify shapes are found, edited and removed by uuid in every group, keeping indexes and hit testing in step.
+    #[test]
+    fn by_uuid() {
+        let mut grid = Grid::new(CoordinateSystem::TopLeftDownRight);
+        for i in 0..3 {
+            grid.add_shape(Rect::new(i as f64 * 10.0, 0.0, 5.0, 5.0).box_ptr());
+        }
+        let uuid = |grid: &Grid, i: usize| grid.get_shape(i).unwrap().get_uuid();
+        let (first, second, third) = (uuid(&grid, 0), uuid(&grid, 1), uuid(&grid, 2));
+
+        let circle = Circle::new(50.0, 50.0, 5.0);
+        let circle_id = circle.get_uuid();
+        let mut nested = Group::new("nested");
+        nested.add_shape(circle.box_ptr());
+        let mut layer = Group::new("layer");
+        layer.add_shape(nested.box_ptr());
+        grid.add_group(layer);
+
+        assert_eq!(grid.get_by_uuid(&second).unwrap().get_uuid(), second);
+        assert_eq!(grid.get_by_uuid(&circle_id).unwrap().get_uuid(), circle_id);
+        assert!(grid.get_by_uuid("missing").is_none());
+
+        grid.get_by_uuid_mut(&circle_id).unwrap().move_to(100.0, 100.0);
+        assert_eq!(grid.shapes_at(Point{ x: 102.0, y: 102.0 }, 0.0).len(), 1);
+
+        // Shapes after a removed one move down an index, and can still be hit
+        assert_eq!(grid.remove_by_uuid(&first).unwrap().get_uuid(), first);
+        assert_eq!(uuid(&grid, 0), second);
+        assert_eq!(grid.get_by_uuid(&third).unwrap().get_uuid(), third);
+        assert_eq!(grid.shapes_at(Point{ x: 22.0, y: 2.0 }, 0.0)[0].get_uuid(), third);
+        assert!(grid.shapes_at(Point{ x: 2.0, y: 2.0 }, 0.0).is_empty());
+        assert!(grid.remove_by_uuid(&first).is_none());
+
+        assert_eq!(grid.remove_by_uuid(&circle_id).unwrap().get_uuid(), circle_id);
+        assert!(grid.shapes_at(Point{ x: 102.0, y: 102.0 }, 0.0).is_empty());
+
+        // Shapes added to a group directly are still found
+        let rect = Rect::new(0.0, 0.0, 1.0, 1.0);
+        let rect_id = rect.get_uuid();
+        grid.get_group_mut("shapes").unwrap().add_shape(rect.box_ptr());
+        assert_eq!(grid.get_by_uuid(&rect_id).unwrap().get_uuid(), rect_id);
+
+        assert_eq!(grid.find_uuid(&third[..8]).unwrap(), third);
+        assert_eq!(grid.find_uuid("xyz").unwrap_err(), "No shape found with uuid #xyz");
+        assert_eq!(grid.find_uuid("").unwrap_err(), "Uuid # is ambiguous, it matches 4 shapes");
+    }
+
+    /// Verify deleting, duplicating and restacking shapes keeps lookups by uuid and index, and hit testing, in step.
+    #[test]
+    fn edit_stacking() {
+        let mut grid = Grid::new(CoordinateSystem::TopLeftDownRight);
+        for _ in 0..3 {
+            grid.add_shape(Rect::new(0.0, 0.0, 10.0, 10.0).box_ptr());
+        }
+        let order = |grid: &Grid| (0..grid.get_group("shapes").unwrap().len())
+            .map(|i| grid.get_shape(i).unwrap().get_uuid())
+            .collect::<Vec<String>>();
+        let ids = order(&grid);
+        let top = |grid: &Grid, x: f64| grid.shapes_at(Point{ x, y: 5.0 }, 0.0)[0].get_uuid();
+
+        grid.to_back(&ids[2]).unwrap();
+        assert_eq!(order(&grid), vec![ids[2].clone(), ids[0].clone(), ids[1].clone()]);
+        grid.raise(&ids[2]).unwrap();
+        assert_eq!(order(&grid), vec![ids[0].clone(), ids[2].clone(), ids[1].clone()]);
+        grid.to_front(&ids[0]).unwrap();
+        assert_eq!(order(&grid), vec![ids[2].clone(), ids[1].clone(), ids[0].clone()]);
+        grid.lower(&ids[0]).unwrap();
+        assert_eq!(order(&grid), vec![ids[2].clone(), ids[0].clone(), ids[1].clone()]);
+        assert_eq!(top(&grid, 5.0), ids[1]);
+
+        // Restacking past either end leaves the shape where it is
+        grid.lower(&ids[2]).unwrap();
+        grid.raise(&ids[1]).unwrap();
+        assert_eq!(order(&grid), vec![ids[2].clone(), ids[0].clone(), ids[1].clone()]);
+        assert_eq!(grid.raise("missing").unwrap_err(), "No shape found with uuid #missing");
+
+        let copy = grid.duplicate_by_uuid(&ids[2], 20.0, 0.0).unwrap();
+        assert_eq!(order(&grid), vec![ids[2].clone(), copy.clone(), ids[0].clone(), ids[1].clone()]);
+        assert_eq!(top(&grid, 25.0), copy);
+        assert_eq!(grid.get_by_uuid(&ids[1]).unwrap().get_uuid(), ids[1]);
+
+        assert!(grid.remove_by_uuid(&ids[1]).is_some());
+        assert_eq!(top(&grid, 5.0), ids[0]);
+        assert_eq!(order(&grid), vec![ids[2].clone(), copy.clone(), ids[0].clone()]);
+
+        // Shapes inside nested groups are restacked and copied within their own group
+        let mut nested = Group::new("nested");
+        let inner = (0..2).map(|_| {
+            let rect = Rect::new(100.0, 0.0, 10.0, 10.0);
+            let id = rect.get_uuid();
+            nested.add_shape(rect.box_ptr());
+            id
+        }).collect::<Vec<String>>();
+        grid.add_shape(nested.box_ptr());
+
+        grid.to_front(&inner[0]).unwrap();
+        let inner_copy = grid.duplicate_by_uuid(&inner[1], 0.0, 50.0).unwrap();
+        let nested = as_group(grid.get_shape(3).unwrap()).unwrap();
+        assert_eq!(nested.uuids(), vec![inner[1].clone(), inner_copy, inner[0].clone()]);
+        assert_eq!(grid.query_rect(BoundingBox::new(100.0, 55.0, 1.0, 1.0)).len(), 1);
+    }
+}
@@ -1,11 +1,22 @@
+use std::any::Any;
+
+use serde::{Serialize, Deserialize};
+
 use crate::utils;
-use super::{SVG, Dimensions};
+use super::{Point, SVG, Dimensions, Style, Transform, BoundingBox, Outline, html_attrs, move_child_by};
+use super::bounding_box::union_all;
 
 
+#[derive(Serialize, Deserialize)]
 pub struct Group {
     uuid: String,
     name: String,
+    #[serde(with = "super::serialize::shape_list")]
     shapes: Vec<Box<dyn SVG>>,
+    #[serde(default)]
+    style: Style,
+    #[serde(default)]
+    transform: Transform,
 }
 
 impl Group {
@@ -15,6 +26,8 @@ impl Group {
             uuid: utils::gen_uuid(),
             name: String::from(name),
             shapes: Vec::new(),
+            style: Style::new(),
+            transform: Transform::identity(),
         }
     }
 
@@ -24,11 +37,21 @@ impl Group {
     }
 
     /// Group should own the shape.
-    pub fn add_shape<'a>(&mut self, shape: Box<dyn SVG>) -> usize {
+    pub fn add_shape(&mut self, shape: Box<dyn SVG>) -> usize {
         self.shapes.push(shape);
         self.shapes.len() - 1
     }
 
+    ///
+    pub fn len(&self) -> usize {
+        self.shapes.len()
+    }
+
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.shapes.is_empty()
+    }
+
     ///
     pub fn get_shape_htmls(&self) -> Vec<String> {
         self.shapes.iter()
@@ -37,14 +60,161 @@ impl Group {
     }
 
     ///
-    pub fn get_shape(&self, i: usize) -> Option<&Box<dyn SVG>> {
-        self.shapes.get(i)
+    pub fn get_shape(&self, i: usize) -> Option<&dyn SVG> {
+        self.shapes.get(i).map(|shape| shape.as_ref())
     }
 
     ///
     pub fn get_shape_mut(&mut self, i: usize) -> Option<&mut Box<dyn SVG>> {
         self.shapes.get_mut(i)
     }
+
+    /// Remove the shape at index `i`, moving the shapes after it down by one.
+    pub fn remove_shape(&mut self, i: usize) -> Option<Box<dyn SVG>> {
+        if i < self.shapes.len() {
+            Some(self.shapes.remove(i))
+        } else {
+            None
+        }
+    }
+
+    /// Insert a shape at index `i`, moving the shapes from there on up by one.
+    pub fn insert_shape(&mut self, i: usize, shape: Box<dyn SVG>) {
+        self.shapes.insert(i.min(self.shapes.len()), shape);
+    }
+
+    /// Move the shape at index `from` to index `to`, shifting the shapes between them to fill the gap.
+    pub fn reorder_shape(&mut self, from: usize, to: usize) -> Result<(), String> {
+        for i in [from, to] {
+            if i >= self.shapes.len() {
+                return Err(format!("No shape found at index {}", i));
+            }
+        }
+
+        let shape = self.shapes.remove(from);
+        self.shapes.insert(to, shape);
+        Ok(())
+    }
+
+    /// A copy of the group and everything in it, with new uuids throughout.
+    pub fn duplicate_group(&self) -> Group {
+        Group {
+            uuid: utils::gen_uuid(),
+            name: self.name.clone(),
+            shapes: self.shapes.iter().map(|shape| shape.duplicate()).collect(),
+            style: self.style.clone(),
+            transform: self.transform,
+        }
+    }
+
+    /// The index of the child that is, or is a group containing, the shape with `uuid`.
+    pub fn position(&self, uuid: &str) -> Option<usize> {
+        self.shapes.iter().position(|shape| {
+            shape.get_uuid() == uuid || as_group(shape.as_ref()).is_some_and(|group| group.find(uuid).is_some())
+        })
+    }
+
+    /// The shape with `uuid`, searching nested groups as well as the group's own children.
+    pub fn find(&self, uuid: &str) -> Option<&dyn SVG> {
+        self.shapes.iter().find_map(|shape| {
+            if shape.get_uuid() == uuid {
+                Some(shape.as_ref())
+            } else {
+                as_group(shape.as_ref())?.find(uuid)
+            }
+        })
+    }
+
+    ///
+    pub fn find_mut(&mut self, uuid: &str) -> Option<&mut Box<dyn SVG>> {
+        let i = self.position(uuid)?;
+        let shape = &mut self.shapes[i];
+
+        if shape.get_uuid() == uuid {
+            return Some(shape);
+        }
+
+        as_group_mut(shape.as_mut())?.find_mut(uuid)
+    }
+
+    /// The group directly holding the shape with `uuid`, which may be this group or one nested in it,
+    /// along with the shape's index in that group.
+    pub fn parent_of(&self, uuid: &str) -> Option<(&Group, usize)> {
+        let i = self.position(uuid)?;
+
+        if self.shapes[i].get_uuid() == uuid {
+            return Some((self, i));
+        }
+
+        as_group(self.shapes[i].as_ref())?.parent_of(uuid)
+    }
+
+    /// Remove the shape with `uuid`, wherever it is nested within the group.
+    pub fn remove_by_uuid(&mut self, uuid: &str) -> Option<Box<dyn SVG>> {
+        let i = self.position(uuid)?;
+
+        if self.shapes[i].get_uuid() == uuid {
+            return self.remove_shape(i);
+        }
+
+        as_group_mut(self.shapes[i].as_mut())?.remove_by_uuid(uuid)
+    }
+
+    /// The uuids of every shape in the group, with each nested group followed by its own shapes.
+    pub fn uuids(&self) -> Vec<String> {
+        let mut uuids = vec![];
+
+        for shape in &self.shapes {
+            uuids.push(shape.get_uuid());
+
+            if let Some(group) = as_group(shape.as_ref()) {
+                uuids.extend(group.uuids());
+            }
+        }
+
+        uuids
+    }
+
+    ///
+    pub fn box_ptr(self) -> Box<Self> {
+        Box::new(self)
+    }
+
+    /// The box containing every child, in the group's own coordinates before its transform.
+    pub fn content_bounding_box(&self) -> Option<BoundingBox> {
+        union_all(self.shapes.iter().map(|shape| shape.bounding_box()))
+    }
+
+    /// The transform taking a child's coordinates into the group's parent coordinates,
+    /// which applies the child's own transform and then the group's.
+    pub fn get_shape_transform(&self, i: usize) -> Option<Transform> {
+        self.shapes.get(i).map(|shape| shape.get_transform().then(&self.transform))
+    }
+}
+
+impl Clone for Group {
+    ///
+    fn clone(&self) -> Group {
+        Group {
+            uuid: self.uuid.clone(),
+            name: self.name.clone(),
+            shapes: self.shapes.iter().map(|shape| shape.clone_box()).collect(),
+            style: self.style.clone(),
+            transform: self.transform,
+        }
+    }
+}
+
+/// The shape as a group, if it is one.
+pub(super) fn as_group(shape: &dyn SVG) -> Option<&Group> {
+    let shape: &dyn Any = shape;
+    shape.downcast_ref::<Group>()
+}
+
+///
+pub(super) fn as_group_mut(shape: &mut dyn SVG) -> Option<&mut Group> {
+    let shape: &mut dyn Any = shape;
+    shape.downcast_mut::<Group>()
 }
 
 impl SVG for Group {
@@ -53,20 +223,118 @@ impl SVG for Group {
         self.uuid.clone()
     }
 
+    ///
+    fn clone_box(&self) -> Box<dyn SVG> {
+        self.clone().box_ptr()
+    }
+
+    ///
+    fn duplicate(&self) -> Box<dyn SVG> {
+        self.duplicate_group().box_ptr()
+    }
+
+    /// Children without their own presentation attributes inherit the group's style.
+    fn get_style(&self) -> &Style {
+        &self.style
+    }
+
+    ///
+    fn get_style_mut(&mut self) -> &mut Style {
+        &mut self.style
+    }
+
+    ///
+    fn get_transform(&self) -> &Transform {
+        &self.transform
+    }
+
+    ///
+    fn get_transform_mut(&mut self) -> &mut Transform {
+        &mut self.transform
+    }
+
     ///
     fn to_html(&self) -> String {
-        format!("<g name=\"{}\">\n{}\n</g>", self.name, self.get_shape_htmls().join("\n"))
+        let mut attrs = vec![("name", self.name.clone())];
+        attrs.extend(self.transform.to_attrs());
+        attrs.extend(self.style.to_attrs());
+
+        format!("<g {}>\n{}\n</g>", html_attrs(&attrs), self.get_shape_htmls().join("\n"))
     }
 
-    fn move_to(&mut self, _: f64, _: f64) { unimplemented!() }
-    fn resize(&mut self, _: Dimensions) -> Result<(), String> { unimplemented!() }
+    /// Moves the top left of the children's bounding box to (x, y), keeping the children relative to it.
+    fn move_to(&mut self, x: f64, y: f64) {
+        if let Some(bounds) = self.content_bounding_box() {
+            self.move_by(x - bounds.min.x, y - bounds.min.y);
+        }
+    }
+
+    ///
+    fn move_by(&mut self, dx: f64, dy: f64) {
+        for shape in &mut self.shapes {
+            move_child_by(shape.as_mut(), dx, dy);
+        }
+    }
+
+    /// Scales the children proportionally so their bounding box has the given width and height, keeping its top left in place.
+    fn resize(&mut self, dim: Dimensions) -> Result<(), String> {
+        if let Dimensions::Double(width, height) = dim {
+            let bounds = match self.content_bounding_box() {
+                Some(bounds) => bounds,
+                None => return Err(String::from("Cannot resize Group because it has no shapes")),
+            };
+
+            if width <= 0.0 || height <= 0.0 {
+                return Err(format!("Cannot resize Group with dimensions {}, width and height must be positive", dim));
+            }
+
+            // A box with no extent in one direction can't be stretched in that direction
+            let sx = if bounds.width() > 0.0 { width / bounds.width() } else { 1.0 };
+            let sy = if bounds.height() > 0.0 { height / bounds.height() } else { 1.0 };
+
+            let scale = Transform::translate(-bounds.min.x, -bounds.min.y)
+                .then(&Transform::scale(sx, sy))
+                .then(&Transform::translate(bounds.min.x, bounds.min.y));
+
+            for shape in &mut self.shapes {
+                shape.apply_transform(scale);
+            }
+
+            Ok(())
+        } else {
+            Err(format!("Cannot resize Group with dimensions {}", dim))
+        }
+    }
+
+    ///
+    fn outlines_in(&self, outer: &Transform) -> Vec<Outline> {
+        let transform = self.transform.then(outer);
+        self.shapes.iter().flat_map(|shape| shape.outlines_in(&transform)).collect()
+    }
+
+    ///
+    fn painted_bounding_box_in(&self, outer: &Transform, inherited: &Style) -> Option<BoundingBox> {
+        let transform = self.transform.then(outer);
+        let style = self.style.inherit(inherited);
+        union_all(self.shapes.iter().map(|shape| shape.painted_bounding_box_in(&transform, &style)))
+    }
+
+    /// The distance to the nearest child, with each child's style on top of the group's.
+    fn distance_in(&self, point: Point, outer: &Transform, inherited: &Style) -> f64 {
+        let transform = self.transform.then(outer);
+        let style = self.style.inherit(inherited);
+        self.shapes.iter()
+            .map(|shape| shape.distance_in(point, &transform, &style))
+            .fold(f64::INFINITY, f64::min)
+    }
 }
 
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use super::super::{Circle, Rect};
+    use super::super::{Circle, Ellipse, Point, Rect, Path};
+    use super::super::bounding_box::assert_close;
 
     /// Verify the `get_shape_htmls` function returns a list of svg strings for each shape in the group.
     #[test]
@@ -107,4 +375,193 @@ mod tests {
 </g>";
         assert_eq!(group.to_html(), expected_html);
     }
-}
\ No newline at end of file
+
+    /// Verify the group's style is written on the `g` element, so children inherit it unless they override it.
+    #[test]
+    fn inherited_style() {
+        let mut group = Group::new("test_group");
+        group.get_style_mut().set_fill("blue");
+        group.get_style_mut().set_stroke("black");
+
+        let mut circle = Circle::new(1.0, 2.0, 3.0);
+        circle.get_style_mut().set_fill("red");
+        group.add_shape(circle.box_ptr());
+        group.add_shape(Rect::new(0.0, 1.0, 2.0, 3.0).box_ptr());
+
+        let expected_html = "\
+<g name=\"test_group\" fill=\"blue\" stroke=\"black\">
+<circle cx=\"1\" cy=\"2\" r=\"3\" fill=\"red\"></circle>
+<rect x=\"0\" y=\"1\" width=\"2\" height=\"3\"></rect>
+</g>";
+        assert_eq!(group.to_html(), expected_html);
+    }
+
+    /// Verify the group's transform is written on the `g` element, and composed with each child's transform.
+    #[test]
+    fn composed_transform() {
+        let mut group = Group::new("test_group");
+        group.translate(10.0, 0.0);
+
+        let mut rect = Rect::new(0.0, 0.0, 2.0, 3.0);
+        rect.scale(2.0, 2.0);
+        group.add_shape(rect.box_ptr());
+        group.add_shape(Circle::new(1.0, 2.0, 3.0).box_ptr());
+
+        let expected_html = "\
+<g name=\"test_group\" transform=\"matrix(1 0 0 1 10 0)\">
+<rect x=\"0\" y=\"0\" width=\"2\" height=\"3\" transform=\"matrix(2 0 0 2 0 0)\"></rect>
+<circle cx=\"1\" cy=\"2\" r=\"3\"></circle>
+</g>";
+        assert_eq!(group.to_html(), expected_html);
+
+        // The child is scaled first, then moved by the group
+        let transform = group.get_shape_transform(0).unwrap();
+        assert_eq!(transform.apply(Point{ x: 1.0, y: 1.0 }), Point{ x: 12.0, y: 2.0 });
+        assert_eq!(group.get_shape_transform(1), Some(Transform::translate(10.0, 0.0)));
+        assert_eq!(group.get_shape_transform(2), None);
+    }
+
+    /// Verify `move_to` moves the children's bounding box, keeping nested groups and transformed children in place relative to it.
+    #[test]
+    fn move_to() {
+        let mut inner = Group::new("inner");
+        inner.add_shape(Rect::new(10.0, 10.0, 4.0, 2.0).box_ptr());
+        let mut scaled = Circle::new(0.0, 0.0, 1.0);
+        scaled.scale(2.0, 2.0);
+        inner.add_shape(scaled.box_ptr());
+        inner.translate(5.0, 0.0);
+
+        let mut group = Group::new("outer");
+        group.add_shape(Path::from_points(vec![Point{ x: 1.0, y: 1.0 }, Point{ x: 3.0, y: 4.0 }]).unwrap().box_ptr());
+        group.add_shape(inner.box_ptr());
+        assert_eq!(group.content_bounding_box(), Some(BoundingBox::new(1.0, -2.0, 18.0, 14.0)));
+
+        group.move_to(0.0, 0.0);
+        assert_eq!(group.content_bounding_box(), Some(BoundingBox::new(0.0, 0.0, 18.0, 14.0)));
+
+        let expected_html = "\
+<g name=\"outer\">
+<path d=\"M 0 3 L 2 6\"></path>
+<g name=\"inner\" transform=\"matrix(1 0 0 1 5 0)\">
+<rect x=\"9\" y=\"12\" width=\"4\" height=\"2\"></rect>
+<circle cx=\"-0.5\" cy=\"1\" r=\"1\" transform=\"matrix(2 0 0 2 0 0)\"></circle>
+</g>
+</g>";
+        assert_eq!(group.to_html(), expected_html);
+
+        // Verify the group's own transform is applied after moving its children
+        group.translate(1.0, 1.0);
+        group.move_to(2.0, 2.0);
+        assert_eq!(group.bounding_box(), Some(BoundingBox::new(3.0, 3.0, 18.0, 14.0)));
+    }
+
+    /// Verify `resize` scales nested children proportionally around the top left of their bounding box.
+    #[test]
+    fn resize() {
+        let mut inner = Group::new("inner");
+        inner.add_shape(Rect::new(2.0, 2.0, 2.0, 2.0).box_ptr());
+
+        let mut group = Group::new("outer");
+        group.add_shape(Rect::new(0.0, 0.0, 1.0, 1.0).box_ptr());
+        group.add_shape(inner.box_ptr());
+        assert_eq!(group.content_bounding_box(), Some(BoundingBox::new(0.0, 0.0, 4.0, 4.0)));
+
+        group.resize(Dimensions::Double(8.0, 2.0)).unwrap();
+        assert_eq!(group.content_bounding_box(), Some(BoundingBox::new(0.0, 0.0, 8.0, 2.0)));
+        assert_eq!(group.get_shape(1).unwrap().bounding_box(), Some(BoundingBox::new(4.0, 1.0, 4.0, 1.0)));
+
+        // Verify invalid dimensions and empty groups are rejected
+        let err = group.resize(Dimensions::Double(0.0, 1.0)).unwrap_err();
+        assert_eq!(err, "Cannot resize Group with dimensions Double(0.0, 1.0), width and height must be positive");
+        let err = group.resize(Dimensions::Single(1.0)).unwrap_err();
+        assert_eq!(err, "Cannot resize Group with dimensions Single(1.0)");
+        let err = Group::new("empty").resize(Dimensions::Double(1.0, 1.0)).unwrap_err();
+        assert_eq!(err, "Cannot resize Group because it has no shapes");
+        assert_eq!(group.content_bounding_box(), Some(BoundingBox::new(0.0, 0.0, 8.0, 2.0)));
+    }
+
+    /// Verify the bounding box composes the transforms of nested shapes, rather than transforming their boxes.
+    #[test]
+    fn bounding_box() {
+        let mut rect = Rect::new(0.0, 0.0, 2.0, 1.0);
+        rect.rotate(45.0, 0.0, 0.0);
+
+        let mut group = Group::new("rotated");
+        group.add_shape(rect.box_ptr());
+        group.add_shape(Ellipse::new(10.0, 0.0, 2.0, 1.0).box_ptr());
+        group.rotate(-45.0, 0.0, 0.0);
+
+        let extent = 2.5_f64.sqrt();
+        let center = 10.0 * 0.5_f64.sqrt();
+        let expected = BoundingBox::new(0.0, 0.0, 2.0, 1.0)
+            .union(&BoundingBox::new(center - extent, -center - extent, 2.0 * extent, 2.0 * extent));
+        assert_close(group.bounding_box().unwrap(), expected);
+
+        let mut outer = Group::new("outer");
+        outer.add_shape(group.box_ptr());
+        outer.add_shape(Group::new("empty").box_ptr());
+        outer.translate(1.0, 1.0);
+        assert_close(outer.bounding_box().unwrap(), BoundingBox { min: Point{ x: expected.min.x + 1.0, y: expected.min.y + 1.0 }, max: Point{ x: expected.max.x + 1.0, y: expected.max.y + 1.0 } });
+        assert_eq!(Group::new("empty").bounding_box(), None);
+    }
+
+    /// Verify shapes are found and removed by uuid, including those inside nested groups.
+    #[test]
+    fn find_and_remove() {
+        let circle = Circle::new(0.0, 0.0, 1.0);
+        let circle_id = circle.get_uuid();
+        let mut inner = Group::new("inner");
+        inner.add_shape(circle.box_ptr());
+
+        let rect = Rect::new(0.0, 0.0, 1.0, 1.0);
+        let rect_id = rect.get_uuid();
+        let inner_id = inner.get_uuid();
+        let mut outer = Group::new("outer");
+        outer.add_shape(rect.box_ptr());
+        outer.add_shape(inner.box_ptr());
+
+        assert_eq!(outer.uuids(), vec![rect_id.clone(), inner_id.clone(), circle_id.clone()]);
+        assert_eq!(outer.position(&circle_id), Some(1));
+        assert_eq!(outer.find(&circle_id).unwrap().get_uuid(), circle_id);
+        assert!(outer.find("missing").is_none());
+
+        outer.find_mut(&circle_id).unwrap().move_to(5.0, 5.0);
+        assert_close(outer.find(&circle_id).unwrap().bounding_box().unwrap(), BoundingBox::new(4.0, 4.0, 2.0, 2.0));
+
+        assert_eq!(outer.remove_by_uuid(&circle_id).unwrap().get_uuid(), circle_id);
+        assert_eq!(outer.uuids(), vec![rect_id.clone(), inner_id.clone()]);
+        assert_eq!(outer.remove_by_uuid(&rect_id).unwrap().get_uuid(), rect_id);
+        assert_eq!(outer.uuids(), vec![inner_id]);
+        assert!(outer.remove_by_uuid(&rect_id).is_none());
+        assert!(outer.remove_shape(1).is_none());
+    }
+
+    /// Verify shapes can be inserted and reordered, and duplicating a group gives everything in it a new uuid.
+    #[test]
+    fn reorder_and_duplicate() {
+        let mut group = Group::new("shapes");
+        let ids = (0..3).map(|i| {
+            let rect = Rect::new(i as f64, 0.0, 1.0, 1.0);
+            let id = rect.get_uuid();
+            group.add_shape(rect.box_ptr());
+            id
+        }).collect::<Vec<String>>();
+
+        group.reorder_shape(0, 2).unwrap();
+        assert_eq!(group.uuids(), vec![ids[1].clone(), ids[2].clone(), ids[0].clone()]);
+        group.reorder_shape(2, 1).unwrap();
+        assert_eq!(group.uuids(), vec![ids[1].clone(), ids[0].clone(), ids[2].clone()]);
+        assert_eq!(group.reorder_shape(1, 3).unwrap_err(), "No shape found at index 3");
+
+        let mut nested = Group::new("nested");
+        nested.add_shape(Circle::new(0.0, 0.0, 1.0).box_ptr());
+        group.insert_shape(0, nested.box_ptr());
+        assert_eq!(group.len(), 4);
+
+        let copy = group.duplicate_group();
+        assert_eq!(copy.get_name(), "shapes");
+        assert_eq!(copy.uuids().len(), 5);
+        assert!(copy.uuids().iter().all(|uuid| !group.uuids().contains(uuid)));
+        assert_eq!(copy.to_html().replace(&copy.get_uuid(), ""), group.to_html().replace(&group.get_uuid(), ""));
+    }
+}
@@ -0,0 +1,318 @@
+use std::collections::VecDeque;
+use std::fmt;
+
+use super::{SVG, Style, Transform, Group, Grid};
+
+
+/// How many operations a grid remembers unless told otherwise.
+pub const DEFAULT_DEPTH: usize = 100;
+
+
+/// A change to a grid, holding everything needed to make it again or to undo it.
+/// Shapes are identified by uuid, and placed in a group by the group's uuid and an index within it.
+pub enum Operation {
+    /// A shape inserted into a group
+    Add { parent: String, index: usize, shape: Box<dyn SVG> },
+    /// A shape removed from a group
+    Delete { parent: String, index: usize, shape: Box<dyn SVG> },
+    /// A shape moved to a new position, as the whole shape before and after
+    Move { before: Box<dyn SVG>, after: Box<dyn SVG> },
+    /// A shape resized, as the whole shape before and after
+    Resize { before: Box<dyn SVG>, after: Box<dyn SVG> },
+    ///
+    Style { uuid: String, before: Style, after: Style },
+    ///
+    Transform { uuid: String, before: Transform, after: Transform },
+    /// A shape moved from one index to another within its group, changing what it's drawn above
+    Restack { uuid: String, from: usize, to: usize },
+    /// One of the grid's own groups added, replaced or removed at an index
+    Group { index: usize, before: Option<Box<Group>>, after: Option<Box<Group>> },
+    /// Several operations made together, such as moving every selected shape, and undone as one
+    Batch { operations: Vec<Operation> },
+}
+
+impl Operation {
+    /// The operation that undoes this one.
+    pub fn inverse(&self) -> Operation {
+        match self {
+            Operation::Add { parent, index, shape } => {
+                Operation::Delete { parent: parent.clone(), index: *index, shape: shape.clone_box() }
+            },
+            Operation::Delete { parent, index, shape } => {
+                Operation::Add { parent: parent.clone(), index: *index, shape: shape.clone_box() }
+            },
+            Operation::Move { before, after } => {
+                Operation::Move { before: after.clone_box(), after: before.clone_box() }
+            },
+            Operation::Resize { before, after } => {
+                Operation::Resize { before: after.clone_box(), after: before.clone_box() }
+            },
+            Operation::Style { uuid, before, after } => {
+                Operation::Style { uuid: uuid.clone(), before: after.clone(), after: before.clone() }
+            },
+            Operation::Transform { uuid, before, after } => {
+                Operation::Transform { uuid: uuid.clone(), before: *after, after: *before }
+            },
+            Operation::Restack { uuid, from, to } => {
+                Operation::Restack { uuid: uuid.clone(), from: *to, to: *from }
+            },
+            Operation::Group { index, before, after } => {
+                Operation::Group { index: *index, before: after.clone(), after: before.clone() }
+            },
+            Operation::Batch { operations } => {
+                Operation::Batch { operations: operations.iter().rev().map(Operation::inverse).collect() }
+            },
+        }
+    }
+
+    /// Make the change to `grid`. The operation keeps its own copies, so it can be applied again after being undone.
+    pub fn apply(&self, grid: &mut Grid) -> Result<(), String> {
+        match self {
+            Operation::Add { parent, index, shape } => grid.insert_into(parent, *index, shape.clone_box()),
+            Operation::Delete { shape, .. } => grid.take_by_uuid(&shape.get_uuid()).map(|_| ()),
+            Operation::Move { after, .. } | Operation::Resize { after, .. } => grid.replace_by_uuid(after.clone_box()),
+            Operation::Style { uuid, after, .. } => match grid.get_by_uuid_mut(uuid) {
+                Some(shape) => {
+                    shape.set_style(after.clone());
+                    Ok(())
+                },
+                None => Err(format!("No shape found with uuid #{}", uuid)),
+            },
+            Operation::Transform { uuid, after, .. } => match grid.get_by_uuid_mut(uuid) {
+                Some(shape) => {
+                    shape.set_transform(*after);
+                    Ok(())
+                },
+                None => Err(format!("No shape found with uuid #{}", uuid)),
+            },
+            Operation::Restack { uuid, to, .. } => grid.reorder_by_uuid(uuid, *to),
+            Operation::Group { index, before, after } => {
+                if before.is_some() {
+                    grid.remove_group(*index)?;
+                }
+
+                if let Some(after) = after {
+                    grid.insert_group(*index, after.as_ref().clone());
+                }
+
+                Ok(())
+            },
+            Operation::Batch { operations } => {
+                for (i, operation) in operations.iter().enumerate() {
+                    if let Err(e) = operation.apply(grid) {
+                        // Take back the operations already made, so the batch is made entirely or not at all
+                        for made in operations[..i].iter().rev() {
+                            made.inverse().apply(grid).map_err(|undo_error| format!("{}, then {}", e, undo_error))?;
+                        }
+                        return Err(e);
+                    }
+                }
+
+                Ok(())
+            },
+        }
+    }
+}
+
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // The start of a uuid is enough to tell shapes apart, and can be used to refer to them in commands
+        let short = |uuid: String| uuid.chars().take(8).collect::<String>();
+
+        match self {
+            Operation::Add { shape, .. } => write!(f, "add #{}", short(shape.get_uuid())),
+            Operation::Delete { shape, .. } => write!(f, "delete #{}", short(shape.get_uuid())),
+            Operation::Move { after, .. } => write!(f, "move #{}", short(after.get_uuid())),
+            Operation::Resize { after, .. } => write!(f, "resize #{}", short(after.get_uuid())),
+            Operation::Style { uuid, .. } => write!(f, "style #{}", short(uuid.clone())),
+            Operation::Transform { uuid, .. } => write!(f, "transform #{}", short(uuid.clone())),
+            Operation::Restack { uuid, from, to } => write!(f, "restack #{} from {} to {}", short(uuid.clone()), from, to),
+            Operation::Group { before, after, .. } => match (before, after) {
+                (_, Some(group)) => write!(f, "group {}", group.get_name()),
+                (Some(group), None) => write!(f, "remove group {}", group.get_name()),
+                (None, None) => write!(f, "group"),
+            },
+            Operation::Batch { operations } => {
+                write!(f, "{}", operations.iter().map(Operation::to_string).collect::<Vec<String>>().join(", "))
+            },
+        }
+    }
+}
+
+
+/// The operations made to a grid, most recent last, and those undone since, so they can be redone.
+/// Only the most recent `depth` operations are kept.
+pub struct History {
+    done: VecDeque<Operation>,
+    undone: Vec<Operation>,
+    depth: usize,
+}
+
+impl History {
+    ///
+    pub fn new(depth: usize) -> History {
+        History { done: VecDeque::new(), undone: vec![], depth }
+    }
+
+    ///
+    pub fn get_depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Change how many operations are kept, forgetting the oldest ones if there are now too many.
+    pub fn set_depth(&mut self, depth: usize) {
+        self.depth = depth;
+        self.trim();
+    }
+
+    /// Remember a new operation. Anything undone can no longer be redone, since it was undone from a different state.
+    pub fn record(&mut self, operation: Operation) {
+        self.undone.clear();
+        self.push_done(operation);
+    }
+
+    ///
+    pub fn clear(&mut self) {
+        self.done.clear();
+        self.undone.clear();
+    }
+
+    /// The operations that can be undone, oldest first.
+    pub fn done(&self) -> impl Iterator<Item = &Operation> {
+        self.done.iter()
+    }
+
+    /// The operations that can be redone, in the order they would be redone.
+    pub fn undone(&self) -> impl Iterator<Item = &Operation> {
+        self.undone.iter().rev()
+    }
+
+    ///
+    pub(super) fn push_done(&mut self, operation: Operation) {
+        self.done.push_back(operation);
+        self.trim();
+    }
+
+    ///
+    pub(super) fn pop_done(&mut self) -> Option<Operation> {
+        self.done.pop_back()
+    }
+
+    ///
+    pub(super) fn push_undone(&mut self, operation: Operation) {
+        self.undone.push(operation);
+    }
+
+    ///
+    pub(super) fn pop_undone(&mut self) -> Option<Operation> {
+        self.undone.pop()
+    }
+
+    ///
+    fn trim(&mut self) {
+        while self.done.len() > self.depth {
+            self.done.pop_front();
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::{CoordinateSystem, Dimensions, Rect, Circle, Point};
+
+    type Edit<'a> = Box<dyn Fn(&mut Grid) + 'a>;
+
+    /// Verify every kind of operation can be undone and redone, returning the grid to the same state each time.
+    #[test]
+    fn undo_and_redo() {
+        let mut grid = Grid::new(CoordinateSystem::TopLeftDownRight);
+        let mut states = vec![grid.to_html()];
+
+        grid.add_shape(Rect::new(0.0, 0.0, 10.0, 10.0).box_ptr());
+        grid.add_shape(Circle::new(50.0, 50.0, 5.0).box_ptr());
+        states.push(grid.to_html());
+        let rect = grid.get_shape(0).unwrap().get_uuid();
+        let circle = grid.get_shape(1).unwrap().get_uuid();
+
+        let mut style = Style::new();
+        style.set_fill("red");
+        let edits: Vec<Edit> = vec![
+            Box::new(|grid| grid.move_shape_by_uuid(&rect, 20.0, 20.0).unwrap()),
+            Box::new(|grid| grid.resize_shape_by_uuid(&circle, Dimensions::Single(8.0)).unwrap()),
+            Box::new(|grid| grid.set_style_by_uuid(&rect, style.clone()).unwrap()),
+            Box::new(|grid| grid.apply_transform_by_uuid(&circle, Transform::rotate(30.0)).unwrap()),
+            Box::new(|grid| grid.perform(Operation::Batch { operations: vec![
+                Operation::Style { uuid: circle.clone(), before: Style::new(), after: style.clone() },
+                Operation::Transform { uuid: circle.clone(), before: Transform::rotate(30.0), after: Transform::identity() },
+            ] }).unwrap()),
+            Box::new(|grid| grid.to_back(&circle).unwrap()),
+            Box::new(|grid| { grid.duplicate_by_uuid(&rect, 5.0, 5.0).unwrap(); }),
+            Box::new(|grid| { grid.remove_by_uuid(&rect).unwrap(); }),
+            Box::new(|grid| { grid.add_group(Group::new("overlay")); }),
+        ];
+        for edit in &edits {
+            edit(&mut grid);
+            states.push(grid.to_html());
+        }
+        assert_eq!(grid.get_history().done().count(), edits.len() + 2);
+
+        // The two adds are undone separately, so skip the state between them
+        for state in states[..states.len() - 1].iter().skip(1).rev() {
+            grid.undo().unwrap();
+            assert_eq!(&grid.to_html(), state);
+        }
+        grid.undo().unwrap();
+        grid.undo().unwrap();
+        assert_eq!(grid.to_html(), states[0]);
+        assert_eq!(grid.undo().unwrap_err(), "Nothing to undo");
+
+        grid.redo().unwrap();
+        for state in states.iter().skip(1) {
+            grid.redo().unwrap();
+            assert_eq!(&grid.to_html(), state);
+        }
+        assert_eq!(grid.redo().unwrap_err(), "Nothing to redo");
+
+        // Undone hit testing and lookups follow the restored shapes
+        grid.undo().unwrap();
+        grid.undo().unwrap();
+        assert_eq!(grid.shapes_at(Point{ x: 22.0, y: 22.0 }, 0.0)[0].get_uuid(), rect);
+        assert_eq!(grid.get_by_uuid(&rect).unwrap().get_style().fill.as_deref(), Some("red"));
+    }
+
+    /// Verify new operations discard anything undone, and only the most recent `depth` operations are kept.
+    #[test]
+    fn depth() {
+        let mut grid = Grid::new(CoordinateSystem::TopLeftDownRight);
+        grid.set_history_depth(3);
+
+        for i in 0..5 {
+            grid.add_shape(Rect::new(i as f64, 0.0, 1.0, 1.0).box_ptr());
+        }
+        assert_eq!(grid.get_history().done().count(), 3);
+        assert_eq!(grid.get_history().get_depth(), 3);
+
+        grid.undo().unwrap();
+        assert_eq!(grid.get_history().undone().count(), 1);
+        grid.move_shape(0, 10.0, 10.0).unwrap();
+        assert_eq!(grid.get_history().undone().count(), 0);
+        assert_eq!(grid.redo().unwrap_err(), "Nothing to redo");
+
+        let descriptions = grid.get_history().done().map(|operation| operation.to_string()).collect::<Vec<String>>();
+        let uuid = |i: usize| grid.get_shape(i).unwrap().get_uuid()[..8].to_string();
+        assert_eq!(descriptions, vec![format!("add #{}", uuid(2)), format!("add #{}", uuid(3)), format!("move #{}", uuid(0))]);
+
+        grid.set_history_depth(1);
+        assert_eq!(grid.get_history().done().count(), 1);
+        grid.set_history_depth(0);
+        grid.add_shape(Rect::new(0.0, 0.0, 1.0, 1.0).box_ptr());
+        assert_eq!(grid.undo().unwrap_err(), "Nothing to undo");
+
+        // Failed edits leave nothing to undo
+        grid.set_history_depth(10);
+        assert!(grid.resize_shape(0, Dimensions::Single(1.0)).is_err());
+        assert_eq!(grid.get_history().done().count(), 0);
+    }
+}
@@ -0,0 +1,479 @@
+use roxmltree::{Document, Node};
+
+use super::{SVG, Style, Transform, Group, Rect, Circle, Ellipse, Line, Polyline, Polygon, Text, Point, move_child_by};
+use super::path_data::{parse_path_data, parse_number_list};
+
+
+pub(super) const SVG_NAMESPACE: &str = "http://www.w3.org/2000/svg";
+
+
+/// The shapes and canvas size read from an svg document, with the root element's style and transform.
+/// Shapes are placed relative to the top left of the canvas once `transform` is applied.
+///
+/// When every top-level element is a group with its own class, as in documents written by `write_svg`, the groups
+/// are returned as `layers` to become the grid's groups. Otherwise the top-level elements are all in `shapes`,
+/// which keeps the order they are drawn in.
+pub(super) struct ImportedDocument {
+    pub width: Option<f64>,
+    pub height: Option<f64>,
+    pub style: Style,
+    pub transform: Transform,
+    pub layers: Vec<Group>,
+    pub shapes: Vec<Box<dyn SVG>>,
+    pub warnings: Vec<String>,
+}
+
+
+/// Convert an svg document into shapes. Only a malformed document is an error, anything that can't be imported is
+/// skipped and reported as a warning.
+pub(super) fn import_svg(source: &str) -> Result<ImportedDocument, String> {
+    let document = Document::parse(source).map_err(|e| format!("Unable to parse svg document: {}", e))?;
+    let root = document.root_element();
+
+    if root.tag_name().name() != "svg" {
+        return Err(format!("Expected an <svg> root element, found <{}>", root.tag_name().name()));
+    }
+
+    let mut warnings = vec![];
+    let (width, height, origin) = read_dimensions(root, &mut warnings);
+    let mut style = Style::new();
+    import_style(root, &mut style, &mut warnings);
+    let mut root_transform = import_transform(root, &mut warnings).unwrap_or_else(Transform::identity);
+    let mut top_level = drawn_children(root);
+
+    // `write_svg` wraps the groups in a group holding nothing but the grid's transform, which belongs to the root
+    if let [wrapper] = top_level[..] {
+        if is_group(wrapper) && wrapper.has_attribute("transform") && wrapper.attributes().all(|a| a.name() == "transform") {
+            root_transform = import_transform(wrapper, &mut warnings).unwrap_or_else(Transform::identity).then(&root_transform);
+            top_level = drawn_children(wrapper);
+        }
+    }
+
+    let mut classes = top_level.iter().map(|node| node.attribute("class")).collect::<Vec<Option<&str>>>();
+    classes.sort_unstable();
+    classes.dedup();
+    let layered = !top_level.is_empty()
+        && top_level.iter().all(|&node| is_group(node))
+        && classes.len() == top_level.len()
+        && classes.iter().all(Option::is_some);
+
+    let (mut layers, mut shapes) = if layered {
+        (top_level.into_iter().map(|node| import_layer(node, &mut warnings)).collect(), vec![])
+    } else {
+        (vec![], top_level.into_iter().filter_map(|node| import_element(node, &mut warnings)).collect())
+    };
+
+    // The viewBox origin is the user space point shown at the top left, which is where the canvas starts.
+    // With a root transform, the origin is folded into it instead, so shapes keep the coordinates they were drawn in.
+    let transform = if root_transform.is_identity() {
+        if origin.x != 0.0 || origin.y != 0.0 {
+            for layer in &mut layers {
+                move_child_by(layer, -origin.x, -origin.y);
+            }
+            for shape in &mut shapes {
+                move_child_by(shape.as_mut(), -origin.x, -origin.y);
+            }
+        }
+        root_transform
+    } else {
+        root_transform.then(&Transform::translate(-origin.x, -origin.y))
+    };
+
+    Ok(ImportedDocument { width, height, style, transform, layers, shapes, warnings })
+}
+
+
+/// Read the canvas size from the `viewBox`, falling back to the `width` and `height` attributes,
+/// along with the viewBox origin.
+fn read_dimensions(root: Node, warnings: &mut Vec<String>) -> (Option<f64>, Option<f64>, Point) {
+    if let Some(view_box) = root.attribute("viewBox") {
+        match parse_number_list(view_box).as_deref() {
+            Ok(&[min_x, min_y, width, height]) if width > 0.0 && height > 0.0 => {
+                return (Some(width), Some(height), Point { x: min_x, y: min_y });
+            },
+            _ => warnings.push(format!("Invalid viewBox \"{}\" was ignored", view_box)),
+        }
+    }
+
+    let mut dimension = |name| match length(root, name) {
+        Ok(value) if value > 0.0 => Some(value),
+        Ok(_) => None,
+        Err(e) => {
+            warnings.push(format!("{} was ignored", e));
+            None
+        },
+    };
+
+    (dimension("width"), dimension("height"), Point { x: 0.0, y: 0.0 })
+}
+
+
+///
+fn import_children(node: Node, warnings: &mut Vec<String>) -> Vec<Box<dyn SVG>> {
+    node.children()
+        .filter(|child| child.is_element())
+        .filter_map(|child| import_element(child, warnings))
+        .collect()
+}
+
+
+/// The child elements of `node`, leaving out the descriptive elements that draw nothing.
+fn drawn_children<'a, 'input>(node: Node<'a, 'input>) -> Vec<Node<'a, 'input>> {
+    node.children()
+        .filter(|child| child.is_element() && !["title", "desc", "metadata"].contains(&child.tag_name().name()))
+        .collect()
+}
+
+
+///
+fn is_group(node: Node) -> bool {
+    node.tag_name().name() == "g" && node.tag_name().namespace() == Some(SVG_NAMESPACE)
+}
+
+
+/// A top-level group, named by its class as `group_html` writes it.
+fn import_layer(node: Node, warnings: &mut Vec<String>) -> Group {
+    let mut group = Group::new(node.attribute("class").unwrap_or("group"));
+
+    for child in import_children(node, warnings) {
+        group.add_shape(child);
+    }
+
+    import_presentation(node, &mut group, warnings);
+    group
+}
+
+
+/// Convert a single element, reporting why it was skipped if it can't be converted.
+fn import_element(node: Node, warnings: &mut Vec<String>) -> Option<Box<dyn SVG>> {
+    let name = node.tag_name().name();
+
+    if node.tag_name().namespace().is_some_and(|ns| ns != SVG_NAMESPACE) {
+        warnings.push(format!("Element <{}> outside the svg namespace was skipped", name));
+        return None;
+    }
+
+    // Descriptive elements draw nothing, so there is nothing lost by skipping them
+    if ["title", "desc", "metadata"].contains(&name) {
+        return None;
+    }
+
+    let mut shape = match import_shape(node, warnings) {
+        Ok(Some(shape)) => shape,
+        Ok(None) => {
+            warnings.push(format!("Unsupported element <{}> was skipped", name));
+            return None;
+        },
+        Err(e) => {
+            warnings.push(format!("{}, <{}> was skipped", e, name));
+            return None;
+        },
+    };
+
+    import_presentation(node, shape.as_mut(), warnings);
+    Some(shape)
+}
+
+
+/// Read the style and transform of an element onto the shape made from it.
+fn import_presentation(node: Node, shape: &mut dyn SVG, warnings: &mut Vec<String>) {
+    import_style(node, shape.get_style_mut(), warnings);
+
+    if let Some(transform) = import_transform(node, warnings) {
+        shape.set_transform(transform);
+    }
+}
+
+
+/// Read the `transform` attribute, if there is a valid one.
+fn import_transform(node: Node, warnings: &mut Vec<String>) -> Option<Transform> {
+    match Transform::parse(node.attribute("transform")?) {
+        Ok(transform) => Some(transform),
+        Err(e) => {
+            warnings.push(format!("{} on <{}> was ignored", e, node.tag_name().name()));
+            None
+        },
+    }
+}
+
+
+///
+fn import_shape(node: Node, warnings: &mut Vec<String>) -> Result<Option<Box<dyn SVG>>, String> {
+    let shape: Box<dyn SVG> = match node.tag_name().name() {
+        "g" => {
+            let mut group = Group::new(node.attribute("id").or_else(|| node.attribute("class")).unwrap_or("group"));
+
+            for child in import_children(node, warnings) {
+                group.add_shape(child);
+            }
+
+            group.box_ptr()
+        },
+        "rect" => Rect::new(
+            length(node, "x")?,
+            length(node, "y")?,
+            length(node, "width")?,
+            length(node, "height")?,
+        ).box_ptr(),
+        "circle" => Circle::new(length(node, "cx")?, length(node, "cy")?, length(node, "r")?).box_ptr(),
+        "ellipse" => Ellipse::new(
+            length(node, "cx")?,
+            length(node, "cy")?,
+            length(node, "rx")?,
+            length(node, "ry")?,
+        ).box_ptr(),
+        "line" => Line::new(
+            length(node, "x1")?,
+            length(node, "y1")?,
+            length(node, "x2")?,
+            length(node, "y2")?,
+        ).box_ptr(),
+        "polyline" => Polyline::from_points(points(node)?)?.box_ptr(),
+        "polygon" => Polygon::from_points(points(node)?)?.box_ptr(),
+        "path" => {
+            let data = node.attribute("d").unwrap_or("");
+            parse_path_data(data).map_err(|e| format!("Invalid path data: {}", e))?.box_ptr()
+        },
+        "text" => import_text(node)?.box_ptr(),
+        _ => return Ok(None),
+    };
+
+    Ok(Some(shape))
+}
+
+
+///
+fn import_text(node: Node) -> Result<Text, String> {
+    let content = node.descendants()
+        .filter(|n| n.is_text())
+        .filter_map(|n| n.text())
+        .collect::<String>();
+
+    // x and y may be lists positioning each character, only the first position is kept
+    let first = |name| match node.attribute(name) {
+        Some(value) => parse_number_list(value)
+            .map(|values| values.first().copied().unwrap_or(0.0))
+            .map_err(|e| format!("Invalid {} \"{}\": {}", name, value, e)),
+        None => Ok(0.0),
+    };
+
+    let mut text = Text::new(first("x")?, first("y")?, content.trim());
+
+    if let Some(family) = node.attribute("font-family") {
+        text.set_font_family(family);
+    }
+    if node.has_attribute("font-size") {
+        text.set_font_size(length(node, "font-size")?)?;
+    }
+    if let Some(weight) = node.attribute("font-weight") {
+        text.set_font_weight(weight);
+    }
+    if let Some(anchor) = node.attribute("text-anchor") {
+        text.set_text_anchor(anchor)?;
+    }
+
+    Ok(text)
+}
+
+
+/// Read presentation attributes, from both attributes and the inline `style`, which takes precedence.
+fn import_style(node: Node, style: &mut Style, warnings: &mut Vec<String>) {
+    const KEYS: [&str; 5] = ["fill", "fill-rule", "stroke", "stroke-width", "opacity"];
+
+    let mut declarations = KEYS.iter()
+        .filter_map(|&key| node.attribute(key).map(|value| (key, value)))
+        .collect::<Vec<(&str, &str)>>();
+
+    if let Some(inline) = node.attribute("style") {
+        declarations.extend(inline.split(';')
+            .filter_map(|declaration| declaration.split_once(':'))
+            .map(|(key, value)| (key.trim(), value.trim()))
+            .filter(|(key, _)| KEYS.contains(key)));
+    }
+
+    for (key, value) in declarations {
+        let value = if key == "stroke-width" { value.trim_end_matches("px") } else { value };
+
+        if let Err(e) = style.set(key, value) {
+            warnings.push(format!("{} on <{}> was ignored", e, node.tag_name().name()));
+        }
+    }
+}
+
+
+/// Read a length attribute in user units, defaulting to 0 when it is missing.
+fn length(node: Node, name: &str) -> Result<f64, String> {
+    match node.attribute(name) {
+        // Rust also parses "NaN", "inf" and out of range literals, none of which are valid svg numbers
+        Some(value) => match value.trim().trim_end_matches("px").parse::<f64>() {
+            Ok(length) if length.is_finite() => Ok(length),
+            _ => Err(format!("Invalid {} \"{}\"", name, value)),
+        },
+        None => Ok(0.0),
+    }
+}
+
+
+///
+fn points(node: Node) -> Result<Vec<Point>, String> {
+    let value = node.attribute("points").unwrap_or("");
+    let numbers = parse_number_list(value).map_err(|e| format!("Invalid points: {}", e))?;
+
+    if !numbers.len().is_multiple_of(2) {
+        return Err(String::from("Invalid points: expected x and y pairs"));
+    }
+
+    Ok(numbers.chunks(2).map(|pair| Point { x: pair[0], y: pair[1] }).collect())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::BoundingBox;
+
+    /// Verify every supported element is converted, keeping document order and group nesting.
+    #[test]
+    fn supported_elements() {
+        let document = import_svg(r##"
+            <svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 200 100">
+                <rect x="1" y="2" width="3" height="4" fill="#f00"/>
+                <circle cx="5" cy="6" r="7"/>
+                <ellipse cx="8" cy="9" rx="10" ry="11"/>
+                <line x1="0" y1="0" x2="10" y2="10" stroke="black"/>
+                <polyline points="0,0 1,1 2,0"/>
+                <polygon points="0 0 1 1 2 0"/>
+                <path d="M 0 0 L 10 10 Z"/>
+                <g id="labels" style="fill: blue; stroke-width: 2px" transform="translate(5 0) scale(2)">
+                    <text x="5" y="5" font-size="12" text-anchor="middle">A &amp; B</text>
+                </g>
+            </svg>
+        "##).unwrap();
+
+        assert_eq!(document.width, Some(200.0));
+        assert_eq!(document.height, Some(100.0));
+        assert_eq!(document.warnings, Vec::<String>::new());
+        assert_eq!(document.shapes.iter().map(|shape| shape.to_html()).collect::<Vec<String>>(), vec![
+            "<rect x=\"1\" y=\"2\" width=\"3\" height=\"4\" fill=\"#f00\"></rect>",
+            "<circle cx=\"5\" cy=\"6\" r=\"7\"></circle>",
+            "<ellipse cx=\"8\" cy=\"9\" rx=\"10\" ry=\"11\"></ellipse>",
+            "<line x1=\"0\" y1=\"0\" x2=\"10\" y2=\"10\" stroke=\"black\"></line>",
+            "<polyline points=\"0,0 1,1 2,0\"></polyline>",
+            "<polygon points=\"0,0 1,1 2,0\"></polygon>",
+            "<path d=\"M 0 0 L 10 10 Z\"></path>",
+            "<g name=\"labels\" transform=\"matrix(2 0 0 2 5 0)\" fill=\"blue\" stroke-width=\"2\">\n<text x=\"5\" y=\"5\" font-size=\"12\" text-anchor=\"middle\">A &amp; B</text>\n</g>",
+        ]);
+    }
+
+    /// Verify unsupported elements and invalid attributes are skipped with a warning rather than failing the import.
+    #[test]
+    fn warnings() {
+        let document = import_svg(r#"
+            <svg xmlns="http://www.w3.org/2000/svg" xmlns:x="urn:example" width="300px" height="150">
+                <defs><linearGradient id="g"/></defs>
+                <x:meta/>
+                <rect width="wide" height="1"/>
+                <rect width="NaN" height="1"/>
+                <circle r="1e999"/>
+                <path d="M 0 0 L"/>
+                <circle r="5" opacity="2" transform="spin(2)"/>
+            </svg>
+        "#).unwrap();
+
+        assert_eq!(document.width, Some(300.0));
+        assert_eq!(document.height, Some(150.0));
+        assert_eq!(document.warnings, vec![
+            "Unsupported element <defs> was skipped",
+            "Element <meta> outside the svg namespace was skipped",
+            "Invalid width \"wide\", <rect> was skipped",
+            "Invalid width \"NaN\", <rect> was skipped",
+            "Invalid r \"1e999\", <circle> was skipped",
+            "Invalid path data: Expected a number but reached the end of the path data at position 7, <path> was skipped",
+            "Opacity must be between 0 and 1, got 2 on <circle> was ignored",
+            "Invalid transform function spin with 1 argument on <circle> was ignored",
+        ]);
+        assert_eq!(document.shapes.len(), 1);
+        assert_eq!(document.shapes[0].to_html(), "<circle cx=\"0\" cy=\"0\" r=\"5\"></circle>");
+    }
+
+    /// Verify a viewBox origin moves the shapes so it becomes the top left of the canvas, through any transforms.
+    #[test]
+    fn view_box_origin() {
+        let document = import_svg(r#"
+            <svg xmlns="http://www.w3.org/2000/svg" viewBox="-50 -25 100 50">
+                <rect x="-50" y="-25" width="10" height="10"/>
+                <g transform="scale(2)">
+                    <circle cx="0" cy="0" r="1"/>
+                </g>
+            </svg>
+        "#).unwrap();
+
+        assert_eq!(document.warnings, Vec::<String>::new());
+        assert_eq!(document.shapes[0].to_html(), "<rect x=\"0\" y=\"0\" width=\"10\" height=\"10\"></rect>");
+        assert_eq!(document.shapes[1].bounding_box(), Some(BoundingBox::new(48.0, 23.0, 4.0, 4.0)));
+    }
+
+    /// Verify the root element's style and transform are kept, with the viewBox origin folded into the transform.
+    #[test]
+    fn root_attributes() {
+        let document = import_svg(r#"
+            <svg xmlns="http://www.w3.org/2000/svg" viewBox="0 -50 100 50" fill="none" style="stroke: red"
+                 transform="scale(1 -1)">
+                <rect x="10" y="10" width="5" height="5"/>
+            </svg>
+        "#).unwrap();
+
+        assert_eq!(document.warnings, Vec::<String>::new());
+        assert_eq!(document.style.to_attrs(), vec![("fill", String::from("none")), ("stroke", String::from("red"))]);
+        assert_eq!(document.transform, Transform::new(1.0, 0.0, 0.0, -1.0, 0.0, 50.0));
+        assert_eq!(document.shapes[0].to_html(), "<rect x=\"10\" y=\"10\" width=\"5\" height=\"5\"></rect>");
+
+        let document = import_svg(r#"<svg xmlns="http://www.w3.org/2000/svg" transform="spin(1)"/>"#).unwrap();
+        assert_eq!(document.transform, Transform::identity());
+        assert_eq!(document.warnings, vec!["Invalid transform function spin with 1 argument on <svg> was ignored"]);
+    }
+
+    /// Verify top-level groups with distinct classes become layers, unwrapped from a group holding only the root
+    /// transform, while any other top-level element keeps every group nested in the shapes.
+    #[test]
+    fn layers() {
+        let document = import_svg(r#"
+            <svg xmlns="http://www.w3.org/2000/svg" viewBox="0 -50 100 50">
+                <title>Layers</title>
+                <g transform="scale(1 -1)">
+                    <g class="shapes"><rect x="1" y="2" width="3" height="4"/></g>
+                    <g class="labels" fill="red" transform="translate(1 1)"><circle r="1"/></g>
+                </g>
+            </svg>
+        "#).unwrap();
+
+        assert_eq!(document.warnings, Vec::<String>::new());
+        assert!(document.shapes.is_empty());
+        assert_eq!(document.transform, Transform::new(1.0, 0.0, 0.0, -1.0, 0.0, 50.0));
+        assert_eq!(document.layers.iter().map(|layer| layer.to_html()).collect::<Vec<String>>(), vec![
+            "<g name=\"shapes\">\n<rect x=\"1\" y=\"2\" width=\"3\" height=\"4\"></rect>\n</g>",
+            "<g name=\"labels\" transform=\"matrix(1 0 0 1 1 1)\" fill=\"red\">\n<circle cx=\"0\" cy=\"0\" r=\"1\"></circle>\n</g>",
+        ]);
+
+        for (top_level, layers, shapes) in [
+            (r#"<g class="a"/><rect width="1" height="1"/>"#, 0, 2),
+            (r#"<g class="a"/><g/>"#, 0, 2),
+            (r#"<g class="a"/><g class="a"/>"#, 0, 2),
+            // A group with a class is a layer rather than a wrapper around the layers
+            (r#"<g class="a" transform="scale(2)"><g class="b"/></g>"#, 1, 0),
+        ] {
+            let document = import_svg(&format!(r#"<svg xmlns="http://www.w3.org/2000/svg">{}</svg>"#, top_level)).unwrap();
+            assert_eq!((document.layers.len(), document.shapes.len()), (layers, shapes), "{}", top_level);
+        }
+    }
+
+    /// Verify documents that aren't svg are rejected.
+    #[test]
+    fn invalid_documents() {
+        let err = import_svg("<html></html>").err().unwrap();
+        assert_eq!(err, "Expected an <svg> root element, found <html>");
+
+        let err = import_svg("<svg>").err().unwrap();
+        assert!(err.starts_with("Unable to parse svg document: "));
+    }
+}
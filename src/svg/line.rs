@@ -0,0 +1,145 @@
+use serde::{Serialize, Deserialize};
+
+use crate::utils;
+use super::{Point, SVG, Dimensions, Style, Transform, html_element, move_points, resize_points, Outline, offset_points, polygon_outline};
+
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Line {
+    uuid: String,
+    points: [Point; 2],
+    #[serde(default)]
+    style: Style,
+    #[serde(default)]
+    transform: Transform,
+}
+
+impl Line {
+    ///
+    pub fn new(x1: f64, y1: f64, x2: f64, y2: f64) -> Line {
+        Line {
+            uuid: utils::gen_uuid(),
+            points: [Point { x: x1, y: y1 }, Point { x: x2, y: y2 }],
+            style: Style::new(),
+            transform: Transform::identity(),
+        }
+    }
+
+    ///
+    pub fn box_ptr(self) -> Box<Self> {
+        Box::new(self)
+    }
+}
+
+impl SVG for Line {
+    ///
+    fn get_uuid(&self) -> String {
+        self.uuid.clone()
+    }
+
+    ///
+    fn clone_box(&self) -> Box<dyn SVG> {
+        Box::new(self.clone())
+    }
+
+    ///
+    fn duplicate(&self) -> Box<dyn SVG> {
+        Box::new(Line { uuid: utils::gen_uuid(), ..self.clone() })
+    }
+
+    ///
+    fn get_style(&self) -> &Style {
+        &self.style
+    }
+
+    ///
+    fn get_style_mut(&mut self) -> &mut Style {
+        &mut self.style
+    }
+
+    ///
+    fn get_transform(&self) -> &Transform {
+        &self.transform
+    }
+
+    ///
+    fn get_transform_mut(&mut self) -> &mut Transform {
+        &mut self.transform
+    }
+
+    ///
+    fn to_html(&self) -> String {
+        let [start, end] = &self.points;
+
+        let mut attrs = vec![
+            ("x1", start.x.to_string()),
+            ("y1", start.y.to_string()),
+            ("x2", end.x.to_string()),
+            ("y2", end.y.to_string()),
+        ];
+        attrs.extend(self.transform.to_attrs());
+        attrs.extend(self.style.to_attrs());
+
+        html_element("line", attrs)
+    }
+
+    /// Moves the start of the line, keeping the end relative to it.
+    fn move_to(&mut self, x: f64, y: f64) {
+        move_points(&mut self.points, x, y);
+    }
+
+    ///
+    fn move_by(&mut self, dx: f64, dy: f64) {
+        offset_points(self.points.iter_mut(), dx, dy);
+    }
+
+    ///
+    fn outlines_in(&self, outer: &Transform) -> Vec<Outline> {
+        vec![polygon_outline(&self.points, false).transform(&self.transform.then(outer))]
+    }
+
+    ///
+    fn get_points(&self) -> Vec<Point> {
+        self.points.to_vec()
+    }
+
+    /// Repositions the start (index 0) or end (index 1) of the line.
+    fn resize(&mut self, dim: Dimensions) -> Result<(), String> {
+        resize_points(&mut self.points, dim, "Line")
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verify the `to_html` function outputs an svg string reflecting the current state of the line.
+    #[test]
+    fn to_html() {
+        let mut line = Line::new(0.0, 1.5, 4.0, -2.0);
+        assert_eq!(line.to_html(), "<line x1=\"0\" y1=\"1.5\" x2=\"4\" y2=\"-2\"></line>");
+
+        line.move_to(1.0, 1.0);
+        assert_eq!(line.to_html(), "<line x1=\"1\" y1=\"1\" x2=\"5\" y2=\"-2.5\"></line>");
+
+        line.resize(Dimensions::IndexPosition(1, 3.0, 0.5)).unwrap();
+        assert_eq!(line.to_html(), "<line x1=\"1\" y1=\"1\" x2=\"3\" y2=\"0.5\"></line>");
+    }
+
+    /// Verify the `resize` function moves one end of the line, and rejects invalid indices and dimensions.
+    #[test]
+    fn resize() {
+        let mut line = Line::new(0.0, 0.0, 1.0, 1.0);
+
+        line.resize(Dimensions::IndexPosition(0, -1.0, 2.0)).unwrap();
+        assert_eq!(line.points, [Point{ x: -1.0, y: 2.0 }, Point{ x: 1.0, y: 1.0 }]);
+
+        let err = line.resize(Dimensions::IndexPosition(2, 1.0, 2.0)).unwrap_err();
+        assert_eq!(err, "Cannot resize Line because point does not exist at index 2");
+
+        let err = line.resize(Dimensions::Double(1.0, 2.0)).unwrap_err();
+        assert_eq!(err, "Cannot resize Line with dimensions Double(1.0, 2.0)");
+        assert_eq!(line.points, [Point{ x: -1.0, y: 2.0 }, Point{ x: 1.0, y: 1.0 }]);
+    }
+}
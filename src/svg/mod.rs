@@ -1,19 +1,52 @@
+use std::any::Any;
 use std::fmt;
 
+use serde::{Serialize, Deserialize};
+
+use bounding_box::union_all;
+use curve::{distance_to_outlines, encloses};
+
+mod bounding_box;
 mod circle;
+mod curve;
+mod ellipse;
+mod export;
+mod line;
 mod path;
+mod path_data;
+mod polygon;
+mod polyline;
 mod rect;
+mod text;
+mod transform;
 mod group;
 mod grid;
+mod history;
+mod import;
+mod serialize;
+mod spatial_index;
+mod style;
 
+pub use bounding_box::BoundingBox;
 pub use circle::Circle;
-pub use path::Path;
+pub use curve::{Curve, EllipticalArc, Outline};
+pub use ellipse::Ellipse;
+pub use export::SvgOptions;
+pub use line::Line;
+pub use path::{Path, Segment};
+pub use path_data::{parse_path_data, PathDataError};
+pub use polygon::Polygon;
+pub use polyline::Polyline;
 pub use rect::Rect;
+pub use text::Text;
+pub use transform::Transform;
 pub use group::Group;
 pub use grid::{Grid, CoordinateSystem};
+pub use history::{History, Operation};
+pub use style::Style;
 
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct Point {
     pub x: f64,
     pub y: f64,
@@ -27,14 +60,14 @@ fn approx_eq(a: f64, b: f64) -> bool {
     let a_abs = a.abs();
     let b_abs = b.abs();
 
-    if a == 0.0 || b == 0.0 || (a_abs + b_abs < std::f64::MIN_POSITIVE) {
-        return (a - b).abs() < std::f64::EPSILON * std::f64::MIN_POSITIVE;
+    if a == 0.0 || b == 0.0 || (a_abs + b_abs < f64::MIN_POSITIVE) {
+        return (a - b).abs() < f64::EPSILON * f64::MIN_POSITIVE;
     }
 
     let sum = a_abs + b_abs;
-    let min = if sum < std::f64::MAX { sum } else { std::f64::MAX };
+    let min = if sum < f64::MAX { sum } else { f64::MAX };
 
-    (a - b).abs() / min < std::f64::EPSILON
+    (a - b).abs() / min < f64::EPSILON
 }
 
 impl PartialEq for Point {
@@ -49,6 +82,7 @@ pub enum Dimensions {
     Single(f64),
     Double(f64, f64),
     IndexPosition(usize, f64, f64),
+    ControlPosition(usize, usize, f64, f64),
 }
 
 impl fmt::Display for Dimensions {
@@ -58,19 +92,227 @@ impl fmt::Display for Dimensions {
 }
 
 
-pub trait SVG {
+/// Implementors are `Any`, so a `dyn SVG` can be downcast to its concrete shape for shape specific editing.
+pub trait SVG: Any {
     fn get_uuid(&self) -> String;
+    /// An exact copy of the shape, uuids included.
+    fn clone_box(&self) -> Box<dyn SVG>;
+    /// A copy of the shape with new uuids, for it and anything it contains.
+    fn duplicate(&self) -> Box<dyn SVG>;
     fn to_html(&self) -> String;
 
+    fn get_style(&self) -> &Style;
+    fn get_style_mut(&mut self) -> &mut Style;
+
+    ///
+    fn set_style(&mut self, style: Style) {
+        *self.get_style_mut() = style;
+    }
+
+    fn get_transform(&self) -> &Transform;
+    fn get_transform_mut(&mut self) -> &mut Transform;
+
+    ///
+    fn set_transform(&mut self, transform: Transform) {
+        *self.get_transform_mut() = transform;
+    }
+
+    /// Apply `transform` on top of any transform the shape already has.
+    fn apply_transform(&mut self, transform: Transform) {
+        let current = *self.get_transform();
+        *self.get_transform_mut() = current.then(&transform);
+    }
+
+    ///
+    fn translate(&mut self, dx: f64, dy: f64) {
+        self.apply_transform(Transform::translate(dx, dy));
+    }
+
+    /// Rotate by `degrees` around the point (cx, cy).
+    fn rotate(&mut self, degrees: f64, cx: f64, cy: f64) {
+        self.apply_transform(Transform::rotate_about(degrees, cx, cy));
+    }
+
+    ///
+    fn scale(&mut self, sx: f64, sy: f64) {
+        self.apply_transform(Transform::scale(sx, sy));
+    }
+
+    ///
+    fn skew(&mut self, x_degrees: f64, y_degrees: f64) {
+        self.apply_transform(Transform::skew(x_degrees, y_degrees));
+    }
+
     fn move_to(&mut self, x: f64, y: f64);
     fn resize(&mut self, dims: Dimensions) -> Result<(), String>;
+
+    /// Offset the shape by (dx, dy), in the same coordinates as `move_to`.
+    fn move_by(&mut self, dx: f64, dy: f64);
+
+    /// The points `Dimensions::IndexPosition` repositions, in index order. Shapes resized some other way have none.
+    fn get_points(&self) -> Vec<Point> {
+        vec![]
+    }
+
+    /// The outline of the shape after its own transform followed by `outer`, with one entry per subpath.
+    fn outlines_in(&self, outer: &Transform) -> Vec<Outline>;
+
+    /// The smallest box containing the shape in its parent's coordinates, after the shape's transform.
+    /// Shapes with no points have no bounding box.
+    fn bounding_box(&self) -> Option<BoundingBox> {
+        self.bounding_box_in(&Transform::identity())
+    }
+
+    /// The smallest box containing the shape after its own transform followed by `outer`.
+    /// Transforming the geometry rather than an already computed box keeps the result exact under rotation and skew.
+    fn bounding_box_in(&self, outer: &Transform) -> Option<BoundingBox> {
+        union_all(self.outlines_in(outer).iter().map(|outline| Some(outline.bounding_box())))
+    }
+
+    /// The box covered by the painted shape in its parent's coordinates, including its stroke.
+    fn painted_bounding_box(&self) -> Option<BoundingBox> {
+        self.painted_bounding_box_in(&Transform::identity(), &Style::new())
+    }
+
+    /// The box covered by the painted shape after its own transform followed by `outer`,
+    /// which reaches half the stroke width beyond the outline. `inherited` is the style resolved for the shape's
+    /// parent, which fills in whatever the shape's own style leaves unset.
+    fn painted_bounding_box_in(&self, outer: &Transform, inherited: &Style) -> Option<BoundingBox> {
+        let style = self.get_style().inherit(inherited);
+        self.bounding_box_in(outer).map(|bounds| bounds.expand(stroke_allowance(self, outer, &style)))
+    }
+
+    /// The distance from `point` to the painted part of the shape, after its own transform followed by `outer`,
+    /// painted with its style on top of `inherited`. Points inside the fill are at distance zero, and the stroke
+    /// covers half its width either side of the outline.
+    fn distance_in(&self, point: Point, outer: &Transform, inherited: &Style) -> f64 {
+        let outlines = self.outlines_in(outer);
+        let style = self.get_style().inherit(inherited);
+
+        if style.is_filled() && encloses(&outlines, point, style.fill_rule.as_deref() == Some("evenodd")) {
+            return 0.0;
+        }
+
+        (distance_to_outlines(&outlines, point) - stroke_allowance(self, outer, &style)).max(0.0)
+    }
+
+    /// The distance from `point`, in the parent's coordinates, to the painted part of the shape. Empty shapes are infinitely far away.
+    fn distance_to(&self, point: Point) -> f64 {
+        self.distance_in(point, &Transform::identity(), &Style::new())
+    }
+
+    /// Whether `point`, in the parent's coordinates, is within `tolerance` of the painted part of the shape.
+    /// Outlines can always be hit within the tolerance, even when they aren't stroked.
+    fn contains_point(&self, point: Point, tolerance: f64) -> bool {
+        self.distance_to(point) <= tolerance
+    }
 }
 
-fn html_element(tag: &str, attrs: Vec<(&str, String)>) -> String {
-    let attr_str = attrs.iter()
-        .map(|(k, v)| format!("{}=\"{}\"", k, v))
+/// How far the shape's stroke reaches beyond its outline when painted with `style`, after its own transform
+/// followed by `outer`.
+fn stroke_allowance<S: SVG + ?Sized>(shape: &S, outer: &Transform, style: &Style) -> f64 {
+    if style.is_stroked() {
+        style.stroke_width.unwrap_or(1.0) * shape.get_transform().then(outer).scale_factor() / 2.0
+    } else {
+        0.0
+    }
+}
+
+/// Escape the characters that are not allowed to appear literally in xml text or attribute values.
+fn escape_xml(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+fn html_attrs(attrs: &[(&str, String)]) -> String {
+    attrs.iter()
+        .map(|(k, v)| format!("{}=\"{}\"", k, escape_xml(v)))
         .collect::<Vec<String>>()
-        .join(" ");
+        .join(" ")
+}
+
+fn html_element(tag: &str, attrs: Vec<(&str, String)>) -> String {
+    html_element_with_content(tag, attrs, "")
+}
+
+/// Like `html_element`, but with escaped text between the opening and closing tags.
+fn html_element_with_content(tag: &str, attrs: Vec<(&str, String)>, content: &str) -> String {
+    format!("<{0} {1}>{2}</{0}>", tag, html_attrs(&attrs), escape_xml(content))
+}
+
+
+/// Translate a list of points so the first point lands on (x, y), keeping the others relative to it.
+fn move_points(points: &mut [Point], x: f64, y: f64) {
+    if let Some(origin) = points.first() {
+        let dx = x - origin.x;
+        let dy = y - origin.y;
 
-    format!("<{0} {1}></{0}>", tag, attr_str)
+        offset_points(points, dx, dy);
+    }
+}
+
+///
+fn offset_points<'a, I: IntoIterator<Item = &'a mut Point>>(points: I, dx: f64, dy: f64) {
+    for point in points {
+        point.x += dx;
+        point.y += dy;
+    }
+}
+
+/// Offset a child by (dx, dy) in its parent's coordinates, converting the offset into the child's own coordinates.
+fn move_child_by(shape: &mut dyn SVG, dx: f64, dy: f64) {
+    let (dx, dy) = match shape.get_transform().inverse() {
+        Some(inverse) => inverse.apply_vector(dx, dy),
+        None => (dx, dy),
+    };
+
+    shape.move_by(dx, dy);
+}
+
+/// An outline joining `points` with straight lines, and back to the first point if `closed`.
+fn polygon_outline(points: &[Point], closed: bool) -> Outline {
+    let mut curves = points.windows(2).map(|pair| Curve::Line(pair[0], pair[1])).collect::<Vec<Curve>>();
+
+    if let (true, Some(&first), Some(&last)) = (closed, points.first(), points.last()) {
+        if first != last {
+            curves.push(Curve::Line(last, first));
+        }
+    }
+
+    Outline { start: points[0], curves }
+}
+
+/// Reposition a single point in a list of points, as described by `Dimensions::IndexPosition`.
+fn resize_points(points: &mut [Point], dim: Dimensions, shape_name: &str) -> Result<(), String> {
+    if let Dimensions::IndexPosition(i, x, y) = dim {
+        if let Some(point) = points.get_mut(i) {
+            point.x = x;
+            point.y = y;
+            Ok(())
+        } else {
+            Err(format!("Cannot resize {} because point does not exist at index {}", shape_name, i))
+        }
+    } else {
+        Err(format!("Cannot resize {} with dimensions {}", shape_name, dim))
+    }
+}
+
+/// Format a list of points for the `points` attribute of polylines and polygons.
+fn points_attr(points: &[Point]) -> String {
+    points.iter()
+        .map(|point| format!("{},{}", point.x, point.y))
+        .collect::<Vec<String>>()
+        .join(" ")
 }
@@ -1,12 +1,69 @@
+use serde::{Serialize, Deserialize};
+
 use crate::utils;
-use super::{Point, SVG, Dimensions, html_element};
+use super::{Point, SVG, Dimensions, Style, Transform, html_element, offset_points, resize_points};
+use super::path_data::{parse_path_data, PathDataError};
+use super::curve::{Curve, EllipticalArc, Outline};
+
 
+/// Describes how a path reaches a point from the point before it.
+/// Control points are absolute, and the first point of every subpath is always reached with a move.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Segment {
+    Line,
+    Cubic(Point, Point),
+    SmoothCubic(Point),
+    Quadratic(Point),
+    SmoothQuadratic,
+    Arc { rx: f64, ry: f64, rotation: f64, large_arc: bool, sweep: bool },
+}
 
+impl Segment {
+    ///
+    fn control_points_mut(&mut self) -> Vec<&mut Point> {
+        match self {
+            Segment::Cubic(c1, c2) => vec![c1, c2],
+            Segment::SmoothCubic(c) | Segment::Quadratic(c) => vec![c],
+            Segment::Line | Segment::SmoothQuadratic | Segment::Arc { .. } => vec![],
+        }
+    }
+
+    /// The path data command that draws this segment to `point`.
+    fn to_command(&self, point: &Point) -> String {
+        match self {
+            Segment::Line => format!("L {} {}", point.x, point.y),
+            Segment::Cubic(c1, c2) => format!("C {} {} {} {} {} {}", c1.x, c1.y, c2.x, c2.y, point.x, point.y),
+            Segment::SmoothCubic(c2) => format!("S {} {} {} {}", c2.x, c2.y, point.x, point.y),
+            Segment::Quadratic(c) => format!("Q {} {} {} {}", c.x, c.y, point.x, point.y),
+            Segment::SmoothQuadratic => format!("T {} {}", point.x, point.y),
+            Segment::Arc { rx, ry, rotation, large_arc, sweep } => format!(
+                "A {} {} {} {} {} {} {}",
+                rx, ry, rotation, *large_arc as u8, *sweep as u8, point.x, point.y
+            ),
+        }
+    }
+}
+
+
+/// The reflection of `control` through `point`, used as the implied control point of smooth segments.
+fn reflect(control: Point, point: Point) -> Point {
+    Point { x: 2.0 * point.x - control.x, y: 2.0 * point.y - control.y }
+}
+
+
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Path {
     pub uuid: String,
     points: Vec<Point>,
+    segments: Vec<Segment>,
     starting_points: Vec<usize>,
+    closed: Vec<bool>,
+    #[serde(skip)]
     active_point: Option<usize>,
+    #[serde(default)]
+    style: Style,
+    #[serde(default)]
+    transform: Transform,
 }
 
 
@@ -16,38 +73,202 @@ impl Path {
         Path {
             uuid: utils::gen_uuid(),
             points: vec![],
+            segments: vec![],
             starting_points: vec![],
+            closed: vec![],
             active_point: None,
+            style: Style::new(),
+            transform: Transform::identity(),
         }
     }
 
     ///
     pub fn from_points(points: Vec<Point>) -> Result<Path, &'static str> {
-        if let Some(_) = points.first() {
+        if !points.is_empty() {
             return Ok(Path {
                 uuid: utils::gen_uuid(),
+                segments: vec![Segment::Line; points.len()],
                 points,
                 starting_points: vec![0],
+                closed: vec![false],
                 active_point: None,
+                style: Style::new(),
+                transform: Transform::identity(),
             });
         }
 
         Err("Expected at least one point to create a Path")
     }
 
-    ///
-    pub fn add_nested_path(&mut self, points: Vec<Point>) -> &Path {
+    /// Start a new subpath. A closed subpath is joined back to its first point.
+    pub fn add_nested_path(&mut self, points: Vec<Point>, closed: bool) -> &Path {
         let starting_point = self.points.len();
+        self.segments.extend(vec![Segment::Line; points.len()]);
         self.points.extend(points);
         self.starting_points.push(starting_point);
+        self.closed.push(closed);
         self
     }
 
+    ///
+    pub fn subpath_count(&self) -> usize {
+        self.starting_points.len()
+    }
+
+    ///
+    pub fn is_closed(&self, subpath: usize) -> Option<bool> {
+        self.closed.get(subpath).copied()
+    }
+
+    /// Close or open the subpath at index `subpath`.
+    pub fn set_closed(&mut self, subpath: usize, closed: bool) -> Result<(), String> {
+        match self.closed.get_mut(subpath) {
+            Some(state) => {
+                *state = closed;
+                Ok(())
+            },
+            None => Err(format!("Subpath {} does not exist, the Path has {} subpaths", subpath, self.closed.len())),
+        }
+    }
+
+    /// Extend the last subpath to `point` using the given segment, returning the index of the new point.
+    pub fn add_segment(&mut self, segment: Segment, point: Point) -> Result<usize, &'static str> {
+        if self.points.is_empty() {
+            return Err("Expected a starting point before adding a segment to a Path");
+        }
+
+        self.points.push(point);
+        self.segments.push(segment);
+        Ok(self.points.len() - 1)
+    }
+
     ///
     pub fn get_point(&mut self, i: usize) -> Option<&mut Point> {
         self.points.get_mut(i)
     }
 
+    /// The segment used to reach the point at index `i`.
+    pub fn get_segment(&self, i: usize) -> Option<&Segment> {
+        self.segments.get(i)
+    }
+
+    /// Parse the contents of an svg `d` attribute into a new path.
+    pub fn from_path_data(data: &str) -> Result<Path, PathDataError> {
+        parse_path_data(data)
+    }
+
+    /// The contents of the `d` attribute describing this path.
+    pub fn to_path_data(&self) -> String {
+        let mut commands = vec![];
+
+        for (subpath, &start) in self.starting_points.iter().enumerate() {
+            let end = self.starting_points.get(subpath + 1).copied().unwrap_or(self.points.len());
+
+            for i in start..end {
+                if i == start {
+                    commands.push(format!("M {} {}", self.points[i].x, self.points[i].y));
+                } else {
+                    commands.push(self.segments[i].to_command(&self.points[i]));
+                }
+            }
+
+            if self.closed[subpath] && start < end {
+                commands.push(String::from("Z"));
+            }
+        }
+
+        commands.join(" ")
+    }
+
+    /// Resolve every subpath into absolute curves, filling in the control points implied by smooth segments,
+    /// converting arcs to their center parameterization and adding the lines that close subpaths.
+    fn local_outlines(&self) -> Vec<Outline> {
+        let mut outlines = vec![];
+
+        for (subpath, &start) in self.starting_points.iter().enumerate() {
+            let end = self.starting_points.get(subpath + 1).copied().unwrap_or(self.points.len());
+            if start == end {
+                continue;
+            }
+
+            let mut curves = vec![];
+            // The last control point of the previous segment, when it was a curve of the same kind
+            let mut cubic_control = None;
+            let mut quadratic_control = None;
+
+            for i in start + 1..end {
+                let (from, to) = (self.points[i - 1], self.points[i]);
+
+                let curve = match &self.segments[i] {
+                    Segment::Line => Some(Curve::Line(from, to)),
+                    Segment::Cubic(c1, c2) => Some(Curve::Cubic(from, *c1, *c2, to)),
+                    Segment::SmoothCubic(c2) => {
+                        let c1 = cubic_control.map_or(from, |control| reflect(control, from));
+                        Some(Curve::Cubic(from, c1, *c2, to))
+                    },
+                    Segment::Quadratic(c) => Some(Curve::Quadratic(from, *c, to)),
+                    Segment::SmoothQuadratic => {
+                        let c = quadratic_control.map_or(from, |control| reflect(control, from));
+                        Some(Curve::Quadratic(from, c, to))
+                    },
+                    Segment::Arc { rx, ry, rotation, large_arc, sweep } => {
+                        match EllipticalArc::from_endpoints(from, to, *rx, *ry, *rotation, *large_arc, *sweep) {
+                            Some(arc) => Some(Curve::Arc(arc)),
+                            // Arcs to the same point are skipped, and arcs without a radius are drawn as lines
+                            None if from == to => None,
+                            None => Some(Curve::Line(from, to)),
+                        }
+                    },
+                };
+
+                cubic_control = match curve {
+                    Some(Curve::Cubic(_, _, c2, _)) => Some(c2),
+                    _ => None,
+                };
+                quadratic_control = match curve {
+                    Some(Curve::Quadratic(_, c, _)) => Some(c),
+                    _ => None,
+                };
+                curves.extend(curve);
+            }
+
+            let (first, last) = (self.points[start], self.points[end - 1]);
+            if self.closed[subpath] && first != last {
+                curves.push(Curve::Line(last, first));
+            }
+
+            outlines.push(Outline { start: first, curves });
+        }
+
+        outlines
+    }
+
+    /// Check the point, segment and subpath lists agree with each other, as they always do unless a path was loaded
+    /// from a corrupt drawing.
+    pub(super) fn validate(&self) -> Result<(), String> {
+        if self.segments.len() != self.points.len() {
+            return Err(format!("Path {} has mismatched points and segments ({} and {})", self.uuid, self.points.len(), self.segments.len()));
+        }
+        if self.closed.len() != self.starting_points.len() {
+            return Err(format!(
+                "Path {} has mismatched subpaths and closed flags ({} and {})",
+                self.uuid, self.starting_points.len(), self.closed.len(),
+            ));
+        }
+
+        // Points before the first subpath starts belong to no subpath, so they would never be drawn
+        if !self.points.is_empty() && self.starting_points.first() != Some(&0) {
+            return Err(format!("Path {} has points before its first subpath starts", self.uuid));
+        }
+
+        let in_order = self.starting_points.windows(2).all(|pair| pair[0] <= pair[1]);
+        if !in_order || self.starting_points.last().is_some_and(|&start| start > self.points.len()) {
+            return Err(format!("Path {} has subpaths starting out of order or past its {} points", self.uuid, self.points.len()));
+        }
+
+        Ok(())
+    }
+
     ///
     pub fn select_point(&mut self, i: usize) {
         self.active_point = Some(i);
@@ -59,6 +280,12 @@ impl Path {
     }
 }
 
+impl Default for Path {
+    fn default() -> Self {
+        Path::new()
+    }
+}
+
 impl SVG for Path {
     ///
     fn get_uuid(&self) -> String {
@@ -66,44 +293,89 @@ impl SVG for Path {
     }
 
     ///
-    fn to_html(&self) -> String {
-        let path = self.points.iter()
-            .enumerate()
-            .map(|(i, point)| {
-                let p_type = if self.starting_points.contains(&i) { "M" } else { "L" };
-                format!("{} {} {}", p_type, point.x, point.y)
-            })
-            .collect::<Vec<String>>()
-            .join(" ");
+    fn clone_box(&self) -> Box<dyn SVG> {
+        Box::new(self.clone())
+    }
 
-        html_element("path", vec![("d", path)])
+    ///
+    fn duplicate(&self) -> Box<dyn SVG> {
+        Box::new(Path { uuid: utils::gen_uuid(), ..self.clone() })
+    }
+
+    ///
+    fn get_style(&self) -> &Style {
+        &self.style
+    }
+
+    ///
+    fn get_style_mut(&mut self) -> &mut Style {
+        &mut self.style
+    }
+
+    ///
+    fn get_transform(&self) -> &Transform {
+        &self.transform
+    }
+
+    ///
+    fn get_transform_mut(&mut self) -> &mut Transform {
+        &mut self.transform
     }
 
     ///
+    fn to_html(&self) -> String {
+        let mut attrs = vec![("d", self.to_path_data())];
+        attrs.extend(self.transform.to_attrs());
+        attrs.extend(self.style.to_attrs());
+
+        html_element("path", attrs)
+    }
+
+
+    /// Moves the first point of the path, keeping every other point and control point relative to it.
     fn move_to(&mut self, x: f64, y: f64) {
-        let origin = self.points.first_mut().unwrap();
+        if let Some(origin) = self.points.first() {
+            self.move_by(x - origin.x, y - origin.y);
+        }
+    }
 
-        let dx = x - origin.x;
-        let dy = y - origin.y;
+    ///
+    fn move_by(&mut self, dx: f64, dy: f64) {
+        offset_points(self.points.iter_mut(), dx, dy);
 
-        for point in &mut self.points {
-            point.x += dx;
-            point.y += dy;
+        for segment in &mut self.segments {
+            offset_points(segment.control_points_mut(), dx, dy);
         }
     }
 
     ///
+    fn outlines_in(&self, outer: &Transform) -> Vec<Outline> {
+        let transform = self.transform.then(outer);
+        self.local_outlines().iter().map(|outline| outline.transform(&transform)).collect()
+    }
+
+    ///
+    fn get_points(&self) -> Vec<Point> {
+        self.points.clone()
+    }
+
+    /// Repositions either a point (`IndexPosition`) or one of the control points of the segment ending at a point (`ControlPosition`).
     fn resize(&mut self, dim: Dimensions) -> Result<(), String> {
-        if let Dimensions::IndexPosition(i, x, y) = dim {
-            if let Some(mut point) = self.get_point(i) {
-                point.x = x;
-                point.y = y;
+        if let Dimensions::ControlPosition(i, j, x, y) = dim {
+            let segment = match self.segments.get_mut(i) {
+                Some(segment) => segment,
+                None => return Err(format!("Cannot resize Path because point does not exist at index {}", i)),
+            };
+
+            if let Some(control) = segment.control_points_mut().into_iter().nth(j) {
+                control.x = x;
+                control.y = y;
                 Ok(())
             } else {
-                Err(format!("Cannot resize Path because point does not exist at index {}", i))
+                Err(format!("Cannot resize Path because point {} has no control point at index {}", i, j))
             }
         } else {
-            Err(format!("Cannot resize Path with dimensions {}", dim))
+            resize_points(&mut self.points, dim, "Path")
         }
     }
 }
@@ -113,6 +385,8 @@ impl SVG for Path {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use super::super::BoundingBox;
+    use super::super::bounding_box::assert_close;
 
     /// Verify the `to_html` function outputs an svg string reflecting the current state of the path.
     #[test]
@@ -132,11 +406,56 @@ mod tests {
             Point{ x: 4.1, y: 2.3},
             Point{ x: 3.0, y: 6.1},
             Point{ x: 7.4, y: -3.0},
-        ]);
+        ], false);
         assert_eq!(path.to_html(),"<path d=\"M 1 4.5 L 3.5 -0.5 L -1.5 -0.5 L 1 4.5 M 4.1 2.3 L 3 6.1 L 7.4 -3\"></path>");
 
         path.resize(Dimensions::IndexPosition(4, 4.2, 1.7)).unwrap();
         assert_eq!(path.to_html(),"<path d=\"M 1 4.5 L 3.5 -0.5 L -1.5 -0.5 L 1 4.5 M 4.2 1.7 L 3 6.1 L 7.4 -3\"></path>");
+
+        path.get_style_mut().set_fill("none");
+        path.get_style_mut().set_stroke("black");
+        assert_eq!(path.to_html(),"<path d=\"M 1 4.5 L 3.5 -0.5 L -1.5 -0.5 L 1 4.5 M 4.2 1.7 L 3 6.1 L 7.4 -3\" fill=\"none\" stroke=\"black\"></path>");
+    }
+
+    /// Verify every segment kind is written with its control points, and that anchors and control points can be moved.
+    #[test]
+    fn segments() {
+        let mut path = Path::from_points(vec![Point{ x: 0.0, y: 0.0 }]).unwrap();
+        path.add_segment(Segment::Cubic(Point{ x: 1.0, y: 2.0 }, Point{ x: 3.0, y: 2.0 }), Point{ x: 4.0, y: 0.0 }).unwrap();
+        path.add_segment(Segment::SmoothCubic(Point{ x: 7.0, y: -2.0 }), Point{ x: 8.0, y: 0.0 }).unwrap();
+        path.add_segment(Segment::Quadratic(Point{ x: 9.0, y: 1.5 }), Point{ x: 10.0, y: 0.0 }).unwrap();
+        path.add_segment(Segment::SmoothQuadratic, Point{ x: 12.0, y: 0.0 }).unwrap();
+        path.add_segment(Segment::Arc { rx: 2.0, ry: 1.0, rotation: 30.0, large_arc: true, sweep: false }, Point{ x: 12.0, y: 4.0 }).unwrap();
+        path.add_segment(Segment::Line, Point{ x: 0.0, y: 4.0 }).unwrap();
+        assert_eq!(path.to_html(), "<path d=\"M 0 0 C 1 2 3 2 4 0 S 7 -2 8 0 Q 9 1.5 10 0 T 12 0 A 2 1 30 1 0 12 4 L 0 4\"></path>");
+
+        // Verify moving the path moves control points along with anchors, but leaves arc radii alone
+        path.move_to(1.0, -1.0);
+        assert_eq!(path.to_html(), "<path d=\"M 1 -1 C 2 1 4 1 5 -1 S 8 -3 9 -1 Q 10 0.5 11 -1 T 13 -1 A 2 1 30 1 0 13 3 L 1 3\"></path>");
+
+        // Verify anchors and control points can be repositioned independently
+        path.resize(Dimensions::IndexPosition(1, 5.0, 0.0)).unwrap();
+        path.resize(Dimensions::ControlPosition(1, 0, 1.0, 3.0)).unwrap();
+        path.resize(Dimensions::ControlPosition(1, 1, 4.0, 3.0)).unwrap();
+        path.resize(Dimensions::ControlPosition(2, 0, 8.0, -4.0)).unwrap();
+        path.resize(Dimensions::ControlPosition(3, 0, 10.0, 2.0)).unwrap();
+        path.resize(Dimensions::IndexPosition(5, 13.0, 5.0)).unwrap();
+        assert_eq!(path.to_html(), "<path d=\"M 1 -1 C 1 3 4 3 5 0 S 8 -4 9 -1 Q 10 2 11 -1 T 13 -1 A 2 1 30 1 0 13 5 L 1 3\"></path>");
+        assert_eq!(path.get_segment(1), Some(&Segment::Cubic(Point{ x: 1.0, y: 3.0 }, Point{ x: 4.0, y: 3.0 })));
+
+        // Verify segments without the requested control point are rejected
+        let err = path.resize(Dimensions::ControlPosition(1, 2, 0.0, 0.0)).unwrap_err();
+        assert_eq!(err, "Cannot resize Path because point 1 has no control point at index 2");
+        let err = path.resize(Dimensions::ControlPosition(4, 0, 0.0, 0.0)).unwrap_err();
+        assert_eq!(err, "Cannot resize Path because point 4 has no control point at index 0");
+        let err = path.resize(Dimensions::ControlPosition(5, 0, 0.0, 0.0)).unwrap_err();
+        assert_eq!(err, "Cannot resize Path because point 5 has no control point at index 0");
+        let err = path.resize(Dimensions::ControlPosition(7, 0, 0.0, 0.0)).unwrap_err();
+        assert_eq!(err, "Cannot resize Path because point does not exist at index 7");
+
+        // Verify a segment cannot be added before the path has a starting point
+        let err = Path::new().add_segment(Segment::Line, Point{ x: 1.0, y: 1.0 }).unwrap_err();
+        assert_eq!(err, "Expected a starting point before adding a segment to a Path");
     }
 
     /// Verify the `add_nested_path` function adds points to the existing list, and records the starting point indices.
@@ -152,7 +471,7 @@ mod tests {
             Point{ x: 3.0, y: 3.0 },
             Point{ x: 4.0, y: 5.0 },
             Point{ x: 5.0, y: 4.0 },
-        ]);
+        ], true);
         assert_eq!(path.points, vec![
             Point{ x: 1.0, y: 2.0 },
             Point{ x: 2.0, y: 1.0 },
@@ -161,6 +480,34 @@ mod tests {
             Point{ x: 5.0, y: 4.0 },
         ]);
         assert_eq!(path.starting_points, vec![0, 2]);
+        assert_eq!(path.closed, vec![false, true]);
+    }
+
+    /// Verify closed subpaths end with a `Z` command, and subpaths can be closed and opened.
+    #[test]
+    fn closed_subpaths() {
+        let mut path = Path::from_points(vec![
+            Point{ x: 0.0, y: 5.0 },
+            Point{ x: 2.5, y: 0.0 },
+            Point{ x: -2.5, y: 0.0 },
+        ]).unwrap();
+        path.add_nested_path(vec![
+            Point{ x: 4.0, y: 4.0 },
+            Point{ x: 5.0, y: 4.0 },
+            Point{ x: 5.0, y: 5.0 },
+        ], true);
+        assert_eq!(path.subpath_count(), 2);
+        assert_eq!(path.to_html(), "<path d=\"M 0 5 L 2.5 0 L -2.5 0 M 4 4 L 5 4 L 5 5 Z\"></path>");
+
+        path.set_closed(0, true).unwrap();
+        path.set_closed(1, false).unwrap();
+        assert_eq!(path.is_closed(0), Some(true));
+        assert_eq!(path.is_closed(1), Some(false));
+        assert_eq!(path.to_html(), "<path d=\"M 0 5 L 2.5 0 L -2.5 0 Z M 4 4 L 5 4 L 5 5\"></path>");
+
+        let err = path.set_closed(2, true).unwrap_err();
+        assert_eq!(err, "Subpath 2 does not exist, the Path has 2 subpaths");
+        assert_eq!(path.is_closed(2), None);
     }
 
     /// Verify the `move_to` function changes the coordinates of all points in the path.
@@ -239,4 +586,53 @@ mod tests {
             Point{ x: 0.3, y: 1.7 },
         ]);
     }
-}
\ No newline at end of file
+
+    /// Verify the bounding box follows the curves, including implied smooth control points, arcs and transforms.
+    #[test]
+    fn bounding_box() {
+        let mut path = Path::from_path_data("M 0 0 C 0 10 10 10 10 0 S 20 -10 20 0").unwrap();
+        assert_close(path.bounding_box().unwrap(), BoundingBox::new(0.0, -7.5, 20.0, 15.0));
+
+        path.rotate(90.0, 0.0, 0.0);
+        assert_close(path.bounding_box().unwrap(), BoundingBox::new(-7.5, 0.0, 15.0, 20.0));
+
+        let path = Path::from_path_data("M 0 0 Q 5 10 10 0 T 20 0").unwrap();
+        assert_close(path.bounding_box().unwrap(), BoundingBox::new(0.0, -5.0, 20.0, 10.0));
+
+        let path = Path::from_path_data("M 0 0 A 5 5 0 0 1 10 0 A 0 5 0 0 1 10 20").unwrap();
+        assert_close(path.bounding_box().unwrap(), BoundingBox::new(0.0, -5.0, 10.0, 25.0));
+
+        // Points that only start a subpath are still part of the box, and empty paths have none
+        let mut path = Path::from_path_data("M 0 0 L 1 1 M 5 -5").unwrap();
+        path.scale(2.0, 2.0);
+        assert_close(path.bounding_box().unwrap(), BoundingBox::new(0.0, -10.0, 10.0, 12.0));
+        assert_eq!(Path::new().bounding_box(), None);
+    }
+
+    /// Verify hit testing follows the fill rule, and counts the stroke width only when the path is stroked.
+    #[test]
+    fn contains_point() {
+        let mut path = Path::from_path_data("M 0 0 H 10 V 10 H 0 Z M 2 2 H 8 V 8 H 2 Z").unwrap();
+        let hole = Point{ x: 5.0, y: 5.0 };
+        let ring = Point{ x: 1.0, y: 5.0 };
+
+        assert!(path.contains_point(hole, 0.0));
+        path.get_style_mut().set_fill_rule("evenodd").unwrap();
+        assert!(!path.contains_point(hole, 0.0));
+        assert!(path.contains_point(ring, 0.0));
+        assert_eq!(path.distance_to(hole), 3.0);
+
+        // Unfilled paths can only be hit near their outline, which the stroke widens
+        path.get_style_mut().set_fill("none");
+        assert_eq!(path.distance_to(ring), 1.0);
+        assert!(!path.contains_point(ring, 0.5));
+        path.get_style_mut().set_stroke("black");
+        path.get_style_mut().set_stroke_width(2.0).unwrap();
+        assert_eq!(path.distance_to(ring), 0.0);
+
+        // The stroke scales with the path's transform
+        path.scale(2.0, 2.0);
+        assert_eq!(path.distance_to(Point{ x: -3.0, y: 10.0 }), 1.0);
+        assert_eq!(Path::new().distance_to(hole), f64::INFINITY);
+    }
+}
@@ -0,0 +1,390 @@
+use std::fmt;
+
+use super::{Point, Path, Segment};
+
+
+/// An error in svg path data, with the byte offset into the data where it was found.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PathDataError {
+    pub position: usize,
+    pub message: String,
+}
+
+impl fmt::Display for PathDataError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} at position {}", self.message, self.position)
+    }
+}
+
+
+/// Parse the contents of a path's `d` attribute.
+///
+/// Relative commands, `H` and `V` are converted to absolute segments, so `Path::to_path_data` may not reproduce the
+/// original string, but it always describes the same shape.
+pub fn parse_path_data(data: &str) -> Result<Path, PathDataError> {
+    Parser { data, pos: 0 }.parse()
+}
+
+
+/// Parse a list of numbers separated by whitespace or commas, as used by the `points` and `viewBox` attributes.
+pub(super) fn parse_number_list(data: &str) -> Result<Vec<f64>, PathDataError> {
+    let mut parser = Parser { data, pos: 0 };
+    let mut numbers = vec![];
+
+    parser.skip_wsp();
+
+    while parser.peek().is_some() {
+        numbers.push(parser.number()?);
+        parser.skip_comma_wsp();
+    }
+
+    Ok(numbers)
+}
+
+
+struct Parser<'a> {
+    data: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    ///
+    fn parse(&mut self) -> Result<Path, PathDataError> {
+        let mut path = Path::new();
+        let mut command: Option<char> = None;
+        let mut current = Point { x: 0.0, y: 0.0 };
+        let mut subpath_start = current;
+        let mut subpath_closed = false;
+
+        self.skip_wsp();
+
+        while let Some(c) = self.peek() {
+            let start = self.pos;
+
+            if c.is_ascii_alphabetic() {
+                self.pos += 1;
+                command = Some(c);
+            } else if !Parser::is_number_start(c) {
+                return Err(self.error_at(start, format!("Unexpected character '{}'", c)));
+            } else {
+                // Numbers without a command repeat the previous one. A repeated moveto becomes a lineto.
+                command = match command {
+                    Some('M') => Some('L'),
+                    Some('m') => Some('l'),
+                    Some('Z') | Some('z') | None => return Err(self.error_at(start, String::from("Expected a command"))),
+                    other => other,
+                };
+            }
+
+            let cmd = command.unwrap();
+            let relative = cmd.is_ascii_lowercase();
+            let offset = if relative { current } else { Point { x: 0.0, y: 0.0 } };
+
+            if path.subpath_count() == 0 && cmd != 'M' && cmd != 'm' {
+                return Err(self.error_at(start, String::from("Path data must begin with a moveto command")));
+            }
+
+            if cmd == 'M' || cmd == 'm' {
+                self.skip_wsp();
+                current = self.point(offset)?;
+                subpath_start = current;
+                subpath_closed = false;
+
+                if path.subpath_count() == 0 {
+                    path = Path::from_points(vec![current]).unwrap();
+                } else {
+                    path.add_nested_path(vec![current], false);
+                }
+            } else if cmd == 'Z' || cmd == 'z' {
+                path.set_closed(path.subpath_count() - 1, true).unwrap();
+                current = subpath_start;
+                subpath_closed = true;
+            } else {
+                self.skip_wsp();
+
+                let (segment, point) = match cmd.to_ascii_uppercase() {
+                    'L' => (Segment::Line, self.point(offset)?),
+                    'H' => (Segment::Line, Point { x: self.number()? + offset.x, y: current.y }),
+                    'V' => (Segment::Line, Point { x: current.x, y: self.number()? + offset.y }),
+                    'C' => {
+                        let c1 = self.point(offset)?;
+                        self.skip_comma_wsp();
+                        let c2 = self.point(offset)?;
+                        self.skip_comma_wsp();
+                        (Segment::Cubic(c1, c2), self.point(offset)?)
+                    },
+                    'S' => {
+                        let c2 = self.point(offset)?;
+                        self.skip_comma_wsp();
+                        (Segment::SmoothCubic(c2), self.point(offset)?)
+                    },
+                    'Q' => {
+                        let c = self.point(offset)?;
+                        self.skip_comma_wsp();
+                        (Segment::Quadratic(c), self.point(offset)?)
+                    },
+                    'T' => (Segment::SmoothQuadratic, self.point(offset)?),
+                    'A' => {
+                        let rx = self.number()?;
+                        self.skip_comma_wsp();
+                        let ry = self.number()?;
+                        self.skip_comma_wsp();
+                        let rotation = self.number()?;
+                        self.skip_comma_wsp();
+                        let large_arc = self.flag()?;
+                        self.skip_comma_wsp();
+                        let sweep = self.flag()?;
+                        self.skip_comma_wsp();
+                        (Segment::Arc { rx, ry, rotation, large_arc, sweep }, self.point(offset)?)
+                    },
+                    _ => return Err(self.error_at(start, format!("Unknown command '{}'", cmd))),
+                };
+
+                // Drawing after a closepath starts a new subpath at the start of the closed one
+                if subpath_closed {
+                    path.add_nested_path(vec![subpath_start], false);
+                    subpath_closed = false;
+                }
+
+                path.add_segment(segment, point).unwrap();
+                current = point;
+            }
+
+            self.skip_comma_wsp();
+        }
+
+        Ok(path)
+    }
+
+    ///
+    fn peek(&self) -> Option<char> {
+        self.data[self.pos..].chars().next()
+    }
+
+    ///
+    fn error_at(&self, position: usize, message: String) -> PathDataError {
+        PathDataError { position, message }
+    }
+
+    ///
+    fn is_number_start(c: char) -> bool {
+        c.is_ascii_digit() || c == '.' || c == '-' || c == '+'
+    }
+
+    ///
+    fn skip_wsp(&mut self) {
+        while let Some(c) = self.peek() {
+            if !c.is_ascii_whitespace() {
+                break;
+            }
+            self.pos += 1;
+        }
+    }
+
+    /// Skip whitespace with at most one comma.
+    fn skip_comma_wsp(&mut self) {
+        self.skip_wsp();
+
+        if let Some(',') = self.peek() {
+            self.pos += 1;
+            self.skip_wsp();
+        }
+    }
+
+    ///
+    fn skip_digits(&mut self) -> usize {
+        let start = self.pos;
+
+        while let Some(c) = self.peek() {
+            if !c.is_ascii_digit() {
+                break;
+            }
+            self.pos += 1;
+        }
+
+        self.pos - start
+    }
+
+    /// Read a number, stopping at the first character that can't continue it, so `1.5.5` is `1.5` followed by `.5`.
+    fn number(&mut self) -> Result<f64, PathDataError> {
+        let start = self.pos;
+
+        if let Some('-') | Some('+') = self.peek() {
+            self.pos += 1;
+        }
+
+        let mut digits = self.skip_digits();
+
+        if let Some('.') = self.peek() {
+            self.pos += 1;
+            digits += self.skip_digits();
+        }
+
+        if digits == 0 {
+            return Err(self.error_at(start, self.expected("a number", start)));
+        }
+
+        if let Some('e') | Some('E') = self.peek() {
+            self.pos += 1;
+
+            if let Some('-') | Some('+') = self.peek() {
+                self.pos += 1;
+            }
+
+            if self.skip_digits() == 0 {
+                return Err(self.error_at(self.pos, self.expected("an exponent", self.pos)));
+            }
+        }
+
+        match self.data[start..self.pos].parse::<f64>() {
+            // Literals too large for an f64 parse as infinity, which can't be written back out as path data
+            Ok(number) if number.is_finite() => Ok(number),
+            _ => Err(self.error_at(start, format!("Invalid number {}", &self.data[start..self.pos]))),
+        }
+    }
+
+    /// Read an arc flag, which is a single `0` or `1` and may be directly followed by the next argument.
+    fn flag(&mut self) -> Result<bool, PathDataError> {
+        match self.peek() {
+            Some('0') => {
+                self.pos += 1;
+                Ok(false)
+            },
+            Some('1') => {
+                self.pos += 1;
+                Ok(true)
+            },
+            _ => Err(self.error_at(self.pos, self.expected("an arc flag (0 or 1)", self.pos))),
+        }
+    }
+
+    /// Read an x and y coordinate pair, offset by `offset` for relative commands.
+    fn point(&mut self, offset: Point) -> Result<Point, PathDataError> {
+        let x = self.number()?;
+        self.skip_comma_wsp();
+        let y = self.number()?;
+
+        Ok(Point { x: x + offset.x, y: y + offset.y })
+    }
+
+    ///
+    fn expected(&self, what: &str, position: usize) -> String {
+        match self.data[position..].chars().next() {
+            Some(c) => format!("Expected {} but found '{}'", what, c),
+            None => format!("Expected {} but reached the end of the path data", what),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::SVG;
+
+    /// Parse `data` and return the path data written back out.
+    fn reformat(data: &str) -> String {
+        parse_path_data(data).unwrap().to_path_data()
+    }
+
+    /// Verify every absolute command is parsed into the matching segment.
+    #[test]
+    fn absolute_commands() {
+        assert_eq!(reformat("M 0 0 L 10 0 H 20 V 5 Z"), "M 0 0 L 10 0 L 20 0 L 20 5 Z");
+        assert_eq!(reformat("M0,0 C1,2 3,2 4,0 S7,-2 8,0"), "M 0 0 C 1 2 3 2 4 0 S 7 -2 8 0");
+        assert_eq!(reformat("M 0 0 Q 1 1 2 0 T 4 0"), "M 0 0 Q 1 1 2 0 T 4 0");
+        assert_eq!(reformat("M 0 0 A 5 3 30 1 0 10 0"), "M 0 0 A 5 3 30 1 0 10 0");
+    }
+
+    /// Verify relative commands are converted to absolute coordinates, including after a closepath.
+    #[test]
+    fn relative_commands() {
+        assert_eq!(reformat("m 1 1 l 2 0 h 1 v 2 z"), "M 1 1 L 3 1 L 4 1 L 4 3 Z");
+        assert_eq!(reformat("M 1 1 c 1 1 2 1 3 0 s 2 -1 3 0"), "M 1 1 C 2 2 3 2 4 1 S 6 0 7 1");
+        assert_eq!(reformat("M 1 1 q 1 1 2 0 t 2 0"), "M 1 1 Q 2 2 3 1 T 5 1");
+        assert_eq!(reformat("M 1 1 a 5 3 0 0 1 4 4"), "M 1 1 A 5 3 0 0 1 5 5");
+        assert_eq!(reformat("M 1 1 l 2 0 z m 1 1 l 0 1"), "M 1 1 L 3 1 Z M 2 2 L 2 3");
+        assert_eq!(reformat("M 1 1 l 2 0 z l 0 3"), "M 1 1 L 3 1 Z M 1 1 L 1 4");
+    }
+
+    /// Verify implicitly repeated commands, where a repeated moveto becomes a lineto.
+    #[test]
+    fn implicit_commands() {
+        assert_eq!(reformat("M 0 0 1 1 2 0"), "M 0 0 L 1 1 L 2 0");
+        assert_eq!(reformat("m 1 1 1 1 2 0"), "M 1 1 L 2 2 L 4 2");
+        assert_eq!(reformat("M 0 0 H 1 2 3"), "M 0 0 L 1 0 L 2 0 L 3 0");
+        assert_eq!(reformat("M 0 0 Q 1 1 2 0 3 -1 4 0"), "M 0 0 Q 1 1 2 0 Q 3 -1 4 0");
+    }
+
+    /// Verify compact number syntax, with signs, decimals and exponents separating numbers.
+    #[test]
+    fn compact_numbers() {
+        assert_eq!(reformat("M1.5.5L-1-2"), "M 1.5 0.5 L -1 -2");
+        assert_eq!(reformat("M1e-3,2E2l+1.,.5e1"), "M 0.001 200 L 1.001 205");
+        assert_eq!(reformat("M0 0a1 1 0 1110 10"), "M 0 0 A 1 1 0 1 1 10 10");
+        assert_eq!(reformat("  \n\tM 0 0\n"), "M 0 0");
+        assert_eq!(reformat(""), "");
+    }
+
+    /// Verify errors report the position of the offending character.
+    #[test]
+    fn errors() {
+        let err = parse_path_data("L 1 1").err().unwrap();
+        assert_eq!(err, PathDataError { position: 0, message: String::from("Path data must begin with a moveto command") });
+
+        let err = parse_path_data("M 1 1 L 2 x").err().unwrap();
+        assert_eq!(err.to_string(), "Expected a number but found 'x' at position 10");
+
+        let err = parse_path_data("M 1 1 L 2").err().unwrap();
+        assert_eq!(err.to_string(), "Expected a number but reached the end of the path data at position 9");
+
+        let err = parse_path_data("M 1 1 A 1 1 0 2 0 3 3").err().unwrap();
+        assert_eq!(err.to_string(), "Expected an arc flag (0 or 1) but found '2' at position 14");
+
+        let err = parse_path_data("M 1e 1").err().unwrap();
+        assert_eq!(err.to_string(), "Expected an exponent but found ' ' at position 4");
+
+        let err = parse_path_data("M 1 1 Z 2 2").err().unwrap();
+        assert_eq!(err.to_string(), "Expected a command at position 8");
+
+        let err = parse_path_data("M 1 1 # 2 2").err().unwrap();
+        assert_eq!(err.to_string(), "Unexpected character '#' at position 6");
+
+        let err = parse_path_data("M 1 1 X 2 2").err().unwrap();
+        assert_eq!(err.to_string(), "Unknown command 'X' at position 6");
+
+        let err = parse_path_data("M 1 1 L 2,,3").err().unwrap();
+        assert_eq!(err.to_string(), "Expected a number but found ',' at position 10");
+
+        let err = parse_path_data("M 1 1 L 1e999 0").err().unwrap();
+        assert_eq!(err.to_string(), "Invalid number 1e999 at position 8");
+    }
+
+    /// Verify number lists accept the same compact syntax as path data.
+    #[test]
+    fn number_list() {
+        assert_eq!(parse_number_list(" 0,5 2.5-1\n.5.5 1e1 ").unwrap(), vec![0.0, 5.0, 2.5, -1.0, 0.5, 0.5, 10.0]);
+        assert_eq!(parse_number_list("").unwrap(), Vec::<f64>::new());
+
+        let err = parse_number_list("0 0 a").unwrap_err();
+        assert_eq!(err.to_string(), "Expected a number but found 'a' at position 4");
+    }
+
+    /// Verify the output of `to_html` parses back into the same path, for every segment kind.
+    #[test]
+    fn round_trip() {
+        let mut path = Path::from_points(vec![Point{ x: 0.25, y: -1e-3 }]).unwrap();
+        path.add_segment(Segment::Line, Point{ x: 10.0, y: 0.0 }).unwrap();
+        path.add_segment(Segment::Cubic(Point{ x: 1.0, y: 2.0 }, Point{ x: 3.0, y: 2.0 }), Point{ x: 4.0, y: 0.0 }).unwrap();
+        path.add_segment(Segment::SmoothCubic(Point{ x: 7.0, y: -2.0 }), Point{ x: 8.0, y: 0.0 }).unwrap();
+        path.add_segment(Segment::Quadratic(Point{ x: 9.0, y: 1.5 }), Point{ x: 10.0, y: 0.0 }).unwrap();
+        path.add_segment(Segment::SmoothQuadratic, Point{ x: 12.0, y: 0.0 }).unwrap();
+        path.add_segment(Segment::Arc { rx: 2.0, ry: 1.0, rotation: 30.0, large_arc: true, sweep: false }, Point{ x: 12.0, y: 4.0 }).unwrap();
+        path.add_nested_path(vec![Point{ x: 1.0 / 3.0, y: 5.0 }, Point{ x: 6.0, y: 5.0 }], true);
+        path.add_nested_path(vec![Point{ x: -4.0, y: 1e21 }], false);
+
+        let parsed = parse_path_data(&path.to_path_data()).unwrap();
+        assert_eq!(parsed.to_html(), path.to_html());
+        assert_eq!(parsed.to_path_data(), path.to_path_data());
+    }
+}
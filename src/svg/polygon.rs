@@ -0,0 +1,157 @@
+use serde::{Serialize, Deserialize};
+
+use crate::utils;
+use super::{Point, SVG, Dimensions, Style, Transform, html_element, move_points, resize_points, points_attr, Outline, offset_points, polygon_outline};
+
+
+/// A closed shape; the last point is implicitly joined back to the first.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Polygon {
+    uuid: String,
+    points: Vec<Point>,
+    #[serde(default)]
+    style: Style,
+    #[serde(default)]
+    transform: Transform,
+}
+
+impl Polygon {
+    ///
+    pub fn from_points(points: Vec<Point>) -> Result<Polygon, &'static str> {
+        if points.is_empty() {
+            return Err("Expected at least one point to create a Polygon");
+        }
+
+        Ok(Polygon {
+            uuid: utils::gen_uuid(),
+            points,
+            style: Style::new(),
+            transform: Transform::identity(),
+        })
+    }
+
+    ///
+    pub fn add_point(&mut self, point: Point) -> usize {
+        self.points.push(point);
+        self.points.len() - 1
+    }
+
+    ///
+    pub fn get_point(&mut self, i: usize) -> Option<&mut Point> {
+        self.points.get_mut(i)
+    }
+
+    ///
+    pub fn box_ptr(self) -> Box<Self> {
+        Box::new(self)
+    }
+}
+
+impl SVG for Polygon {
+    ///
+    fn get_uuid(&self) -> String {
+        self.uuid.clone()
+    }
+
+    ///
+    fn clone_box(&self) -> Box<dyn SVG> {
+        Box::new(self.clone())
+    }
+
+    ///
+    fn duplicate(&self) -> Box<dyn SVG> {
+        Box::new(Polygon { uuid: utils::gen_uuid(), ..self.clone() })
+    }
+
+    ///
+    fn get_style(&self) -> &Style {
+        &self.style
+    }
+
+    ///
+    fn get_style_mut(&mut self) -> &mut Style {
+        &mut self.style
+    }
+
+    ///
+    fn get_transform(&self) -> &Transform {
+        &self.transform
+    }
+
+    ///
+    fn get_transform_mut(&mut self) -> &mut Transform {
+        &mut self.transform
+    }
+
+    ///
+    fn to_html(&self) -> String {
+        let mut attrs = vec![("points", points_attr(&self.points))];
+        attrs.extend(self.transform.to_attrs());
+        attrs.extend(self.style.to_attrs());
+
+        html_element("polygon", attrs)
+    }
+
+    ///
+    fn move_to(&mut self, x: f64, y: f64) {
+        move_points(&mut self.points, x, y);
+    }
+
+    ///
+    fn move_by(&mut self, dx: f64, dy: f64) {
+        offset_points(self.points.iter_mut(), dx, dy);
+    }
+
+    ///
+    fn outlines_in(&self, outer: &Transform) -> Vec<Outline> {
+        vec![polygon_outline(&self.points, true).transform(&self.transform.then(outer))]
+    }
+
+    ///
+    fn get_points(&self) -> Vec<Point> {
+        self.points.clone()
+    }
+
+    ///
+    fn resize(&mut self, dim: Dimensions) -> Result<(), String> {
+        resize_points(&mut self.points, dim, "Polygon")
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verify the `to_html` function outputs an svg string reflecting the current state of the polygon.
+    #[test]
+    fn to_html() {
+        let mut polygon = Polygon::from_points(vec![
+            Point{ x: 0.0, y: 5.0 },
+            Point{ x: 2.5, y: 0.0 },
+        ]).unwrap();
+        assert_eq!(polygon.to_html(), "<polygon points=\"0,5 2.5,0\"></polygon>");
+
+        polygon.add_point(Point{ x: -2.5, y: 0.0 });
+        assert_eq!(polygon.to_html(), "<polygon points=\"0,5 2.5,0 -2.5,0\"></polygon>");
+
+        polygon.move_to(1.0, 4.5);
+        assert_eq!(polygon.to_html(), "<polygon points=\"1,4.5 3.5,-0.5 -1.5,-0.5\"></polygon>");
+
+        polygon.resize(Dimensions::IndexPosition(2, 0.0, 0.0)).unwrap();
+        assert_eq!(polygon.to_html(), "<polygon points=\"1,4.5 3.5,-0.5 0,0\"></polygon>");
+    }
+
+    /// Verify a polygon cannot be created without points, and `resize` rejects invalid indices.
+    #[test]
+    fn invalid() {
+        let err = Polygon::from_points(vec![]).err().unwrap();
+        assert_eq!(err, "Expected at least one point to create a Polygon");
+
+        let mut polygon = Polygon::from_points(vec![Point{ x: 0.0, y: 0.0 }]).unwrap();
+        let err = polygon.resize(Dimensions::IndexPosition(1, 1.0, 2.0)).unwrap_err();
+        assert_eq!(err, "Cannot resize Polygon because point does not exist at index 1");
+        let err = polygon.resize(Dimensions::Single(1.0)).unwrap_err();
+        assert_eq!(err, "Cannot resize Polygon with dimensions Single(1.0)");
+    }
+}
@@ -0,0 +1,156 @@
+use serde::{Serialize, Deserialize};
+
+use crate::utils;
+use super::{Point, SVG, Dimensions, Style, Transform, html_element, move_points, resize_points, points_attr, Outline, offset_points, polygon_outline};
+
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Polyline {
+    uuid: String,
+    points: Vec<Point>,
+    #[serde(default)]
+    style: Style,
+    #[serde(default)]
+    transform: Transform,
+}
+
+impl Polyline {
+    ///
+    pub fn from_points(points: Vec<Point>) -> Result<Polyline, &'static str> {
+        if points.is_empty() {
+            return Err("Expected at least one point to create a Polyline");
+        }
+
+        Ok(Polyline {
+            uuid: utils::gen_uuid(),
+            points,
+            style: Style::new(),
+            transform: Transform::identity(),
+        })
+    }
+
+    ///
+    pub fn add_point(&mut self, point: Point) -> usize {
+        self.points.push(point);
+        self.points.len() - 1
+    }
+
+    ///
+    pub fn get_point(&mut self, i: usize) -> Option<&mut Point> {
+        self.points.get_mut(i)
+    }
+
+    ///
+    pub fn box_ptr(self) -> Box<Self> {
+        Box::new(self)
+    }
+}
+
+impl SVG for Polyline {
+    ///
+    fn get_uuid(&self) -> String {
+        self.uuid.clone()
+    }
+
+    ///
+    fn clone_box(&self) -> Box<dyn SVG> {
+        Box::new(self.clone())
+    }
+
+    ///
+    fn duplicate(&self) -> Box<dyn SVG> {
+        Box::new(Polyline { uuid: utils::gen_uuid(), ..self.clone() })
+    }
+
+    ///
+    fn get_style(&self) -> &Style {
+        &self.style
+    }
+
+    ///
+    fn get_style_mut(&mut self) -> &mut Style {
+        &mut self.style
+    }
+
+    ///
+    fn get_transform(&self) -> &Transform {
+        &self.transform
+    }
+
+    ///
+    fn get_transform_mut(&mut self) -> &mut Transform {
+        &mut self.transform
+    }
+
+    ///
+    fn to_html(&self) -> String {
+        let mut attrs = vec![("points", points_attr(&self.points))];
+        attrs.extend(self.transform.to_attrs());
+        attrs.extend(self.style.to_attrs());
+
+        html_element("polyline", attrs)
+    }
+
+    ///
+    fn move_to(&mut self, x: f64, y: f64) {
+        move_points(&mut self.points, x, y);
+    }
+
+    ///
+    fn move_by(&mut self, dx: f64, dy: f64) {
+        offset_points(self.points.iter_mut(), dx, dy);
+    }
+
+    ///
+    fn outlines_in(&self, outer: &Transform) -> Vec<Outline> {
+        vec![polygon_outline(&self.points, false).transform(&self.transform.then(outer))]
+    }
+
+    ///
+    fn get_points(&self) -> Vec<Point> {
+        self.points.clone()
+    }
+
+    ///
+    fn resize(&mut self, dim: Dimensions) -> Result<(), String> {
+        resize_points(&mut self.points, dim, "Polyline")
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verify the `to_html` function outputs an svg string reflecting the current state of the polyline.
+    #[test]
+    fn to_html() {
+        let mut polyline = Polyline::from_points(vec![
+            Point{ x: 0.0, y: 5.0 },
+            Point{ x: 2.5, y: 0.0 },
+        ]).unwrap();
+        assert_eq!(polyline.to_html(), "<polyline points=\"0,5 2.5,0\"></polyline>");
+
+        polyline.add_point(Point{ x: -2.5, y: 0.0 });
+        assert_eq!(polyline.to_html(), "<polyline points=\"0,5 2.5,0 -2.5,0\"></polyline>");
+
+        polyline.move_to(1.0, 4.5);
+        assert_eq!(polyline.to_html(), "<polyline points=\"1,4.5 3.5,-0.5 -1.5,-0.5\"></polyline>");
+
+        polyline.resize(Dimensions::IndexPosition(2, 0.0, 0.0)).unwrap();
+        assert_eq!(polyline.to_html(), "<polyline points=\"1,4.5 3.5,-0.5 0,0\"></polyline>");
+    }
+
+    /// Verify a polyline cannot be created without points, and `resize` rejects invalid indices.
+    #[test]
+    fn invalid() {
+        let err = Polyline::from_points(vec![]).err().unwrap();
+        assert_eq!(err, "Expected at least one point to create a Polyline");
+
+        let mut polyline = Polyline::from_points(vec![Point{ x: 0.0, y: 0.0 }]).unwrap();
+        let err = polyline.resize(Dimensions::IndexPosition(1, 1.0, 2.0)).unwrap_err();
+        assert_eq!(err, "Cannot resize Polyline because point does not exist at index 1");
+        let err = polyline.resize(Dimensions::Single(1.0)).unwrap_err();
+        assert_eq!(err, "Cannot resize Polyline with dimensions Single(1.0)");
+    }
+}
@@ -1,12 +1,19 @@
+use serde::{Serialize, Deserialize};
+
 use crate::utils;
-use super::{Point, SVG, Dimensions, html_element};
+use super::{Point, SVG, Dimensions, Style, Transform, html_element, BoundingBox, Outline, polygon_outline};
 
 
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Rect {
     pub uuid: String,
     origin: Point,
     width: f64,
     height: f64,
+    #[serde(default)]
+    style: Style,
+    #[serde(default)]
+    transform: Transform,
 }
 
 impl Rect {
@@ -16,7 +23,9 @@ impl Rect {
             uuid: utils::gen_uuid(),
             origin: Point { x, y },
             width,
-            height
+            height,
+            style: Style::new(),
+            transform: Transform::identity(),
         }
     }
 
@@ -32,14 +41,48 @@ impl SVG for Rect {
         self.uuid.clone()
     }
 
+    ///
+    fn clone_box(&self) -> Box<dyn SVG> {
+        Box::new(self.clone())
+    }
+
+    ///
+    fn duplicate(&self) -> Box<dyn SVG> {
+        Box::new(Rect { uuid: utils::gen_uuid(), ..self.clone() })
+    }
+
+    ///
+    fn get_style(&self) -> &Style {
+        &self.style
+    }
+
+    ///
+    fn get_style_mut(&mut self) -> &mut Style {
+        &mut self.style
+    }
+
+    ///
+    fn get_transform(&self) -> &Transform {
+        &self.transform
+    }
+
+    ///
+    fn get_transform_mut(&mut self) -> &mut Transform {
+        &mut self.transform
+    }
+
     ///
     fn to_html(&self) -> String {
-        html_element("rect", vec![
+        let mut attrs = vec![
             ("x", self.origin.x.to_string()),
             ("y", self.origin.y.to_string()),
             ("width", self.width.to_string()),
             ("height", self.height.to_string()),
-        ])
+        ];
+        attrs.extend(self.transform.to_attrs());
+        attrs.extend(self.style.to_attrs());
+
+        html_element("rect", attrs)
     }
 
     ///
@@ -47,6 +90,18 @@ impl SVG for Rect {
         self.origin.x = x;
         self.origin.y = y;
     }
+
+    ///
+    fn move_by(&mut self, dx: f64, dy: f64) {
+        self.origin.x += dx;
+        self.origin.y += dy;
+    }
+
+    ///
+    fn outlines_in(&self, outer: &Transform) -> Vec<Outline> {
+        let corners = BoundingBox::new(self.origin.x, self.origin.y, self.width, self.height).corners();
+        vec![polygon_outline(&corners, true).transform(&self.transform.then(outer))]
+    }
     
     ///
     fn resize(&mut self, dim: Dimensions) -> Result<(), String> {
@@ -76,6 +131,12 @@ mod tests {
         
         rect.resize(Dimensions::Double(5.0, 0.5)).unwrap();
         assert_eq!(rect.to_html(),"<rect x=\"-3.1\" y=\"4\" width=\"5\" height=\"0.5\"></rect>");
+
+        let mut style = Style::new();
+        style.set_stroke("blue");
+        style.set_opacity(0.5).unwrap();
+        rect.set_style(style);
+        assert_eq!(rect.to_html(),"<rect x=\"-3.1\" y=\"4\" width=\"5\" height=\"0.5\" stroke=\"blue\" opacity=\"0.5\"></rect>");
     }
 
     /// Verify the `move_to` function changes the origin of the rect, and the dimensions stay the same.
@@ -0,0 +1,292 @@
+use std::any::Any;
+
+use serde::{Serialize, Deserialize, Serializer, Deserializer};
+use serde::ser::Error;
+use serde_json::Value;
+
+use super::{SVG, Circle, Ellipse, Rect, Line, Polyline, Polygon, Path, Text, Group, Grid};
+
+
+/// The schema version written to saved drawings. Bump it, and upgrade documents of the old version in `from_json`,
+/// whenever a change to the saved fields would stop older drawings from loading as they are.
+/// Fields that are simply added should have a default instead, so older drawings load without them.
+pub(super) const VERSION: u64 = 1;
+
+
+/// A shape borrowed for saving, tagged with its type so it can be loaded back as the same shape.
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum ShapeRef<'a> {
+    Circle(&'a Circle),
+    Ellipse(&'a Ellipse),
+    Rect(&'a Rect),
+    Line(&'a Line),
+    Polyline(&'a Polyline),
+    Polygon(&'a Polygon),
+    Path(&'a Path),
+    Text(&'a Text),
+    Group(&'a Group),
+}
+
+/// A shape loaded from a drawing, by the type it was tagged with.
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum ShapeData {
+    Circle(Circle),
+    Ellipse(Ellipse),
+    Rect(Rect),
+    Line(Line),
+    Polyline(Polyline),
+    Polygon(Polygon),
+    Path(Path),
+    Text(Text),
+    Group(Group),
+}
+
+impl<'a> ShapeRef<'a> {
+    /// The concrete shape behind `shape`, if it is one that can be saved.
+    fn from_shape(shape: &'a dyn SVG) -> Option<ShapeRef<'a>> {
+        let shape: &dyn Any = shape;
+
+        if let Some(circle) = shape.downcast_ref::<Circle>() {
+            Some(ShapeRef::Circle(circle))
+        } else if let Some(ellipse) = shape.downcast_ref::<Ellipse>() {
+            Some(ShapeRef::Ellipse(ellipse))
+        } else if let Some(rect) = shape.downcast_ref::<Rect>() {
+            Some(ShapeRef::Rect(rect))
+        } else if let Some(line) = shape.downcast_ref::<Line>() {
+            Some(ShapeRef::Line(line))
+        } else if let Some(polyline) = shape.downcast_ref::<Polyline>() {
+            Some(ShapeRef::Polyline(polyline))
+        } else if let Some(polygon) = shape.downcast_ref::<Polygon>() {
+            Some(ShapeRef::Polygon(polygon))
+        } else if let Some(path) = shape.downcast_ref::<Path>() {
+            Some(ShapeRef::Path(path))
+        } else if let Some(text) = shape.downcast_ref::<Text>() {
+            Some(ShapeRef::Text(text))
+        } else {
+            shape.downcast_ref::<Group>().map(ShapeRef::Group)
+        }
+    }
+}
+
+impl ShapeData {
+    /// The loaded shape, unless it is missing something every shape of its type has, which only a hand edited or
+    /// corrupt drawing can be.
+    fn into_shape(self) -> Result<Box<dyn SVG>, String> {
+        let shape: Box<dyn SVG> = match self {
+            ShapeData::Circle(circle) => circle.box_ptr(),
+            ShapeData::Ellipse(ellipse) => ellipse.box_ptr(),
+            ShapeData::Rect(rect) => rect.box_ptr(),
+            ShapeData::Line(line) => line.box_ptr(),
+            ShapeData::Polyline(polyline) => polyline.box_ptr(),
+            ShapeData::Polygon(polygon) => polygon.box_ptr(),
+            ShapeData::Path(path) => {
+                path.validate()?;
+                path.box_ptr()
+            },
+            ShapeData::Text(text) => text.box_ptr(),
+            ShapeData::Group(group) => group.box_ptr(),
+        };
+
+        let shape_any: &dyn Any = shape.as_ref();
+        if (shape_any.is::<Polyline>() || shape_any.is::<Polygon>()) && shape.get_points().is_empty() {
+            return Err(format!("Shape {} has no points", shape.get_uuid()));
+        }
+
+        Ok(shape)
+    }
+}
+
+
+/// Saves and loads the shapes of a group, for use with `#[serde(with = "...")]`.
+pub(super) mod shape_list {
+    use super::*;
+
+    ///
+    pub fn serialize<S: Serializer>(shapes: &[Box<dyn SVG>], serializer: S) -> Result<S::Ok, S::Error> {
+        let shapes = shapes.iter()
+            .map(|shape| ShapeRef::from_shape(shape.as_ref())
+                .ok_or_else(|| S::Error::custom(format!("Unable to save shape {} of an unknown type", shape.get_uuid()))))
+            .collect::<Result<Vec<ShapeRef>, S::Error>>()?;
+
+        shapes.serialize(serializer)
+    }
+
+    ///
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<Box<dyn SVG>>, D::Error> {
+        let shapes = Vec::<ShapeData>::deserialize(deserializer)?;
+        shapes.into_iter()
+            .map(|shape| shape.into_shape().map_err(serde::de::Error::custom))
+            .collect()
+    }
+}
+
+
+/// A drawing as it is saved, with the version of the schema it was saved with.
+#[derive(Serialize)]
+struct Document<'a> {
+    version: u64,
+    grid: &'a Grid,
+}
+
+/// Save a grid as a json document in the current schema.
+pub(super) fn to_json(grid: &Grid) -> Result<String, String> {
+    serde_json::to_string_pretty(&Document { version: VERSION, grid })
+        .map_err(|e| format!("Unable to save drawing: {}", e))
+}
+
+/// Load a grid from a json document, as long as it was saved with a schema this version understands.
+pub(super) fn from_json(source: &str) -> Result<Grid, String> {
+    let mut document: Value = serde_json::from_str(source).map_err(|e| format!("Unable to parse drawing: {}", e))?;
+
+    let version = match document.get("version").and_then(Value::as_u64) {
+        Some(version) => version,
+        None => return Err(String::from("Unable to load drawing without a schema version")),
+    };
+
+    // Documents saved with an older schema are upgraded here, one version at a time, before being loaded
+    match version {
+        VERSION => {},
+        newer if newer > VERSION => {
+            return Err(format!("Unable to load drawing saved with schema version {}, the newest supported is {}", newer, VERSION));
+        },
+        unknown => return Err(format!("Unable to load drawing saved with unknown schema version {}", unknown)),
+    }
+
+    serde_json::from_value(document["grid"].take()).map_err(|e| format!("Unable to load drawing: {}", e))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::{CoordinateSystem, Point, Style, Transform};
+
+    /// Verify every kind of shape, nested groups and the grid's own settings survive saving and loading.
+    #[test]
+    fn round_trip() {
+        let mut grid = Grid::new(CoordinateSystem::MidMidUpRight);
+        grid.resize(super::super::Dimensions::Double(300.0, 200.0)).unwrap();
+
+        let mut circle = Circle::new(1.0, 2.0, 3.0);
+        let mut style = Style::new();
+        style.set_fill("red");
+        style.set_fill_rule("evenodd").unwrap();
+        circle.set_style(style);
+        circle.rotate(30.0, 1.0, 1.0);
+        grid.add_shape(circle.box_ptr());
+        grid.add_shape(Ellipse::new(1.0, 2.0, 3.0, 4.0).box_ptr());
+        grid.add_shape(Rect::new(1.0, 2.0, 3.0, 4.0).box_ptr());
+        grid.add_shape(Line::new(1.0, 2.0, 3.0, 4.0).box_ptr());
+        grid.add_shape(Polyline::from_points(vec![Point{ x: 0.0, y: 0.0 }, Point{ x: 1.0, y: 1.0 }]).unwrap().box_ptr());
+        grid.add_shape(Polygon::from_points(vec![Point{ x: 0.0, y: 0.0 }, Point{ x: 1.0, y: 1.0 }, Point{ x: 1.0, y: 0.0 }]).unwrap().box_ptr());
+        grid.add_shape(Path::from_path_data("M 0 0 C 1 2 3 4 5 6 S 7 8 9 10 A 5 5 0 1 0 20 20 Z M 30 30 q 1 1 2 0 t 2 0").unwrap().box_ptr());
+        grid.add_shape(Text::new(1.0, 2.0, "<Hello>").box_ptr());
+
+        let mut nested = Group::new("nested");
+        nested.add_shape(Rect::new(5.0, 5.0, 1.0, 1.0).box_ptr());
+        nested.set_transform(Transform::scale(2.0, 2.0));
+        let mut layer = Group::new("layer");
+        layer.add_shape(nested.box_ptr());
+        grid.add_group(layer);
+
+        let loaded = Grid::from_json_str(&grid.to_json().unwrap()).unwrap();
+        assert_eq!(loaded.to_html(), grid.to_html());
+        assert_eq!(loaded.get_uuid(), grid.get_uuid());
+        assert_eq!(loaded.get_coordinate_system(), CoordinateSystem::MidMidUpRight);
+        assert_eq!(loaded.get_history().done().count(), 0);
+
+        // Lookups and hit testing work on the loaded shapes
+        let uuid = grid.get_shape(2).unwrap().get_uuid();
+        assert_eq!(loaded.get_by_uuid(&uuid).unwrap().get_uuid(), uuid);
+        assert_eq!(loaded.shapes_at(Point{ x: 11.0, y: 11.0 }, 0.0).len(), 1);
+    }
+
+    /// Verify documents are tagged with the schema version, and only versions this build understands are loaded.
+    #[test]
+    fn versions() {
+        let grid = Grid::new(CoordinateSystem::TopLeftDownRight);
+        let mut document: Value = serde_json::from_str(&grid.to_json().unwrap()).unwrap();
+        assert_eq!(document["version"], Value::from(VERSION));
+        assert_eq!(document["grid"]["groups"][0]["name"], Value::from("shapes"));
+
+        document["version"] = Value::from(VERSION + 1);
+        let err = Grid::from_json_str(&document.to_string()).err().unwrap();
+        assert_eq!(err, format!("Unable to load drawing saved with schema version {}, the newest supported is {}", VERSION + 1, VERSION));
+
+        document["version"] = Value::from(0);
+        let err = Grid::from_json_str(&document.to_string()).err().unwrap();
+        assert_eq!(err, "Unable to load drawing saved with unknown schema version 0");
+
+        document.as_object_mut().unwrap().remove("version");
+        let err = Grid::from_json_str(&document.to_string()).err().unwrap();
+        assert_eq!(err, "Unable to load drawing without a schema version");
+        assert!(Grid::from_json_str("{").err().unwrap().starts_with("Unable to parse drawing: "));
+    }
+
+    /// Verify shapes saved without optional fields load with their defaults, and unknown shape types are rejected.
+    #[test]
+    fn defaults() {
+        let source = r#"{
+            "version": 1,
+            "grid": {
+                "uuid": "grid", "width": 10, "height": 10, "coordinate_system": "TopLeftDownRight",
+                "groups": [{ "uuid": "shapes", "name": "shapes", "shapes": [
+                    { "type": "rect", "uuid": "a", "origin": { "x": 1, "y": 2 }, "width": 3, "height": 4 },
+                    { "type": "text", "uuid": "b", "origin": { "x": 1, "y": 2 }, "content": "Hi" }
+                ]}]
+            }
+        }"#;
+
+        let grid = Grid::from_json_str(source).unwrap();
+        assert_eq!(grid.get_by_uuid("a").unwrap().get_transform(), &Transform::identity());
+        assert_eq!(grid.get_by_uuid("b").unwrap().get_style(), &Style::new());
+        assert_eq!(grid.get_width(), 10.0);
+
+        let err = Grid::from_json_str(&source.replace("\"rect\"", "\"star\"")).err().unwrap();
+        assert!(err.starts_with("Unable to load drawing: unknown variant `star`"), "{}", err);
+    }
+
+    /// Verify drawings that deserialize but would leave the grid unusable are rejected rather than loaded.
+    #[test]
+    fn invalid_drawings() {
+        let load = |groups: &str| {
+            let source = format!(r#"{{
+                "version": 1,
+                "grid": {{ "uuid": "grid", "width": 10, "height": 10, "coordinate_system": "TopLeftDownRight", "groups": {} }}
+            }}"#, groups);
+            Grid::from_json_str(&source)
+        };
+        let shapes = |shape: &str| format!(r#"[{{ "uuid": "shapes", "name": "shapes", "shapes": [{}] }}]"#, shape);
+        let path = |segments: &str, starting_points: &str, closed: &str| shapes(&format!(
+            r#"{{ "type": "path", "uuid": "p", "points": [{{ "x": 0, "y": 0 }}, {{ "x": 1, "y": 1 }}],
+                "segments": {}, "starting_points": {}, "closed": {} }}"#,
+            segments, starting_points, closed,
+        ));
+
+        assert_eq!(load("[]").err().unwrap(), "Unable to load drawing: Drawing grid has no \"shapes\" group");
+        assert_eq!(load(r#"[{ "uuid": "g", "name": "layer", "shapes": [] }]"#).err().unwrap(), "Unable to load drawing: Drawing grid has no \"shapes\" group");
+
+        let err = load(&shapes(r#"{ "type": "polygon", "uuid": "a", "points": [] }"#)).err().unwrap();
+        assert_eq!(err, "Unable to load drawing: Shape a has no points");
+        let err = load(&shapes(r#"{ "type": "polyline", "uuid": "b", "points": [] }"#)).err().unwrap();
+        assert_eq!(err, "Unable to load drawing: Shape b has no points");
+
+        let err = load(&path(r#"["Line"]"#, "[0]", "[false]")).err().unwrap();
+        assert_eq!(err, "Unable to load drawing: Path p has mismatched points and segments (2 and 1)");
+        let err = load(&path(r#"["Line", "Line"]"#, "[0]", "[]")).err().unwrap();
+        assert_eq!(err, "Unable to load drawing: Path p has mismatched subpaths and closed flags (1 and 0)");
+        let err = load(&path(r#"["Line", "Line"]"#, "[1]", "[false]")).err().unwrap();
+        assert_eq!(err, "Unable to load drawing: Path p has points before its first subpath starts");
+        let err = load(&path(r#"["Line", "Line"]"#, "[]", "[]")).err().unwrap();
+        assert_eq!(err, "Unable to load drawing: Path p has points before its first subpath starts");
+        let err = load(&path(r#"["Line", "Line"]"#, "[0, 3]", "[false, true]")).err().unwrap();
+        assert_eq!(err, "Unable to load drawing: Path p has subpaths starting out of order or past its 2 points");
+
+        // Shapes in nested groups are checked too
+        let err = load(&shapes(r#"{ "type": "group", "uuid": "g", "name": "g", "shapes": [{ "type": "polygon", "uuid": "c", "points": [] }] }"#)).err().unwrap();
+        assert_eq!(err, "Unable to load drawing: Shape c has no points");
+        assert!(load(&path(r#"["Line", "Line"]"#, "[0]", "[true]")).is_ok());
+    }
+}
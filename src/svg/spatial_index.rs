@@ -0,0 +1,102 @@
+use std::collections::HashMap;
+
+use rstar::{RTree, AABB};
+use rstar::primitives::{GeomWithData, Rectangle};
+
+use super::{Point, BoundingBox};
+
+
+/// Locates a shape by the index of its group in the grid, and its index within that group.
+pub(super) type ShapeKey = (usize, usize);
+
+type Entry = GeomWithData<Rectangle<[f64; 2]>, ShapeKey>;
+
+
+/// An R-tree of the boxes shapes are painted within, so region and point queries
+/// only have to look at the shapes near them.
+pub(super) struct SpatialIndex {
+    tree: RTree<Entry>,
+    boxes: HashMap<ShapeKey, Entry>,
+}
+
+/// Converts a box into an R-tree entry for a shape.
+fn entry(key: ShapeKey, bounds: &BoundingBox) -> Entry {
+    GeomWithData::new(Rectangle::from_corners([bounds.min.x, bounds.min.y], [bounds.max.x, bounds.max.y]), key)
+}
+
+impl SpatialIndex {
+    ///
+    pub fn new() -> SpatialIndex {
+        SpatialIndex { tree: RTree::new(), boxes: HashMap::new() }
+    }
+
+    /// Build an index from scratch, which packs the tree better than inserting each box in turn.
+    pub fn bulk_load(boxes: Vec<(ShapeKey, BoundingBox)>) -> SpatialIndex {
+        let boxes = boxes.iter()
+            .map(|(key, bounds)| (*key, entry(*key, bounds)))
+            .collect::<HashMap<ShapeKey, Entry>>();
+
+        SpatialIndex { tree: RTree::bulk_load(boxes.values().cloned().collect()), boxes }
+    }
+
+    /// Set the box of a shape, replacing any box it had before. Shapes without a box are removed from the index.
+    pub fn update(&mut self, key: ShapeKey, bounds: Option<BoundingBox>) {
+        if let Some(old) = self.boxes.remove(&key) {
+            self.tree.remove(&old);
+        }
+
+        if let Some(bounds) = bounds {
+            let new = entry(key, &bounds);
+            self.tree.insert(new);
+            self.boxes.insert(key, new);
+        }
+    }
+
+    /// The keys of every shape whose box touches `bounds`, in no particular order.
+    pub fn query_rect(&self, bounds: &BoundingBox) -> Vec<ShapeKey> {
+        let envelope = AABB::from_corners([bounds.min.x, bounds.min.y], [bounds.max.x, bounds.max.y]);
+
+        self.tree.locate_in_envelope_intersecting(&envelope)
+            .map(|entry| entry.data)
+            .collect()
+    }
+
+    /// Every shape in order of the distance from `point` to its box, along with that distance.
+    pub fn nearest(&self, point: Point) -> impl Iterator<Item = (ShapeKey, f64)> + '_ {
+        self.tree.nearest_neighbor_iter_with_distance_2(&[point.x, point.y])
+            .map(|(entry, distance_2)| (entry.data, distance_2.sqrt()))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verify boxes can be queried by region and distance, and updating a box replaces it.
+    #[test]
+    fn query() {
+        let mut index = SpatialIndex::bulk_load(vec![
+            ((0, 0), BoundingBox::new(0.0, 0.0, 10.0, 10.0)),
+            ((0, 1), BoundingBox::new(20.0, 0.0, 10.0, 10.0)),
+        ]);
+        index.update((1, 0), Some(BoundingBox::new(5.0, 5.0, 20.0, 1.0)));
+        assert_eq!(index.query_rect(&BoundingBox::new(-1e3, -1e3, 2e3, 2e3)).len(), 3);
+
+        let mut keys = index.query_rect(&BoundingBox::new(8.0, 0.0, 4.0, 4.0));
+        keys.sort();
+        assert_eq!(keys, vec![(0, 0)]);
+
+        let mut keys = index.query_rect(&BoundingBox::new(9.0, 5.5, 0.0, 0.0));
+        keys.sort();
+        assert_eq!(keys, vec![(0, 0), (1, 0)]);
+
+        index.update((0, 0), Some(BoundingBox::new(100.0, 100.0, 1.0, 1.0)));
+        index.update((1, 0), None);
+        assert_eq!(index.query_rect(&BoundingBox::new(-1e3, -1e3, 2e3, 2e3)).len(), 2);
+        assert!(index.query_rect(&BoundingBox::new(9.0, 5.5, 0.0, 0.0)).is_empty());
+
+        let nearest = index.nearest(Point{ x: 15.0, y: 5.0 }).collect::<Vec<(ShapeKey, f64)>>();
+        assert_eq!(nearest, vec![((0, 1), 5.0), ((0, 0), 85.0_f64.hypot(95.0))]);
+    }
+}
@@ -0,0 +1,199 @@
+use serde::{Serialize, Deserialize};
+
+use crate::utils;
+
+
+/// Presentation attributes shared by every shape.
+///
+/// Unset attributes are omitted from the output, so a shape without a value inherits it from its enclosing group.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Style {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fill: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fill_rule: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stroke: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stroke_width: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub opacity: Option<f64>,
+}
+
+impl Style {
+    ///
+    pub fn new() -> Style {
+        Style::default()
+    }
+
+    ///
+    pub fn set_fill(&mut self, fill: &str) {
+        self.fill = Some(String::from(fill));
+    }
+
+    /// Rule must be one of `nonzero` or `evenodd`.
+    pub fn set_fill_rule(&mut self, rule: &str) -> Result<(), String> {
+        match rule {
+            "nonzero" | "evenodd" => {
+                self.fill_rule = Some(String::from(rule));
+                Ok(())
+            },
+            _ => Err(format!("Unknown fill rule {}", rule)),
+        }
+    }
+
+    /// Whether the inside of the shape is painted. Unset fills default to black.
+    pub fn is_filled(&self) -> bool {
+        self.fill.as_deref() != Some("none")
+    }
+
+    /// Whether the outline of the shape is painted. Unset strokes default to none.
+    pub fn is_stroked(&self) -> bool {
+        !matches!(self.stroke.as_deref(), None | Some("none")) && self.stroke_width != Some(0.0)
+    }
+
+    ///
+    pub fn set_stroke(&mut self, stroke: &str) {
+        self.stroke = Some(String::from(stroke));
+    }
+
+    ///
+    pub fn set_stroke_width(&mut self, width: f64) -> Result<(), String> {
+        if width < 0.0 {
+            return Err(format!("Stroke width must not be negative, got {}", width));
+        }
+
+        self.stroke_width = Some(width);
+        Ok(())
+    }
+
+    ///
+    pub fn set_opacity(&mut self, opacity: f64) -> Result<(), String> {
+        if !(0.0..=1.0).contains(&opacity) {
+            return Err(format!("Opacity must be between 0 and 1, got {}", opacity));
+        }
+
+        self.opacity = Some(opacity);
+        Ok(())
+    }
+
+    /// The style a shape is painted with, taking each attribute it leaves unset from `parent`, the style resolved
+    /// for its enclosing group. Opacity isn't inherited in svg, so it is never taken from the parent.
+    pub fn inherit(&self, parent: &Style) -> Style {
+        Style {
+            fill: self.fill.clone().or_else(|| parent.fill.clone()),
+            fill_rule: self.fill_rule.clone().or_else(|| parent.fill_rule.clone()),
+            stroke: self.stroke.clone().or_else(|| parent.stroke.clone()),
+            stroke_width: self.stroke_width.or(parent.stroke_width),
+            opacity: self.opacity,
+        }
+    }
+
+    /// Set an attribute by its svg name. An empty value clears the attribute so it is inherited again.
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        match (key, value) {
+            ("fill", "") => self.fill = None,
+            ("fill-rule", "") => self.fill_rule = None,
+            ("stroke", "") => self.stroke = None,
+            ("stroke-width", "") => self.stroke_width = None,
+            ("opacity", "") => self.opacity = None,
+            ("fill", _) => self.set_fill(value),
+            ("fill-rule", _) => self.set_fill_rule(value)?,
+            ("stroke", _) => self.set_stroke(value),
+            ("stroke-width", _) => self.set_stroke_width(utils::str_to_float(value)?)?,
+            ("opacity", _) => self.set_opacity(utils::str_to_float(value)?)?,
+            _ => return Err(format!("Unknown style attribute {}", key)),
+        }
+
+        Ok(())
+    }
+
+    /// The attributes that have been set, in the order they are written to svg.
+    pub fn to_attrs(&self) -> Vec<(&'static str, String)> {
+        let mut attrs = vec![];
+
+        if let Some(fill) = &self.fill {
+            attrs.push(("fill", fill.clone()));
+        }
+        if let Some(rule) = &self.fill_rule {
+            attrs.push(("fill-rule", rule.clone()));
+        }
+        if let Some(stroke) = &self.stroke {
+            attrs.push(("stroke", stroke.clone()));
+        }
+        if let Some(width) = self.stroke_width {
+            attrs.push(("stroke-width", width.to_string()));
+        }
+        if let Some(opacity) = self.opacity {
+            attrs.push(("opacity", opacity.to_string()));
+        }
+
+        attrs
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verify the `to_attrs` function only outputs the attributes that have been set.
+    #[test]
+    fn to_attrs() {
+        let mut style = Style::new();
+        assert_eq!(style.to_attrs(), vec![]);
+
+        style.set_stroke("blue");
+        style.set_opacity(0.5).unwrap();
+        assert_eq!(style.to_attrs(), vec![
+            ("stroke", String::from("blue")),
+            ("opacity", String::from("0.5")),
+        ]);
+
+        style.set_fill("#f00");
+        style.set_stroke_width(2.0).unwrap();
+        assert_eq!(style.to_attrs(), vec![
+            ("fill", String::from("#f00")),
+            ("stroke", String::from("blue")),
+            ("stroke-width", String::from("2")),
+            ("opacity", String::from("0.5")),
+        ]);
+    }
+
+    /// Verify the `set` function parses attributes by svg name, and rejects invalid values.
+    #[test]
+    fn set() {
+        let mut style = Style::new();
+        style.set("fill", "#f00").unwrap();
+        style.set("fill-rule", "evenodd").unwrap();
+        style.set("stroke", "none").unwrap();
+        style.set("stroke-width", "1.5").unwrap();
+        style.set("opacity", "0.25").unwrap();
+        assert_eq!(style, Style {
+            fill: Some(String::from("#f00")),
+            fill_rule: Some(String::from("evenodd")),
+            stroke: Some(String::from("none")),
+            stroke_width: Some(1.5),
+            opacity: Some(0.25),
+        });
+
+        // Verify an empty value clears the attribute
+        style.set("stroke", "").unwrap();
+        assert_eq!(style.stroke, None);
+
+        // Verify invalid attributes and values are rejected without modifying the style
+        let err = style.set("font", "serif").unwrap_err();
+        assert_eq!(err, "Unknown style attribute font");
+        let err = style.set("fill-rule", "inherit").unwrap_err();
+        assert_eq!(err, "Unknown fill rule inherit");
+        let err = style.set("opacity", "2").unwrap_err();
+        assert_eq!(err, "Opacity must be between 0 and 1, got 2");
+        let err = style.set("stroke-width", "-1").unwrap_err();
+        assert_eq!(err, "Stroke width must not be negative, got -1");
+        let err = style.set("stroke-width", "wide").unwrap_err();
+        assert_eq!(err, "Error parsing float from wide");
+        assert_eq!(style.opacity, Some(0.25));
+        assert_eq!(style.stroke_width, Some(1.5));
+    }
+}
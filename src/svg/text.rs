@@ -0,0 +1,244 @@
+use std::any::Any;
+
+use serde::{Serialize, Deserialize};
+
+use crate::utils;
+use super::{Point, SVG, Dimensions, Style, Transform, html_element_with_content, Outline};
+use super::group::as_group_mut;
+
+
+/// A label positioned by its baseline origin. Unset font attributes are omitted so they can be inherited.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Text {
+    uuid: String,
+    origin: Point,
+    content: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    font_family: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    font_size: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    font_weight: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    text_anchor: Option<String>,
+    #[serde(default)]
+    style: Style,
+    #[serde(default)]
+    transform: Transform,
+}
+
+impl Text {
+    ///
+    pub fn new(x: f64, y: f64, content: &str) -> Text {
+        Text {
+            uuid: utils::gen_uuid(),
+            origin: Point { x, y },
+            content: String::from(content),
+            font_family: None,
+            font_size: None,
+            font_weight: None,
+            text_anchor: None,
+            style: Style::new(),
+            transform: Transform::identity(),
+        }
+    }
+
+    ///
+    pub fn get_content(&self) -> String {
+        self.content.clone()
+    }
+
+    ///
+    pub fn set_content(&mut self, content: &str) {
+        self.content = String::from(content);
+    }
+
+    ///
+    pub fn set_font_family(&mut self, family: &str) {
+        self.font_family = Some(String::from(family));
+    }
+
+    ///
+    pub fn set_font_size(&mut self, size: f64) -> Result<(), String> {
+        if size <= 0.0 {
+            return Err(format!("Font size must be positive, got {}", size));
+        }
+
+        self.font_size = Some(size);
+        Ok(())
+    }
+
+    ///
+    pub fn set_font_weight(&mut self, weight: &str) {
+        self.font_weight = Some(String::from(weight));
+    }
+
+    /// Anchor must be one of `start`, `middle` or `end`.
+    pub fn set_text_anchor(&mut self, anchor: &str) -> Result<(), String> {
+        match anchor {
+            "start" | "middle" | "end" => {
+                self.text_anchor = Some(String::from(anchor));
+                Ok(())
+            },
+            _ => Err(format!("Unknown text anchor {}", anchor)),
+        }
+    }
+
+    ///
+    pub fn box_ptr(self) -> Box<Self> {
+        Box::new(self)
+    }
+}
+
+impl SVG for Text {
+    ///
+    fn get_uuid(&self) -> String {
+        self.uuid.clone()
+    }
+
+    ///
+    fn clone_box(&self) -> Box<dyn SVG> {
+        Box::new(self.clone())
+    }
+
+    ///
+    fn duplicate(&self) -> Box<dyn SVG> {
+        Box::new(Text { uuid: utils::gen_uuid(), ..self.clone() })
+    }
+
+    ///
+    fn get_style(&self) -> &Style {
+        &self.style
+    }
+
+    ///
+    fn get_style_mut(&mut self) -> &mut Style {
+        &mut self.style
+    }
+
+    ///
+    fn get_transform(&self) -> &Transform {
+        &self.transform
+    }
+
+    ///
+    fn get_transform_mut(&mut self) -> &mut Transform {
+        &mut self.transform
+    }
+
+    ///
+    fn to_html(&self) -> String {
+        let mut attrs = vec![
+            ("x", self.origin.x.to_string()),
+            ("y", self.origin.y.to_string()),
+        ];
+
+        if let Some(family) = &self.font_family {
+            attrs.push(("font-family", family.clone()));
+        }
+        if let Some(size) = self.font_size {
+            attrs.push(("font-size", size.to_string()));
+        }
+        if let Some(weight) = &self.font_weight {
+            attrs.push(("font-weight", weight.clone()));
+        }
+        if let Some(anchor) = &self.text_anchor {
+            attrs.push(("text-anchor", anchor.clone()));
+        }
+        attrs.extend(self.transform.to_attrs());
+        attrs.extend(self.style.to_attrs());
+
+        html_element_with_content("text", attrs, &self.content)
+    }
+
+    ///
+    fn move_to(&mut self, x: f64, y: f64) {
+        self.origin.x = x;
+        self.origin.y = y;
+    }
+
+    ///
+    fn move_by(&mut self, dx: f64, dy: f64) {
+        self.origin.x += dx;
+        self.origin.y += dy;
+    }
+
+    /// Without font metrics the extent of the text is unknown, so the outline is only its origin.
+    fn outlines_in(&self, outer: &Transform) -> Vec<Outline> {
+        vec![Outline { start: self.transform.then(outer).apply(self.origin), curves: vec![] }]
+    }
+
+    /// Text is resized by changing its font size.
+    fn resize(&mut self, dim: Dimensions) -> Result<(), String> {
+        if let Dimensions::Single(size) = dim {
+            self.set_font_size(size)
+        } else {
+            Err(format!("Cannot resize Text with dimensions {}", dim))
+        }
+    }
+}
+
+
+/// Turn any text in `shape` upright, for drawing inside a root transform that flips the y axis. Each text is flipped
+/// back about its origin, so its baseline stays where it was.
+pub(super) fn upright_text(shape: &mut dyn SVG) {
+    if let Some(group) = as_group_mut(shape) {
+        for i in 0..group.len() {
+            if let Some(child) = group.get_shape_mut(i) {
+                upright_text(child.as_mut());
+            }
+        }
+        return;
+    }
+
+    let shape: &mut dyn Any = shape;
+    if let Some(text) = shape.downcast_mut::<Text>() {
+        let flip = Transform::new(1.0, 0.0, 0.0, -1.0, 0.0, 2.0 * text.origin.y);
+        text.transform = flip.then(&text.transform);
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verify the `to_html` function outputs an svg string reflecting the current state of the text.
+    #[test]
+    fn to_html() {
+        let mut text = Text::new(10.0, 20.5, "Label");
+        assert_eq!(text.to_html(), "<text x=\"10\" y=\"20.5\">Label</text>");
+
+        text.move_to(-1.0, 4.0);
+        text.resize(Dimensions::Single(12.0)).unwrap();
+        assert_eq!(text.to_html(), "<text x=\"-1\" y=\"4\" font-size=\"12\">Label</text>");
+
+        text.set_font_family("Open Sans");
+        text.set_font_weight("bold");
+        text.set_text_anchor("middle").unwrap();
+        text.get_style_mut().set_fill("#333");
+        assert_eq!(text.to_html(), "<text x=\"-1\" y=\"4\" font-family=\"Open Sans\" font-size=\"12\" font-weight=\"bold\" text-anchor=\"middle\" fill=\"#333\">Label</text>");
+    }
+
+    /// Verify markup characters in the content and attributes are escaped.
+    #[test]
+    fn escaping() {
+        let mut text = Text::new(0.0, 0.0, "a < b & \"c\" > 'd'");
+        text.set_font_family("\"Fira Code\", monospace");
+        assert_eq!(text.to_html(), "<text x=\"0\" y=\"0\" font-family=\"&quot;Fira Code&quot;, monospace\">a &lt; b &amp; &quot;c&quot; &gt; &apos;d&apos;</text>");
+    }
+
+    /// Verify invalid font sizes, anchors and dimensions are rejected.
+    #[test]
+    fn invalid() {
+        let mut text = Text::new(0.0, 0.0, "Label");
+
+        let err = text.resize(Dimensions::Single(0.0)).unwrap_err();
+        assert_eq!(err, "Font size must be positive, got 0");
+        let err = text.resize(Dimensions::Double(1.0, 2.0)).unwrap_err();
+        assert_eq!(err, "Cannot resize Text with dimensions Double(1.0, 2.0)");
+        let err = text.set_text_anchor("left").unwrap_err();
+        assert_eq!(err, "Unknown text anchor left");
+        assert_eq!(text.to_html(), "<text x=\"0\" y=\"0\">Label</text>");
+    }
+}
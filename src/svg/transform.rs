@@ -0,0 +1,250 @@
+use serde::{Serialize, Deserialize};
+
+use super::Point;
+use super::path_data::parse_number_list;
+
+
+/// A 2D affine transformation, stored as the matrix `[a c e; b d f; 0 0 1]` in the same order as svg's `matrix()`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Transform {
+    pub a: f64,
+    pub b: f64,
+    pub c: f64,
+    pub d: f64,
+    pub e: f64,
+    pub f: f64,
+}
+
+/// Round away floating point noise, so quarter turns produce exact matrices.
+fn snap(value: f64) -> f64 {
+    let rounded = value.round();
+    if (value - rounded).abs() < 1e-12 { rounded } else { value }
+}
+
+impl Transform {
+    ///
+    pub fn new(a: f64, b: f64, c: f64, d: f64, e: f64, f: f64) -> Transform {
+        Transform { a, b, c, d, e, f }
+    }
+
+    ///
+    pub fn identity() -> Transform {
+        Transform::new(1.0, 0.0, 0.0, 1.0, 0.0, 0.0)
+    }
+
+    ///
+    pub fn translate(dx: f64, dy: f64) -> Transform {
+        Transform::new(1.0, 0.0, 0.0, 1.0, dx, dy)
+    }
+
+    /// Scale relative to the origin.
+    pub fn scale(sx: f64, sy: f64) -> Transform {
+        Transform::new(sx, 0.0, 0.0, sy, 0.0, 0.0)
+    }
+
+    /// Rotate clockwise (in svg's y-down space) by `degrees` around the origin.
+    pub fn rotate(degrees: f64) -> Transform {
+        let (sin, cos) = degrees.to_radians().sin_cos();
+        let (sin, cos) = (snap(sin), snap(cos));
+
+        Transform::new(cos, sin, -sin, cos, 0.0, 0.0)
+    }
+
+    /// Rotate by `degrees` around the point (cx, cy).
+    pub fn rotate_about(degrees: f64, cx: f64, cy: f64) -> Transform {
+        Transform::translate(-cx, -cy)
+            .then(&Transform::rotate(degrees))
+            .then(&Transform::translate(cx, cy))
+    }
+
+    /// Skew along the x axis by `x_degrees` and along the y axis by `y_degrees`.
+    pub fn skew(x_degrees: f64, y_degrees: f64) -> Transform {
+        Transform::new(1.0, snap(y_degrees.to_radians().tan()), snap(x_degrees.to_radians().tan()), 1.0, 0.0, 0.0)
+    }
+
+    /// The matrix product `self * other`, which applies `other` first and then `self`.
+    pub fn multiply(&self, other: &Transform) -> Transform {
+        Transform {
+            a: self.a * other.a + self.c * other.b,
+            b: self.b * other.a + self.d * other.b,
+            c: self.a * other.c + self.c * other.d,
+            d: self.b * other.c + self.d * other.d,
+            e: self.a * other.e + self.c * other.f + self.e,
+            f: self.b * other.e + self.d * other.f + self.f,
+        }
+    }
+
+    /// A transform that applies `self` first and then `next`.
+    pub fn then(&self, next: &Transform) -> Transform {
+        next.multiply(self)
+    }
+
+    ///
+    pub fn apply(&self, point: Point) -> Point {
+        Point {
+            x: self.a * point.x + self.c * point.y + self.e,
+            y: self.b * point.x + self.d * point.y + self.f,
+        }
+    }
+
+    /// Apply the transform to a direction, ignoring the translation.
+    pub fn apply_vector(&self, x: f64, y: f64) -> (f64, f64) {
+        (self.a * x + self.c * y, self.b * x + self.d * y)
+    }
+
+    /// The factor areas are scaled by, square rooted, so it is exact for uniform scales and rotations
+    /// and a reasonable average for lengths otherwise.
+    pub fn scale_factor(&self) -> f64 {
+        (self.a * self.d - self.b * self.c).abs().sqrt()
+    }
+
+    /// The smallest factor any length is scaled by, which is the smaller singular value of the matrix.
+    pub fn min_scale(&self) -> f64 {
+        let sum = self.a * self.a + self.b * self.b + self.c * self.c + self.d * self.d;
+        let det = self.a * self.d - self.b * self.c;
+        let spread = (sum * sum - 4.0 * det * det).max(0.0).sqrt();
+
+        ((sum - spread) / 2.0).max(0.0).sqrt()
+    }
+
+    /// The transform that undoes this one, if it isn't degenerate.
+    pub fn inverse(&self) -> Option<Transform> {
+        let det = self.a * self.d - self.b * self.c;
+
+        if det.abs() < f64::EPSILON {
+            return None;
+        }
+
+        Some(Transform {
+            a: self.d / det,
+            b: -self.b / det,
+            c: -self.c / det,
+            d: self.a / det,
+            e: (self.c * self.f - self.d * self.e) / det,
+            f: (self.b * self.e - self.a * self.f) / det,
+        })
+    }
+
+    ///
+    pub fn is_identity(&self) -> bool {
+        *self == Transform::identity()
+    }
+
+    /// The `transform` attribute, which is omitted for the identity transform.
+    pub fn to_attrs(&self) -> Vec<(&'static str, String)> {
+        if self.is_identity() {
+            return vec![];
+        }
+
+        vec![("transform", format!("matrix({} {} {} {} {} {})", self.a, self.b, self.c, self.d, self.e, self.f))]
+    }
+
+    /// Parse an svg transform list such as `translate(10 20) rotate(45)`.
+    pub fn parse(value: &str) -> Result<Transform, String> {
+        let mut transform = Transform::identity();
+        let mut rest = value.trim_start_matches(|c: char| c.is_ascii_whitespace() || c == ',');
+
+        while !rest.is_empty() {
+            let (name, args) = match (rest.find('('), rest.find(')')) {
+                (Some(open), Some(close)) if open < close => (rest[..open].trim(), &rest[open + 1..close]),
+                _ => return Err(format!("Invalid transform \"{}\"", value)),
+            };
+
+            let args = parse_number_list(args).map_err(|e| format!("Invalid arguments to {}: {}", name, e))?;
+
+            let next = match (name, &args[..]) {
+                ("matrix", &[a, b, c, d, e, f]) => Transform::new(a, b, c, d, e, f),
+                ("translate", &[dx]) => Transform::translate(dx, 0.0),
+                ("translate", &[dx, dy]) => Transform::translate(dx, dy),
+                ("scale", &[s]) => Transform::scale(s, s),
+                ("scale", &[sx, sy]) => Transform::scale(sx, sy),
+                ("rotate", &[degrees]) => Transform::rotate(degrees),
+                ("rotate", &[degrees, cx, cy]) => Transform::rotate_about(degrees, cx, cy),
+                ("skewX", &[degrees]) => Transform::skew(degrees, 0.0),
+                ("skewY", &[degrees]) => Transform::skew(0.0, degrees),
+                _ => {
+                    let plural = if args.len() == 1 { "" } else { "s" };
+                    return Err(format!("Invalid transform function {} with {} argument{}", name, args.len(), plural));
+                },
+            };
+
+            // The functions in a list apply right to left
+            transform = transform.multiply(&next);
+            rest = rest[rest.find(')').unwrap() + 1..].trim_start_matches(|c: char| c.is_ascii_whitespace() || c == ',');
+        }
+
+        Ok(transform)
+    }
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Transform::identity()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verify each basic transform moves points as svg would.
+    #[test]
+    fn apply() {
+        let point = Point{ x: 2.0, y: 1.0 };
+
+        assert_eq!(Transform::identity().apply(point), Point{ x: 2.0, y: 1.0 });
+        assert_eq!(Transform::translate(3.0, -1.0).apply(point), Point{ x: 5.0, y: 0.0 });
+        assert_eq!(Transform::scale(2.0, 3.0).apply(point), Point{ x: 4.0, y: 3.0 });
+        assert_eq!(Transform::rotate(90.0).apply(point), Point{ x: -1.0, y: 2.0 });
+        assert_eq!(Transform::rotate_about(180.0, 1.0, 1.0).apply(point), Point{ x: 0.0, y: 1.0 });
+        assert_eq!(Transform::skew(45.0, 0.0).apply(point), Point{ x: 3.0, y: 1.0 });
+        assert_eq!(Transform::skew(0.0, 45.0).apply(point), Point{ x: 2.0, y: 3.0 });
+    }
+
+    /// Verify `then` applies transforms in order, and `inverse` undoes a transform.
+    #[test]
+    fn compose() {
+        let point = Point{ x: 2.0, y: 1.0 };
+        let transform = Transform::scale(2.0, 2.0).then(&Transform::translate(1.0, 0.0));
+        assert_eq!(transform.apply(point), Point{ x: 5.0, y: 2.0 });
+        assert_eq!(transform, Transform::translate(1.0, 0.0).multiply(&Transform::scale(2.0, 2.0)));
+
+        let inverse = transform.inverse().unwrap();
+        assert_eq!(inverse.apply(Point{ x: 5.0, y: 2.0 }), point);
+        assert!(transform.then(&inverse).is_identity());
+
+        assert_eq!(Transform::scale(0.0, 1.0).inverse(), None);
+    }
+
+    /// Verify the transform attribute is only written for non-identity transforms.
+    #[test]
+    fn to_attrs() {
+        assert_eq!(Transform::identity().to_attrs(), vec![]);
+        assert_eq!(Transform::rotate(90.0).to_attrs(), vec![("transform", String::from("matrix(0 1 -1 0 0 0)"))]);
+        assert_eq!(Transform::translate(1.5, -2.0).to_attrs(), vec![("transform", String::from("matrix(1 0 0 1 1.5 -2)"))]);
+    }
+
+    /// Verify transform lists are parsed, and composed right to left.
+    #[test]
+    fn parse() {
+        assert_eq!(Transform::parse("").unwrap(), Transform::identity());
+        assert_eq!(Transform::parse("matrix(1,2,3,4,5,6)").unwrap(), Transform::new(1.0, 2.0, 3.0, 4.0, 5.0, 6.0));
+        assert_eq!(Transform::parse("translate(10)").unwrap(), Transform::translate(10.0, 0.0));
+        assert_eq!(Transform::parse("scale(2)").unwrap(), Transform::scale(2.0, 2.0));
+        assert_eq!(Transform::parse("rotate(90 1 1)").unwrap(), Transform::rotate_about(90.0, 1.0, 1.0));
+        assert_eq!(Transform::parse("skewX(45) skewY(45)").unwrap(), Transform::new(1.0, 0.0, 1.0, 1.0, 0.0, 0.0).multiply(&Transform::new(1.0, 1.0, 0.0, 1.0, 0.0, 0.0)));
+
+        let transform = Transform::parse(" translate(10, 20) , scale(2 3) ").unwrap();
+        assert_eq!(transform.apply(Point{ x: 1.0, y: 1.0 }), Point{ x: 12.0, y: 23.0 });
+
+        let err = Transform::parse("scale(1 2 3)").unwrap_err();
+        assert_eq!(err, "Invalid transform function scale with 3 arguments");
+        let err = Transform::parse("spin(90)").unwrap_err();
+        assert_eq!(err, "Invalid transform function spin with 1 argument");
+        let err = Transform::parse("rotate(90").unwrap_err();
+        assert_eq!(err, "Invalid transform \"rotate(90\"");
+        let err = Transform::parse("rotate(a)").unwrap_err();
+        assert_eq!(err, "Invalid arguments to rotate: Expected a number but found 'a' at position 0");
+    }
+}